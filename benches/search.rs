@@ -0,0 +1,69 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use vault::{
+	db::{Db, DynField, DynFieldKind, NewDbEntry},
+	search::query::parse_query,
+};
+
+const ENTRY_COUNT: usize = 50_000;
+const INPUT: &str = "service 4242";
+
+// a vault big enough that a slow keystroke shows up
+fn large_db() -> Db {
+	Db::from_new_entries(
+		(0..ENTRY_COUNT)
+			.map(|id| NewDbEntry {
+				title: format!("Service number {}", id),
+				fields: vec![
+					DynField::new(
+						DynFieldKind::TextLine,
+						String::from("Username"),
+						vec![(0, format!("user{}@example.com", id))],
+					),
+					DynField::new(
+						DynFieldKind::TextLineSecret,
+						String::from("Password"),
+						vec![
+							(0, String::from("hunter2hunter2")),
+							(1, String::from("correct horse")),
+						],
+					),
+					DynField::new(
+						DynFieldKind::Url,
+						String::from("Website"),
+						vec![(0, format!("https://site{}.com", id))],
+					),
+					DynField::new(
+						DynFieldKind::TextLine,
+						String::from("Notes"),
+						vec![(0, String::from("old notes"))],
+					)
+					.with_visibility(false),
+				],
+				tags: Vec::new(),
+			})
+			.collect(),
+	)
+}
+
+// every prefix of the input is one keystroke in the search field
+fn typing(c: &mut Criterion) {
+	let db = large_db();
+	let mut group = c.benchmark_group("typing");
+
+	for deep in [false, true] {
+		for end in 1..=INPUT.len() {
+			let query = parse_query(&INPUT[..end]).unwrap();
+			group.bench_with_input(
+				BenchmarkId::new(if deep { "deep" } else { "shallow" }, &INPUT[..end]),
+				&query,
+				|b, query| b.iter(|| db.search(query, deep)),
+			);
+		}
+	}
+
+	group.finish();
+}
+
+criterion_group!(benches, typing);
+criterion_main!(benches);
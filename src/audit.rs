@@ -0,0 +1,443 @@
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+use crate::{
+	breach::{hash_hex, BreachError, BreachFile},
+	db::{AuditField, DbFields, DynFieldKind},
+	password_strength::{estimate, get_score_name},
+};
+
+// secrets scoring below this are reported as weak
+const WEAK_SCORE: u8 = 2;
+const SECONDS_PER_DAY: u64 = 60 * 60 * 24;
+// a plain value needs to look random to this degree to be taken for a password
+const PASSWORD_LIKE_SCORE: u8 = 3;
+const PASSWORD_LIKE_MIN_LENGTH: usize = 8;
+const PASSWORD_LIKE_MAX_LENGTH: usize = 64;
+
+// usernames are often stored as secrets but are meant to be reused
+const IDENTITY_WORDS: [&str; 6] =
+	["user", "username", "login", "email", "e-mail", "account"];
+const SECRET_WORDS: [&str; 8] = [
+	"pin", "pwd", "secret", "token", "key", "apikey", "otp", "totp",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FindingKind {
+	// how often the value appears in the breach file
+	Breached(u64),
+	// how many fields share this value
+	Reused(usize),
+	Weak(u8),
+	// how many days ago the value was last changed
+	Old(u64),
+	NotSecret,
+}
+
+impl std::fmt::Display for FindingKind {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match *self {
+			FindingKind::Breached(count) => write!(f, "Found in {} breaches", count),
+			FindingKind::Reused(count) => write!(f, "Reused in {} fields", count),
+			FindingKind::Weak(score) => write!(f, "{}", get_score_name(score)),
+			FindingKind::Old(days) => write!(f, "{} days old", days),
+			FindingKind::NotSecret => write!(f, "Not stored as secret"),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+	pub id: usize,
+	pub entry_title: String,
+	pub field: DbFields,
+	pub field_title: String,
+	pub kind: FindingKind,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditSummary {
+	pub breached: usize,
+	pub reused: usize,
+	pub weak: usize,
+	pub old: usize,
+	pub not_secret: usize,
+}
+
+impl AuditSummary {
+	pub fn new(findings: &[Finding]) -> Self {
+		let mut summary = AuditSummary::default();
+		for finding in findings {
+			match finding.kind {
+				FindingKind::Breached(_) => summary.breached += 1,
+				FindingKind::Reused(_) => summary.reused += 1,
+				FindingKind::Weak(_) => summary.weak += 1,
+				FindingKind::Old(_) => summary.old += 1,
+				FindingKind::NotSecret => summary.not_secret += 1,
+			}
+		}
+		summary
+	}
+}
+
+impl std::fmt::Display for AuditSummary {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		if *self == AuditSummary::default() {
+			write!(f, "No problems found")
+		} else {
+			if self.breached > 0 {
+				write!(f, "{} breached, ", self.breached)?;
+			}
+			write!(
+				f,
+				"{} reused, {} weak, {} old, {} not stored as secret",
+				self.reused, self.weak, self.old, self.not_secret
+			)
+		}
+	}
+}
+
+// PRIVATE: the lowercase words of a field title
+fn title_words(title: &str) -> Vec<String> {
+	title
+		.to_lowercase()
+		.split(|c: char| !c.is_alphanumeric() && c != '-')
+		.filter(|word| !word.is_empty())
+		.map(String::from)
+		.collect()
+}
+
+fn is_identity_field(title: &str) -> bool {
+	title_words(title).iter().any(|word| IDENTITY_WORDS.contains(&word.as_str()))
+}
+
+fn is_secret_title(title: &str) -> bool {
+	title_words(title).iter().any(|word| {
+		SECRET_WORDS.contains(&word.as_str()) || word.starts_with("pass")
+	})
+}
+
+fn is_secret(field: &AuditField) -> bool {
+	matches!(
+		field.kind,
+		DynFieldKind::TextLineSecret | DynFieldKind::MultiLineSecret
+	) && !field.value.is_empty()
+		&& !is_identity_field(&field.field_title)
+}
+
+// a single line plain field that is either named like a password or holds a
+// value that looks like a random password
+pub fn looks_like_password(field: &AuditField) -> bool {
+	if field.kind != DynFieldKind::TextLine
+		|| field.value.is_empty()
+		|| is_identity_field(&field.field_title)
+	{
+		return false;
+	}
+
+	if is_secret_title(&field.field_title) {
+		return true;
+	}
+
+	let length = field.value.chars().count();
+	let classes = [
+		field.value.chars().any(|c| c.is_lowercase()),
+		field.value.chars().any(|c| c.is_uppercase()),
+		field.value.chars().any(|c| c.is_ascii_digit()),
+		field.value.chars().any(|c| !c.is_alphanumeric()),
+	]
+	.iter()
+	.filter(|has_class| **has_class)
+	.count();
+
+	(PASSWORD_LIKE_MIN_LENGTH..=PASSWORD_LIKE_MAX_LENGTH).contains(&length)
+		&& !field.value.chars().any(char::is_whitespace)
+		&& !field.value.contains('@')
+		&& classes >= 3
+		&& estimate(&field.value).score >= PASSWORD_LIKE_SCORE
+}
+
+fn finding(field: &AuditField, kind: FindingKind) -> Finding {
+	Finding {
+		id: field.id,
+		entry_title: field.entry_title.clone(),
+		field: field.field,
+		field_title: field.field_title.clone(),
+		kind,
+	}
+}
+
+// reused secrets are found by comparing hashes which are zeroized once grouped
+fn reused_findings(fields: &[AuditField]) -> Vec<Finding> {
+	let mut hashes = fields
+		.iter()
+		.enumerate()
+		.filter(|(_, field)| is_secret(field))
+		.map(|(idx, field)| {
+			let hash: [u8; 32] = Sha256::digest(field.value.as_bytes()).into();
+			(hash, idx)
+		})
+		.collect::<Vec<([u8; 32], usize)>>();
+	hashes.sort();
+
+	let mut findings = Vec::new();
+	for group in hashes.chunk_by(|a, b| a.0 == b.0) {
+		if group.len() > 1 {
+			for (_, idx) in group {
+				findings.push(finding(&fields[*idx], FindingKind::Reused(group.len())));
+			}
+		}
+	}
+
+	for (hash, idx) in hashes.iter_mut() {
+		hash.zeroize();
+		idx.zeroize();
+	}
+
+	findings
+}
+
+// check all fields and zeroize their values afterwards
+pub fn run_audit(
+	mut fields: Vec<AuditField>,
+	max_age_days: u64,
+	now: u64,
+) -> Vec<Finding> {
+	let mut findings = reused_findings(&fields);
+
+	for field in &fields {
+		if is_secret(field) {
+			if field.kind == DynFieldKind::TextLineSecret {
+				let score = estimate(&field.value).score;
+				if score < WEAK_SCORE {
+					findings.push(finding(field, FindingKind::Weak(score)));
+				}
+			}
+
+			let age_days = now.saturating_sub(field.date) / SECONDS_PER_DAY;
+			if max_age_days > 0 && age_days >= max_age_days {
+				findings.push(finding(field, FindingKind::Old(age_days)));
+			}
+		} else if looks_like_password(field) {
+			findings.push(finding(field, FindingKind::NotSecret));
+		}
+	}
+
+	for field in fields.iter_mut() {
+		field.zeroize();
+	}
+
+	sort_findings(&mut findings);
+	findings
+}
+
+// look up every single line secret in the breach file and zeroize the values
+// and their hashes afterwards
+pub fn run_breach_audit(
+	mut fields: Vec<AuditField>,
+	breach_file: &mut BreachFile,
+) -> Result<Vec<Finding>, BreachError> {
+	let mut findings = Vec::new();
+	let mut result = Ok(());
+
+	for field in &fields {
+		if is_secret(field) && field.kind == DynFieldKind::TextLineSecret {
+			let mut hash = hash_hex(&field.value);
+			let lookup = breach_file.lookup(&hash);
+			hash.zeroize();
+
+			match lookup {
+				Ok(Some(count)) => {
+					findings.push(finding(field, FindingKind::Breached(count)))
+				},
+				Ok(None) => {},
+				Err(error) => {
+					result = Err(error);
+					break;
+				},
+			}
+		}
+	}
+
+	for field in fields.iter_mut() {
+		field.zeroize();
+	}
+
+	result?;
+	sort_findings(&mut findings);
+	Ok(findings)
+}
+
+pub fn sort_findings(findings: &mut [Finding]) {
+	findings.sort_by(|a, b| {
+		a.entry_title
+			.to_lowercase()
+			.cmp(&b.entry_title.to_lowercase())
+			.then(a.field_title.cmp(&b.field_title))
+	});
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const NOW: u64 = 1_700_000_000;
+
+	fn field(
+		id: usize,
+		kind: DynFieldKind,
+		title: &str,
+		value: &str,
+		days_old: u64,
+	) -> AuditField {
+		AuditField {
+			id,
+			entry_title: format!("Entry {}", id),
+			field: DbFields::Fields(title.len()),
+			field_title: String::from(title),
+			kind,
+			value: String::from(value),
+			date: NOW - days_old * SECONDS_PER_DAY,
+		}
+	}
+
+	fn secret(id: usize, title: &str, value: &str, days_old: u64) -> AuditField {
+		field(id, DynFieldKind::TextLineSecret, title, value, days_old)
+	}
+
+	#[test]
+	fn reused_test() {
+		let findings = run_audit(
+			vec![
+				secret(1, "Password", "xK9#mQ2$vL7!", 1),
+				secret(2, "Password", "xK9#mQ2$vL7!", 1),
+				secret(3, "Password", "pR4!tW8%nB3&", 1),
+				secret(1, "Username", "dominik", 1),
+				secret(2, "Username", "dominik", 1),
+			],
+			0,
+			NOW,
+		);
+
+		assert_eq!(findings.len(), 2);
+		assert!(findings
+			.iter()
+			.all(|finding| finding.kind == FindingKind::Reused(2)));
+		assert_eq!(findings[0].id, 1);
+		assert_eq!(findings[1].id, 2);
+	}
+
+	#[test]
+	fn weak_and_old_test() {
+		let findings = run_audit(
+			vec![
+				secret(1, "Password", "password1", 1),
+				secret(2, "Password", "xK9#mQ2$vL7!", 400),
+				field(3, DynFieldKind::MultiLineSecret, "Notes", "my notes", 10),
+			],
+			365,
+			NOW,
+		);
+
+		assert_eq!(findings.len(), 2);
+		assert_eq!(findings[0].kind, FindingKind::Weak(0));
+		assert_eq!(findings[1].kind, FindingKind::Old(400));
+
+		let findings =
+			run_audit(vec![secret(2, "Password", "xK9#mQ2$vL7!", 400)], 0, NOW);
+		assert!(findings.is_empty());
+	}
+
+	#[test]
+	fn not_secret_test() {
+		let plain = |title: &str, value: &str| {
+			looks_like_password(&field(1, DynFieldKind::TextLine, title, value, 1))
+		};
+
+		assert!(plain("Password", "hunter2"));
+		assert!(plain("API key", "abc"));
+		assert!(plain("Passcode", "1234"));
+		assert!(plain("Notes", "xK9#mQ2$vL7!pR4%"));
+		assert!(!plain("Notes", "Remember the milk"));
+		assert!(!plain("Notes", "Password1"));
+		assert!(!plain("Username", "xK9#mQ2$vL7!pR4%"));
+		assert!(!plain("Email", "dom@example.com"));
+		assert!(!plain("Password", ""));
+		assert!(!looks_like_password(&field(
+			1,
+			DynFieldKind::Url,
+			"Password",
+			"https://example.com",
+			1
+		)));
+
+		let findings = run_audit(
+			vec![field(1, DynFieldKind::TextLine, "PIN", "1234", 1)],
+			365,
+			NOW,
+		);
+		assert_eq!(findings.len(), 1);
+		assert_eq!(findings[0].kind, FindingKind::NotSecret);
+	}
+
+	#[test]
+	fn summary_test() {
+		assert_eq!(AuditSummary::new(&[]).to_string(), "No problems found");
+
+		let findings = run_audit(
+			vec![
+				secret(1, "Password", "123456", 500),
+				secret(2, "Password", "123456", 1),
+			],
+			365,
+			NOW,
+		);
+		assert_eq!(
+			AuditSummary::new(&findings),
+			AuditSummary {
+				breached: 0,
+				reused: 2,
+				weak: 2,
+				old: 1,
+				not_secret: 0,
+			}
+		);
+	}
+
+	#[test]
+	fn breach_test() {
+		use std::io::Write;
+
+		let mut hashes = ["123456", "password"]
+			.iter()
+			.map(|value| String::from_utf8(hash_hex(value).to_vec()).unwrap())
+			.collect::<Vec<String>>();
+		hashes.sort();
+		let path = std::env::temp_dir().join("vault_audit_breach_test.txt");
+		let mut file = std::fs::File::create(&path).unwrap();
+		for hash in hashes {
+			writeln!(file, "{}:1000", hash).unwrap();
+		}
+
+		let mut breach_file = BreachFile::open(&path).unwrap();
+		let findings = run_breach_audit(
+			vec![
+				secret(1, "Password", "password", 1),
+				secret(2, "Password", "xK9#mQ2$vL7!", 1),
+				field(3, DynFieldKind::TextLine, "Notes", "123456", 1),
+			],
+			&mut breach_file,
+		)
+		.unwrap();
+
+		assert_eq!(findings.len(), 1);
+		assert_eq!(findings[0].id, 1);
+		assert_eq!(findings[0].kind, FindingKind::Breached(1000));
+		assert_eq!(
+			AuditSummary::new(&findings).to_string(),
+			"1 breached, 0 reused, 0 weak, 0 old, 0 not stored as secret"
+		);
+
+		let _ = std::fs::remove_file(path);
+	}
+}
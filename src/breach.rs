@@ -0,0 +1,226 @@
+use sha1::{Digest, Sha1};
+use std::{
+	fs::File,
+	io::{BufRead, BufReader, Seek, SeekFrom},
+	path::Path,
+};
+use zeroize::Zeroize;
+
+const HASH_HEX_LENGTH: usize = 40;
+
+#[derive(thiserror::Error, Debug)]
+pub enum BreachError {
+	#[error("Failed to read the breach file: {0}")]
+	Io(#[from] std::io::Error),
+	#[error("The breach file is not a list of SHA-1 hashes")]
+	Format,
+}
+
+// the uppercase hex sha-1 of a value as it appears in the breach file
+pub fn hash_hex(value: &str) -> [u8; HASH_HEX_LENGTH] {
+	const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+	let mut hash: [u8; 20] = Sha1::digest(value.as_bytes()).into();
+	let mut hex = [0u8; HASH_HEX_LENGTH];
+	for (i, byte) in hash.iter().enumerate() {
+		hex[i * 2] = HEX[(byte >> 4) as usize];
+		hex[i * 2 + 1] = HEX[(byte & 0x0F) as usize];
+	}
+
+	hash.zeroize();
+	hex
+}
+
+// the Have I Been Pwned file sorted by hash with one "HASH:COUNT" per line
+pub struct BreachFile {
+	reader: BufReader<File>,
+	length: u64,
+}
+
+impl BreachFile {
+	pub fn open(path: impl AsRef<Path>) -> Result<Self, BreachError> {
+		let file = File::open(path)?;
+		let length = file.metadata()?.len();
+		let mut breach_file = BreachFile {
+			reader: BufReader::new(file),
+			length,
+		};
+
+		let mut line = Vec::new();
+		breach_file.read_line_at(0, &mut line)?;
+		if length > 0 && parse_line(&line).is_none() {
+			return Err(BreachError::Format);
+		}
+
+		Ok(breach_file)
+	}
+
+	// PRIVATE: read the line that starts at the offset into the buffer
+	fn read_line_at(
+		&mut self,
+		offset: u64,
+		line: &mut Vec<u8>,
+	) -> Result<usize, BreachError> {
+		line.zeroize();
+		line.clear();
+		self.reader.seek(SeekFrom::Start(offset))?;
+		Ok(self.reader.read_until(b'\n', line)?)
+	}
+
+	// PRIVATE: the offset of the first line starting at or after the offset
+	fn next_line_start(&mut self, offset: u64) -> Result<u64, BreachError> {
+		if offset == 0 {
+			return Ok(0);
+		}
+
+		let mut skipped = Vec::new();
+		let read = self.read_line_at(offset - 1, &mut skipped)?;
+		Ok(offset - 1 + read as u64)
+	}
+
+	// binary search over byte offsets, each step reads the first full line after
+	// the middle so we never need to load the file
+	pub fn lookup(
+		&mut self,
+		hash: &[u8; HASH_HEX_LENGTH],
+	) -> Result<Option<u64>, BreachError> {
+		let mut low = 0;
+		let mut high = self.length;
+		let mut line = Vec::new();
+		let mut result = None;
+
+		while low < high {
+			let middle = low + (high - low) / 2;
+			let start = self.next_line_start(middle)?;
+			if start >= high {
+				high = middle;
+				continue;
+			}
+
+			let read = self.read_line_at(start, &mut line)?;
+			let (line_hash, count) = parse_line(&line).ok_or(BreachError::Format)?;
+			match line_hash.cmp(&hash[..]) {
+				std::cmp::Ordering::Equal => {
+					result = Some(count);
+					break;
+				},
+				std::cmp::Ordering::Less => low = start + read as u64,
+				std::cmp::Ordering::Greater => high = middle,
+			}
+		}
+
+		line.zeroize();
+		Ok(result)
+	}
+}
+
+// PRIVATE: split a "HASH:COUNT" line, the count is optional
+fn parse_line(line: &[u8]) -> Option<(&[u8], u64)> {
+	let line = line.trim_ascii_end();
+	if line.len() < HASH_HEX_LENGTH
+		|| !line[..HASH_HEX_LENGTH].iter().all(|c| c.is_ascii_hexdigit())
+		|| line[..HASH_HEX_LENGTH].iter().any(|c| c.is_ascii_lowercase())
+	{
+		return None;
+	}
+
+	let count = match &line[HASH_HEX_LENGTH..] {
+		[] => 1,
+		[b':', count @ ..] => std::str::from_utf8(count).ok()?.parse().ok()?,
+		_ => return None,
+	};
+
+	Some((&line[..HASH_HEX_LENGTH], count))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::io::Write;
+
+	fn hex_string(value: &str) -> String {
+		String::from_utf8(hash_hex(value).to_vec()).unwrap()
+	}
+
+	fn write_breach_file(name: &str, lines: &[String]) -> std::path::PathBuf {
+		let path = std::env::temp_dir().join(name);
+		let mut file = File::create(&path).unwrap();
+		file.write_all(lines.join("").as_bytes()).unwrap();
+		path
+	}
+
+	#[test]
+	fn sha1_test() {
+		assert_eq!(hex_string(""), "DA39A3EE5E6B4B0D3255BFEF95601890AFD80709");
+		assert_eq!(hex_string("abc"), "A9993E364706816ABA3E25717850C26C9CD0D89D");
+		assert_eq!(
+			hex_string("abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+			"84983E441C3BD26EBAAE4AA1F95129E5E54670F1"
+		);
+		assert_eq!(
+			hex_string("password"),
+			"5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8"
+		);
+		assert_eq!(
+			hex_string(&"a".repeat(1000)),
+			"291E9A6C66994949B57BA5E650361E98FC36B1BA"
+		);
+	}
+
+	#[test]
+	fn lookup_test() {
+		let mut values = (0..500)
+			.map(|i| (hex_string(&format!("breached-{}", i)), i as u64 + 1))
+			.collect::<Vec<(String, u64)>>();
+		values.sort();
+		let lines = values
+			.iter()
+			.enumerate()
+			.map(|(idx, (hash, count))| {
+				// the published file uses crlf line endings
+				let ending = if idx % 2 == 0 { "\r\n" } else { "\n" };
+				format!("{}:{}{}", hash, count, ending)
+			})
+			.collect::<Vec<String>>();
+		let path = write_breach_file("vault_breach_lookup_test.txt", &lines);
+		let mut breach_file = BreachFile::open(&path).unwrap();
+
+		for i in 0..500 {
+			assert_eq!(
+				breach_file.lookup(&hash_hex(&format!("breached-{}", i))).unwrap(),
+				Some(i as u64 + 1)
+			);
+		}
+		assert_eq!(breach_file.lookup(&hash_hex("not breached")).unwrap(), None);
+		assert_eq!(breach_file.lookup(&[b'0'; 40]).unwrap(), None);
+		assert_eq!(breach_file.lookup(&[b'F'; 40]).unwrap(), None);
+
+		let _ = std::fs::remove_file(path);
+	}
+
+	#[test]
+	fn format_test() {
+		let path = write_breach_file(
+			"vault_breach_format_test.txt",
+			&[String::from("not a hash file\n")],
+		);
+		assert!(matches!(BreachFile::open(&path), Err(BreachError::Format)));
+		let _ = std::fs::remove_file(path);
+
+		let path = write_breach_file("vault_breach_empty_test.txt", &[]);
+		let mut breach_file = BreachFile::open(&path).unwrap();
+		assert_eq!(breach_file.lookup(&hash_hex("password")).unwrap(), None);
+		let _ = std::fs::remove_file(path);
+
+		assert_eq!(
+			parse_line(b"5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:42\r\n"),
+			Some((&b"5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8"[..], 42))
+		);
+		assert_eq!(
+			parse_line(b"5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8"),
+			Some((&b"5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8"[..], 1))
+		);
+		assert_eq!(parse_line(b"5baa61e4c9b93f3f0682250b6cf8331b7ee68fd8:1"), None);
+		assert!(BreachFile::open("/does/not/exist").is_err());
+	}
+}
@@ -16,6 +16,10 @@ pub const CONFIG_FILE_NAME: &str = "vault_config.toml";
 pub const DB_FILE_NAME: &str = "vault_db.toml";
 const SIDEBAR_WIDTH: f64 = 140.0;
 const WINDOW_SIZE: (f64, f64) = (800.0, 350.0);
+const PASS_GEN_LETTER_COUNT: usize = 22;
+const MIN_MASTER_STRENGTH: u8 = 2;
+const MAX_SECRET_AGE_DAYS: u64 = 365;
+const CLIPBOARD_TIMEOUT: u64 = 30;
 
 #[derive(Debug, Deserialize, Serialize)]
 struct ConfigFile {
@@ -58,7 +62,25 @@ mod arc_rwlock_serde {
 pub struct ConfigGeneral {
 	pub db_timeout: f32,
 	pub db_path: String,
-	pub pass_gen_letter_count: usize,
+	// the lowest password strength score (0-4) accepted for the master password
+	#[serde(default = "default_min_master_strength")]
+	pub min_master_strength: u8,
+	// the audit flags secrets older than this, 0 turns the check off
+	#[serde(default = "default_max_secret_age_days")]
+	pub max_secret_age_days: u64,
+	// the sorted sha-1 file from Have I Been Pwned the audit checks against
+	#[serde(default)]
+	pub breach_file_path: String,
+	// seconds after which a copied secret is cleared from the clipboard, 0 keeps it
+	#[serde(default = "default_clipboard_timeout")]
+	pub clipboard_timeout: u64,
+	// only read to carry the length over from configs without generator profiles
+	#[serde(default, skip_serializing)]
+	pass_gen_letter_count: Option<usize>,
+	#[serde(default)]
+	pub generator_profiles: GeneratorProfiles,
+	#[serde(default)]
+	pub generator_rule_presets: GeneratorRulePresets,
 	pub window_settings: WindowSettings,
 	pub preset_fields: PresetFields,
 	pub shortcuts: Shortcuts,
@@ -73,6 +95,188 @@ pub struct Shortcuts {
 
 pub type PresetFields = Vec<(usize, String, String, DynFieldKind)>;
 
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GeneratorMode {
+	#[default]
+	Characters,
+	Passphrase,
+}
+
+impl std::fmt::Display for GeneratorMode {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match *self {
+			GeneratorMode::Characters => write!(f, "Characters"),
+			GeneratorMode::Passphrase => write!(f, "Passphrase"),
+		}
+	}
+}
+
+impl GeneratorMode {
+	pub fn all_values() -> Vec<GeneratorMode> {
+		vec![GeneratorMode::Characters, GeneratorMode::Passphrase]
+	}
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Capitalization {
+	Lowercase,
+	#[default]
+	FirstLetter,
+	// each word is randomly capitalized which adds a bit of entropy per word
+	Random,
+}
+
+impl std::fmt::Display for Capitalization {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match *self {
+			Capitalization::Lowercase => write!(f, "lowercase"),
+			Capitalization::FirstLetter => write!(f, "Capitalized"),
+			Capitalization::Random => write!(f, "rAndOm"),
+		}
+	}
+}
+
+impl Capitalization {
+	pub fn all_values() -> Vec<Capitalization> {
+		vec![
+			Capitalization::Lowercase,
+			Capitalization::FirstLetter,
+			Capitalization::Random,
+		]
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PassphraseOptions {
+	pub word_count: usize,
+	pub separator: String,
+	pub capitalization: Capitalization,
+	pub add_digit: bool,
+	pub add_symbol: bool,
+}
+
+impl Default for PassphraseOptions {
+	fn default() -> Self {
+		PassphraseOptions {
+			word_count: 6,
+			separator: String::from("-"),
+			capitalization: Capitalization::default(),
+			add_digit: true,
+			add_symbol: false,
+		}
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeneratorProfile {
+	pub id: usize,
+	pub name: String,
+	#[serde(default)]
+	pub mode: GeneratorMode,
+	pub length: usize,
+	pub upper: bool,
+	pub lower: bool,
+	pub digits: bool,
+	pub symbols: bool,
+	// replaces the default symbols when not empty
+	pub custom_symbols: String,
+	pub exclude_look_alikes: bool,
+	pub at_least_one_of_each: bool,
+	#[serde(default)]
+	pub passphrase: PassphraseOptions,
+}
+
+pub type GeneratorProfiles = Vec<GeneratorProfile>;
+
+impl Default for GeneratorProfile {
+	fn default() -> Self {
+		GeneratorProfile {
+			id: 0,
+			name: String::from("Default"),
+			mode: GeneratorMode::default(),
+			length: PASS_GEN_LETTER_COUNT,
+			upper: true,
+			lower: true,
+			digits: true,
+			symbols: true,
+			custom_symbols: String::from(""),
+			exclude_look_alikes: false,
+			at_least_one_of_each: true,
+			passphrase: PassphraseOptions::default(),
+		}
+	}
+}
+
+// constraints a site puts on its passwords
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeneratorRule {
+	pub min_length: usize,
+	// 0 means there is no maximum
+	pub max_length: usize,
+	pub require_upper: bool,
+	pub require_lower: bool,
+	pub require_digits: bool,
+	pub require_symbols: bool,
+	pub forbidden: String,
+	pub no_repeats: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeneratorRulePreset {
+	pub id: usize,
+	pub name: String,
+	// entries with a url on one of these domains use this rule automatically
+	pub domains: Vec<String>,
+	pub rule: GeneratorRule,
+}
+
+pub type GeneratorRulePresets = Vec<GeneratorRulePreset>;
+
+fn default_min_master_strength() -> u8 {
+	MIN_MASTER_STRENGTH
+}
+
+fn default_max_secret_age_days() -> u64 {
+	MAX_SECRET_AGE_DAYS
+}
+
+fn default_clipboard_timeout() -> u64 {
+	CLIPBOARD_TIMEOUT
+}
+
+fn default_generator_profiles(length: usize) -> GeneratorProfiles {
+	vec![
+		GeneratorProfile {
+			length,
+			..GeneratorProfile::default()
+		},
+		GeneratorProfile {
+			id: 1,
+			name: String::from("Alphanumeric"),
+			length: 16,
+			symbols: false,
+			exclude_look_alikes: true,
+			..GeneratorProfile::default()
+		},
+		GeneratorProfile {
+			id: 2,
+			name: String::from("PIN"),
+			length: 6,
+			upper: false,
+			lower: false,
+			symbols: false,
+			at_least_one_of_each: false,
+			..GeneratorProfile::default()
+		},
+		GeneratorProfile {
+			id: 3,
+			name: String::from("Passphrase"),
+			mode: GeneratorMode::Passphrase,
+			..GeneratorProfile::default()
+		},
+	]
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct WindowSettings {
 	pub sidebar_width: f64,
@@ -99,7 +303,13 @@ impl Default for Config {
 		Config {
 			general: Arc::new(RwLock::new(ConfigGeneral {
 				db_timeout: 900.0,
-				pass_gen_letter_count: 22,
+				min_master_strength: MIN_MASTER_STRENGTH,
+				max_secret_age_days: MAX_SECRET_AGE_DAYS,
+				breach_file_path: String::new(),
+				clipboard_timeout: CLIPBOARD_TIMEOUT,
+				pass_gen_letter_count: None,
+				generator_profiles: default_generator_profiles(PASS_GEN_LETTER_COUNT),
+				generator_rule_presets: Vec::new(),
 				db_path: db_path.into_os_string().to_string_lossy().to_string(),
 				window_settings: WindowSettings::default(),
 				preset_fields: vec![
@@ -148,7 +358,23 @@ impl From<ConfigFile> for Config {
 			general: Arc::new(RwLock::new(ConfigGeneral {
 				db_timeout: config_file.general.db_timeout,
 				db_path: config_file.general.db_path,
-				pass_gen_letter_count: config_file.general.pass_gen_letter_count,
+				min_master_strength: config_file.general.min_master_strength,
+				max_secret_age_days: config_file.general.max_secret_age_days,
+				breach_file_path: config_file.general.breach_file_path,
+				clipboard_timeout: config_file.general.clipboard_timeout,
+				pass_gen_letter_count: None,
+				generator_profiles: if config_file.general.generator_profiles.is_empty()
+				{
+					default_generator_profiles(
+						config_file
+							.general
+							.pass_gen_letter_count
+							.unwrap_or(PASS_GEN_LETTER_COUNT),
+					)
+				} else {
+					config_file.general.generator_profiles
+				},
+				generator_rule_presets: config_file.general.generator_rule_presets,
 				window_settings: WindowSettings {
 					sidebar_width: config_file.general.window_settings.sidebar_width,
 					window_size: config_file.general.window_settings.window_size,
@@ -263,6 +489,171 @@ impl Config {
 		self.get_field_presets()
 	}
 
+	pub fn get_generator_profiles(&self) -> GeneratorProfiles {
+		self.general.read().generator_profiles.clone()
+	}
+
+	// get a profile by id and fall back to the first one
+	pub fn get_generator_profile(&self, id: usize) -> GeneratorProfile {
+		let profiles = self.get_generator_profiles();
+		profiles
+			.iter()
+			.find(|profile| profile.id == id)
+			.or(profiles.first())
+			.cloned()
+			.unwrap_or_default()
+	}
+
+	// the first passphrase profile or the default passphrase options
+	pub fn get_passphrase_options(&self) -> PassphraseOptions {
+		self
+			.get_generator_profiles()
+			.into_iter()
+			.find(|profile| profile.mode == GeneratorMode::Passphrase)
+			.map(|profile| profile.passphrase)
+			.unwrap_or_default()
+	}
+
+	pub fn add_generator_profile(
+		&self,
+		profile: GeneratorProfile,
+	) -> GeneratorProfiles {
+		{
+			let id = self
+				.general
+				.read()
+				.generator_profiles
+				.iter()
+				.map(|item| item.id)
+				.max()
+				.unwrap_or(0)
+				+ 1;
+			self
+				.general
+				.write()
+				.generator_profiles
+				.push(GeneratorProfile { id, ..profile });
+		}
+
+		self.get_generator_profiles()
+	}
+
+	pub fn edit_generator_profile(
+		&self,
+		profile: GeneratorProfile,
+	) -> GeneratorProfiles {
+		if let Some(item) = self
+			.general
+			.write()
+			.generator_profiles
+			.iter_mut()
+			.find(|item| item.id == profile.id)
+		{
+			*item = profile;
+		}
+
+		self.get_generator_profiles()
+	}
+
+	// the default profile with id 0 can't be deleted
+	pub fn delete_generator_profile(&self, id: usize) -> GeneratorProfiles {
+		if id != 0 {
+			self.general.write().generator_profiles.retain(|item| item.id != id);
+		}
+
+		self.get_generator_profiles()
+	}
+
+	pub fn get_generator_rule_presets(&self) -> GeneratorRulePresets {
+		self.general.read().generator_rule_presets.clone()
+	}
+
+	pub fn add_generator_rule_preset(
+		&self,
+		preset: GeneratorRulePreset,
+	) -> GeneratorRulePresets {
+		{
+			let id = self
+				.general
+				.read()
+				.generator_rule_presets
+				.iter()
+				.map(|item| item.id)
+				.max()
+				.unwrap_or(0)
+				+ 1;
+			self
+				.general
+				.write()
+				.generator_rule_presets
+				.push(GeneratorRulePreset { id, ..preset });
+		}
+
+		self.get_generator_rule_presets()
+	}
+
+	pub fn edit_generator_rule_preset(
+		&self,
+		preset: GeneratorRulePreset,
+	) -> GeneratorRulePresets {
+		if let Some(item) = self
+			.general
+			.write()
+			.generator_rule_presets
+			.iter_mut()
+			.find(|item| item.id == preset.id)
+		{
+			*item = preset;
+		}
+
+		self.get_generator_rule_presets()
+	}
+
+	pub fn delete_generator_rule_preset(
+		&self,
+		id: usize,
+	) -> GeneratorRulePresets {
+		self.general.write().generator_rule_presets.retain(|item| item.id != id);
+
+		self.get_generator_rule_presets()
+	}
+
+	pub fn get_min_master_strength(&self) -> u8 {
+		self.general.read().min_master_strength
+	}
+
+	pub fn set_min_master_strength(&self, score: u8) {
+		self.general.write().min_master_strength = score;
+		let _ = self.save();
+	}
+
+	pub fn get_max_secret_age_days(&self) -> u64 {
+		self.general.read().max_secret_age_days
+	}
+
+	pub fn set_max_secret_age_days(&self, days: u64) {
+		self.general.write().max_secret_age_days = days;
+		let _ = self.save();
+	}
+
+	pub fn get_breach_file_path(&self) -> String {
+		self.general.read().breach_file_path.clone()
+	}
+
+	pub fn set_breach_file_path(&self, path: String) {
+		self.general.write().breach_file_path = path;
+		let _ = self.save();
+	}
+
+	pub fn get_clipboard_timeout(&self) -> u64 {
+		self.general.read().clipboard_timeout
+	}
+
+	pub fn set_clipboard_timeout(&self, seconds: u64) {
+		self.general.write().clipboard_timeout = seconds;
+		let _ = self.save();
+	}
+
 	pub fn edit_shortcuts(&self, shortcuts: Shortcuts) {
 		self.general.write().shortcuts = shortcuts;
 		let _ = self.save();
@@ -278,3 +669,68 @@ impl Config {
 		let _ = self.save();
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn generator_profiles_test() {
+		let config = Config::default();
+		let names = |profiles: GeneratorProfiles| {
+			profiles
+				.into_iter()
+				.map(|profile| (profile.id, profile.name))
+				.collect::<Vec<(usize, String)>>()
+		};
+		assert_eq!(
+			names(config.get_generator_profiles()),
+			vec![
+				(0, String::from("Default")),
+				(1, String::from("Alphanumeric")),
+				(2, String::from("PIN")),
+				(3, String::from("Passphrase"))
+			]
+		);
+		assert_eq!(config.get_generator_profile(2).length, 6);
+		assert_eq!(config.get_generator_profile(99).id, 0);
+		assert_eq!(
+			config.get_passphrase_options(),
+			config.get_generator_profile(3).passphrase
+		);
+
+		let profiles = config.add_generator_profile(GeneratorProfile {
+			id: 0,
+			name: String::from("Wifi"),
+			length: 63,
+			..GeneratorProfile::default()
+		});
+		assert_eq!(profiles.last().map(|profile| profile.id), Some(4));
+		assert_eq!(config.get_generator_profile(4).length, 63);
+
+		config.edit_generator_profile(GeneratorProfile {
+			name: String::from("Router"),
+			..config.get_generator_profile(4)
+		});
+		assert_eq!(config.get_generator_profile(4).name, "Router");
+		assert_eq!(config.get_generator_profile(4).length, 63);
+
+		// the default profile always stays
+		assert_eq!(config.delete_generator_profile(0).len(), 5);
+		assert_eq!(config.delete_generator_profile(4).len(), 4);
+		assert_eq!(config.get_generator_profile(4).id, 0);
+	}
+
+	#[test]
+	fn generator_profiles_migration_test() {
+		let mut file = toml::Value::try_from(Config::default()).unwrap();
+		let general = file.get_mut("general").unwrap().as_table_mut().unwrap();
+		general.remove("generator_profiles");
+		general.insert(String::from("pass_gen_letter_count"), 24.into());
+
+		let config: Config = file.try_into::<ConfigFile>().unwrap().into();
+		assert_eq!(config.get_generator_profiles().len(), 4);
+		assert_eq!(config.get_generator_profile(0).length, 24);
+		assert_eq!(config.get_generator_profile(1).length, 16);
+	}
+}
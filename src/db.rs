@@ -2,6 +2,7 @@ use anyhow::bail;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::{
+	collections::HashMap,
 	fs,
 	io::Write,
 	path::PathBuf,
@@ -13,15 +14,22 @@ use zeroize::Zeroize;
 use floem::reactive::{use_context, SignalUpdate};
 
 use crate::{
-	config::DB_FILE_NAME,
+	config::{GeneratorRule, DB_FILE_NAME},
 	db::ChangeError::WrongPassword,
 	encryption::{decrypt_vault, encrypt_vault, password_hash, CryptError},
 	env::Environment,
+	expiry::{get_status, DueItem, Expiry, ExpiryStatus},
 	password_gen::get_random_string,
+	search::{
+		fuzzy::Haystack,
+		index::{IndexedEntry, IndexedField, SearchIndex},
+		query::Query,
+	},
 	ui::app_view::SidebarList,
 };
 
 const SALT_LETTER_COUNT: usize = 32;
+const MAX_REFERENCE_DEPTH: usize = 8;
 
 type SecureField = (u64, String);
 
@@ -40,9 +48,37 @@ pub struct DynField {
 	title: String,
 	visible: bool,
 	value: Vec<SecureField>,
+	// the generator profile last used on this field
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	generator_profile: Option<usize>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	expiry: Option<Expiry>,
 }
 
 impl DynField {
+	// a field of an entry that isn't in the db yet, the id is given once it's added
+	pub fn new(
+		kind: DynFieldKind,
+		title: String,
+		value: Vec<SecureField>,
+	) -> Self {
+		Self {
+			id: 0,
+			kind,
+			title,
+			visible: true,
+			value,
+			generator_profile: None,
+			expiry: None,
+		}
+	}
+
+	// hidden fields stay hidden when they're imported
+	pub fn with_visibility(mut self, visible: bool) -> Self {
+		self.visible = visible;
+		self
+	}
+
 	pub fn zeroize(&mut self) {
 		self.id.zeroize();
 		self.title.zeroize();
@@ -65,6 +101,7 @@ pub enum DynFieldKind {
 	MultiLineSecret,
 	Url,
 	Heading,
+	Reference,
 }
 
 impl std::fmt::Display for DynFieldKind {
@@ -76,11 +113,27 @@ impl std::fmt::Display for DynFieldKind {
 			DynFieldKind::MultiLine => write!(f, "Multi Line"),
 			DynFieldKind::Url => write!(f, "Url"),
 			DynFieldKind::Heading => write!(f, "Heading"),
+			DynFieldKind::Reference => write!(f, "Reference"),
 		}
 	}
 }
 
 impl DynFieldKind {
+	// single line kinds can't hold newlines so those values get the multi line kind
+	pub fn for_value(self, value: &str) -> DynFieldKind {
+		match self {
+			DynFieldKind::TextLine if value.contains('\n') => DynFieldKind::MultiLine,
+			DynFieldKind::TextLineSecret if value.contains('\n') => {
+				DynFieldKind::MultiLineSecret
+			},
+			kind => kind,
+		}
+	}
+
+	pub fn is_secret(&self) -> bool {
+		matches!(self, DynFieldKind::TextLineSecret | DynFieldKind::MultiLineSecret)
+	}
+
 	pub fn all_values() -> Vec<DynFieldKind> {
 		vec![
 			DynFieldKind::TextLineSecret,
@@ -89,6 +142,7 @@ impl DynFieldKind {
 			DynFieldKind::MultiLine,
 			DynFieldKind::Url,
 			DynFieldKind::Heading,
+			DynFieldKind::Reference,
 		]
 	}
 }
@@ -101,6 +155,8 @@ impl Default for DynField {
 			title: String::from("Notes"),
 			visible: true,
 			value: vec![(0, String::from("My notes"))],
+			generator_profile: None,
+			expiry: None,
 		}
 	}
 }
@@ -110,6 +166,16 @@ pub struct DbEntry {
 	pub id: usize,
 	pub title: String,
 	pub fields: Vec<DynField>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub history: Vec<SecureField>,
+	#[serde(default)]
+	pub last_used: u64,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub tags: Vec<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub generator_rule: Option<GeneratorRule>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub expiry: Option<Expiry>,
 }
 
 impl DbEntry {
@@ -119,6 +185,73 @@ impl DbEntry {
 		for field in &mut self.fields {
 			field.zeroize();
 		}
+		for item in &mut self.history {
+			item.0.zeroize();
+			item.1.zeroize();
+		}
+		self.last_used.zeroize();
+		for tag in &mut self.tags {
+			tag.zeroize();
+		}
+	}
+
+	// PRIVATE: the last time any of the fields changed
+	fn last_modified(&self) -> u64 {
+		self
+			.fields
+			.iter()
+			.filter_map(|field| field.value.last().map(|value| value.0))
+			.max()
+			.unwrap_or(0)
+	}
+
+	// PRIVATE: the entry expiry is about rotating secrets so only those restart
+	// it, entries without any secret fields count every change
+	fn last_secret_change(&self) -> u64 {
+		if !self.fields.iter().any(|field| field.kind.is_secret()) {
+			return self.last_modified();
+		}
+
+		self
+			.fields
+			.iter()
+			.filter(|field| field.kind.is_secret())
+			.filter_map(|field| field.value.last().map(|value| value.0))
+			.max()
+			.unwrap_or(0)
+	}
+
+	// PRIVATE: the last time this entry was used or any of its fields changed
+	fn last_activity(&self) -> u64 {
+		self.last_modified().max(self.last_used)
+	}
+
+	// PRIVATE: the lowercased copy of this entry the search index works on
+	fn to_indexed(&self, index: &mut SearchIndex) -> IndexedEntry {
+		IndexedEntry {
+			id: self.id,
+			title: self.title.clone(),
+			title_haystack: Haystack::new(&self.title),
+			tags: self.tags.iter().map(|tag| tag.to_lowercase()).collect(),
+			fields: self
+				.fields
+				.iter()
+				.map(|field| IndexedField {
+					id: field.id,
+					kind: field.kind.clone(),
+					visible: field.visible,
+					title: field.title.clone(),
+					title_slot: index.field_title_slot(&field.title),
+					values: field
+						.value
+						.iter()
+						.map(|(date, value)| (*date, value.to_lowercase()))
+						.collect(),
+				})
+				.collect(),
+			last_modified: self.last_modified(),
+			last_activity: self.last_activity(),
+		}
 	}
 }
 
@@ -126,6 +259,7 @@ impl DbEntry {
 pub struct NewDbEntry {
 	pub title: String,
 	pub fields: Vec<DynField>,
+	pub tags: Vec<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -134,6 +268,48 @@ pub struct DbEntryNonSecure {
 	pub title: String,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchOrigin {
+	pub field: DbFields,
+	pub field_title: String,
+	pub date: u64,
+	// n as used in get_n_by_field
+	pub history_idx: usize,
+	pub is_archived: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit {
+	pub id: usize,
+	pub title: String,
+	pub idx: usize,
+	pub score: i64,
+	pub title_positions: Vec<usize>,
+	// where a deep search found the match if it wasn't in any current value
+	pub origin: Option<MatchOrigin>,
+}
+
+// the current value of a field as the security audit sees it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditField {
+	pub id: usize,
+	pub entry_title: String,
+	pub field: DbFields,
+	pub field_title: String,
+	pub kind: DynFieldKind,
+	pub value: String,
+	pub date: u64,
+}
+
+impl AuditField {
+	pub fn zeroize(&mut self) {
+		self.entry_title.zeroize();
+		self.field_title.zeroize();
+		self.value.zeroize();
+		self.date.zeroize();
+	}
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum DbFields {
 	Id,
@@ -181,6 +357,8 @@ pub struct Db {
 	hash: Arc<RwLock<[u8; 32]>>,
 	#[serde(skip)]
 	db_path: Arc<RwLock<String>>,
+	#[serde(skip)]
+	search_index: Arc<RwLock<SearchIndex>>,
 }
 
 mod arc_rwlock_serde {
@@ -231,7 +409,14 @@ impl Default for Db {
 							",
 						),
 					)],
+					generator_profile: None,
+					expiry: None,
 				}],
+				history: Vec::new(),
+				last_used: 0,
+				tags: Vec::new(),
+				generator_rule: None,
+				expiry: None,
 			}])),
 			config_db: Arc::new(RwLock::new(DbFileDb {
 				encrypted: true,
@@ -244,6 +429,7 @@ impl Default for Db {
 			db_path: Arc::new(RwLock::new(
 				db_path.into_os_string().to_string_lossy().to_string(),
 			)),
+			search_index: Arc::new(Default::default()),
 		}
 	}
 }
@@ -252,6 +438,26 @@ fn to_tuple(item: &DbEntry, idx: usize) -> (usize, String, usize) {
 	(item.id, item.title.clone(), idx)
 }
 
+fn get_timestamp() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.unwrap_or(Duration::new(0, 0))
+		.as_secs()
+}
+
+// create the value of a reference field pointing to the field of another entry
+pub fn make_reference(entry_id: &usize, field_id: &usize) -> String {
+	format!("{{REF:{}:{}}}", entry_id, field_id)
+}
+
+// parse the value of a reference field into the entry id and field id
+pub fn parse_reference(value: &str) -> Option<(usize, usize)> {
+	let inner = value.trim().strip_prefix("{REF:")?.strip_suffix('}')?;
+	let (entry_id, field_id) = inner.split_once(':')?;
+
+	Some((entry_id.parse().ok()?, field_id.parse().ok()?))
+}
+
 impl From<DbFile> for Db {
 	fn from(db_file: DbFile) -> Self {
 		Db {
@@ -265,11 +471,47 @@ impl From<DbFile> for Db {
 			vault_unlocked: Arc::new(RwLock::new(false)),
 			hash: Arc::new(RwLock::new(*b"00000000000000000000000000000000")),
 			db_path: Arc::new(RwLock::new(String::from(""))),
+			search_index: Arc::new(Default::default()),
 		}
 	}
 }
 
 impl Db {
+	// an unlocked db that only lives in memory so imports can be previewed
+	pub fn from_new_entries(entries: Vec<NewDbEntry>) -> Self {
+		let contents = entries
+			.into_iter()
+			.enumerate()
+			.map(|(idx, entry)| DbEntry {
+				id: idx + 1,
+				title: entry.title,
+				fields: entry
+					.fields
+					.into_iter()
+					.enumerate()
+					.map(|(field_idx, mut field)| {
+						field.id = field_idx + 1;
+						field
+					})
+					.collect(),
+				history: Vec::new(),
+				last_used: 0,
+				tags: entry.tags,
+				generator_rule: None,
+				expiry: None,
+			})
+			.collect::<Vec<DbEntry>>();
+
+		let db = Db {
+			contents: Arc::new(RwLock::new(contents)),
+			vault_unlocked: Arc::new(RwLock::new(true)),
+			db_path: Arc::new(RwLock::new(String::new())),
+			..Default::default()
+		};
+		db.rebuild_search_index();
+		db
+	}
+
 	pub fn load(db_path: String) -> Self {
 		let path = PathBuf::from(db_path.as_str());
 
@@ -302,9 +544,32 @@ impl Db {
 
 		*self.vault_unlocked.write() = true;
 		*self.contents.write() = contents.contents;
+		self.rebuild_search_index();
 		Ok(())
 	}
 
+	// PRIVATE: build the search index from scratch
+	fn rebuild_search_index(&self) {
+		let contents = self.contents.read();
+		let mut index = self.search_index.write();
+		index.zeroize();
+
+		for entry in contents.iter() {
+			let indexed = entry.to_indexed(&mut index);
+			index.push(indexed);
+		}
+	}
+
+	// PRIVATE: bring the search index up to date with the changes to an entry
+	fn reindex(&self, id: &usize) {
+		let contents = self.contents.read();
+		if let Some(entry) = contents.iter().find(|item| item.id == *id) {
+			let mut index = self.search_index.write();
+			let indexed = entry.to_indexed(&mut index);
+			index.upsert(indexed);
+		}
+	}
+
 	fn serialize_db(&self) -> anyhow::Result<()> {
 		// self.db -> self.config_db.cypher as toml
 		#[derive(Debug, Serialize, Deserialize)]
@@ -327,6 +592,55 @@ impl Db {
 		Ok(toml::to_string_pretty(self)?)
 	}
 
+	// export some entries as a vault file of their own, encrypted with a separate
+	// password and a fresh salt so it can be handed out without the main password
+	pub fn export_entries(
+		&self,
+		ids: &[usize],
+		password: String,
+	) -> anyhow::Result<String> {
+		let timestamp = get_timestamp();
+		let mut contents = self
+			.contents
+			.read()
+			.iter()
+			.filter(|item| ids.contains(&item.id))
+			.cloned()
+			.collect::<Vec<DbEntry>>();
+
+		// references to entries that aren't exported become the value they point to
+		for entry in &mut contents {
+			for field in &mut entry.fields {
+				if field.kind != DynFieldKind::Reference {
+					continue;
+				}
+				if let Some((source_id, _, source)) = self.resolve_reference(field) {
+					if !ids.contains(&source_id) {
+						field.kind = source.kind;
+						field.value = vec![(
+							timestamp,
+							source.value.last().cloned().unwrap_or_default().1,
+						)];
+					}
+				}
+			}
+		}
+
+		let salt = get_random_string(SALT_LETTER_COUNT);
+		let hash = password_hash(password, salt.clone())?;
+		let cypher =
+			encrypt_vault(toml::to_string(&DbFileCypher { contents })?, hash)?;
+
+		Ok(toml::to_string_pretty(&DbFile {
+			db: DbFileDb {
+				encrypted: true,
+				salt,
+				salt_letter_count: SALT_LETTER_COUNT,
+				cypher,
+			},
+		})?)
+	}
+
 	pub fn save(&self) -> anyhow::Result<()> {
 		let config = self.export()?;
 		let mut config_file = fs::OpenOptions::new()
@@ -339,6 +653,12 @@ impl Db {
 		Ok(())
 	}
 
+	// check a password against the one the vault was unlocked with
+	pub fn check_password(&self, password: String) -> bool {
+		password_hash(password, self.config_db.read().salt.clone())
+			.is_ok_and(|hash| hash == *self.hash.read())
+	}
+
 	pub fn change_password(
 		&self,
 		old: String,
@@ -372,6 +692,7 @@ impl Db {
 
 	pub fn lock(&self) {
 		self.hash.write().zeroize();
+		self.search_index.write().zeroize();
 		{
 			// clear data
 			for content in &mut *self.contents.write() {
@@ -409,6 +730,11 @@ impl Db {
 				id: *id,
 				title: String::from("Not found"),
 				fields: vec![DynField::default()],
+				history: Vec::new(),
+				last_used: 0,
+				tags: Vec::new(),
+				generator_rule: None,
+				expiry: None,
 			}
 		}
 	}
@@ -426,9 +752,33 @@ impl Db {
 				title: String::from("Notes"),
 				visible: true,
 				value: vec![(0, String::from("My Notes"))],
+				generator_profile: None,
+				expiry: None,
 			})
 	}
 
+	// PRIVATE: follow a reference field to the entry id, entry title and field it points to
+	fn resolve_reference(
+		&self,
+		field: &DynField,
+	) -> Option<(usize, String, DynField)> {
+		let mut current = field.clone();
+
+		for _ in 0..MAX_REFERENCE_DEPTH {
+			let (entry_id, field_id) = parse_reference(&current.value.last()?.1)?;
+			let contents = self.contents.read();
+			let entry = contents.iter().find(|item| item.id == entry_id)?;
+			let source = entry.fields.iter().find(|item| item.id == field_id)?;
+
+			if source.kind != DynFieldKind::Reference {
+				return Some((entry.id, entry.title.clone(), source.clone()));
+			}
+			current = source.clone();
+		}
+
+		None
+	}
+
 	// get the list of all entries for sidebar view
 	pub fn get_sidebar_list(&self) -> im::Vector<(usize, String, usize)> {
 		self
@@ -441,35 +791,192 @@ impl Db {
 			.collect()
 	}
 
-	// search through db and return a list for sidebar view
-	pub fn search(&self, needle: &str) -> im::Vector<(usize, String, usize)> {
+	// search through db and return a list of hits ranked with title matches first,
+	// a deep search also looks at older values and hidden fields
+	pub fn search(&self, query: &Query, deep: bool) -> Vec<SearchHit> {
+		self.search_index.read().search(query, deep, get_timestamp())
+	}
+
+	// get the tags of an entry
+	pub fn get_tags(&self, id: &usize) -> Vec<String> {
+		self.get_by_id_secure(id).tags
+	}
+
+	// get every tag used in the db once, sorted by name
+	pub fn get_all_tags(&self) -> Vec<String> {
+		let mut tags: Vec<String> = Vec::new();
+		for entry in self.contents.read().iter() {
+			for tag in &entry.tags {
+				if !tags.iter().any(|item| item.to_lowercase() == tag.to_lowercase()) {
+					tags.push(tag.clone());
+				}
+			}
+		}
+		tags.sort_by_key(|tag| tag.to_lowercase());
+		tags
+	}
+
+	// set the tags of an entry, dropping empty and duplicate ones
+	pub fn set_tags(&self, id: &usize, tags: Vec<String>) {
+		let mut new_tags: Vec<String> = Vec::new();
+		for tag in tags {
+			let tag = String::from(tag.trim());
+			if !tag.is_empty()
+				&& !new_tags
+					.iter()
+					.any(|item| item.to_lowercase() == tag.to_lowercase())
+			{
+				new_tags.push(tag);
+			}
+		}
+
+		self.contents.write().iter_mut().for_each(|item| {
+			if item.id == *id {
+				item.tags.clone_from(&new_tags);
+			}
+		});
+		self.reindex(id);
+	}
+
+	pub fn get_generator_rule(&self, id: &usize) -> Option<GeneratorRule> {
+		self.get_by_id_secure(id).generator_rule
+	}
+
+	// set or remove the password rules of an entry
+	pub fn set_generator_rule(&self, id: &usize, rule: Option<GeneratorRule>) {
+		self.contents.write().iter_mut().for_each(|item| {
+			if item.id == *id {
+				item.generator_rule.clone_from(&rule);
+			}
+		});
+	}
+
+	// get the expiry of an entry (title) or one of its fields
+	pub fn get_expiry(&self, id: &usize, field: &DbFields) -> Option<Expiry> {
+		let entry = self.get_by_id_secure(id);
+
+		match field {
+			DbFields::Id => None,
+			DbFields::Title => entry.expiry,
+			DbFields::Fields(field_id) => {
+				self.get_field_by_id(&entry, field_id).expiry
+			},
+		}
+	}
+
+	// set or remove the expiry of an entry (title) or one of its fields
+	pub fn set_expiry(
+		&self,
+		id: &usize,
+		field: &DbFields,
+		expiry: Option<Expiry>,
+	) {
+		self.contents.write().iter_mut().for_each(|item| {
+			if item.id == *id {
+				match field {
+					DbFields::Id => {},
+					DbFields::Title => item.expiry = expiry,
+					DbFields::Fields(field_id) => {
+						if let Some(dyn_field) =
+							item.fields.iter_mut().find(|field| field.id == *field_id)
+						{
+							dyn_field.expiry = expiry;
+						}
+					},
+				}
+			}
+		});
+	}
+
+	// get the date the current value of an entry (title) or field is due
+	pub fn get_due_date(&self, id: &usize, field: &DbFields) -> Option<u64> {
+		let entry = self.get_by_id_secure(id);
+
+		match field {
+			DbFields::Id => None,
+			DbFields::Title => entry
+				.expiry
+				.map(|expiry| expiry.get_due_date(entry.last_secret_change())),
+			DbFields::Fields(field_id) => {
+				let dyn_field = self.get_field_by_id(&entry, field_id);
+				let last_change =
+					dyn_field.value.last().map(|value| value.0).unwrap_or(0);
+				dyn_field.expiry.map(|expiry| expiry.get_due_date(last_change))
+			},
+		}
+	}
+
+	// get all entries and visible fields that are expired or due soon
+	pub fn get_due_items(&self, now: u64) -> Vec<DueItem> {
+		let mut items = Vec::new();
+
+		for entry in self.contents.read().iter() {
+			if let Some(expiry) = entry.expiry {
+				let due_date = expiry.get_due_date(entry.last_secret_change());
+				if let Some(status) = get_status(due_date, now) {
+					items.push(DueItem {
+						id: entry.id,
+						entry_title: entry.title.clone(),
+						field_title: None,
+						due_date,
+						status,
+					});
+				}
+			}
+
+			for field in entry.fields.iter().filter(|field| field.visible) {
+				if let Some(expiry) = field.expiry {
+					let last_change =
+						field.value.last().map(|value| value.0).unwrap_or(0);
+					let due_date = expiry.get_due_date(last_change);
+					if let Some(status) = get_status(due_date, now) {
+						items.push(DueItem {
+							id: entry.id,
+							entry_title: entry.title.clone(),
+							field_title: Some(field.title.clone()),
+							due_date,
+							status,
+						});
+					}
+				}
+			}
+		}
+
+		items
+	}
+
+	// the most urgent expiry status of each entry for the sidebar
+	pub fn get_expiry_list(&self) -> HashMap<usize, ExpiryStatus> {
+		let mut list: HashMap<usize, ExpiryStatus> = HashMap::new();
+		for item in self.get_due_items(get_timestamp()) {
+			let status = list.entry(item.id).or_insert(item.status);
+			*status = (*status).max(item.status);
+		}
+		list
+	}
+
+	// get the current values of all url fields of an entry
+	pub fn get_urls(&self, id: &usize) -> Vec<String> {
 		self
-			.contents
-			.read()
+			.get_by_id_secure(id)
+			.fields
 			.iter()
-			.enumerate()
-			.filter(|(_, entry)| {
-				let lowercase_needle = needle.to_lowercase();
-				// look at entry title
-				entry.title.to_lowercase().contains(&lowercase_needle)
-					|| entry.fields.iter().any(|field| {
-						// look at field title
-						field.title.to_lowercase().contains(&lowercase_needle) ||
-						// look at field value (important for notes) but we only look at the last value
-						field
-								.value
-								.last()
-								.unwrap_or(&(0, String::from("")))
-								.1
-								.to_lowercase()
-								.contains(&lowercase_needle)
-					})
-			})
-			.map(|(idx, item)| to_tuple(item, idx))
-			.rev()
+			.filter(|field| field.kind == DynFieldKind::Url)
+			.filter_map(|field| field.value.last().map(|value| value.1.clone()))
 			.collect()
 	}
 
+	// mark an entry as used so it ranks higher in search
+	pub fn touch(&self, id: &usize) {
+		let timestamp = get_timestamp();
+		self.contents.write().iter_mut().for_each(|item| {
+			if item.id == *id {
+				item.last_used = timestamp;
+			}
+		});
+		self.reindex(id);
+	}
+
 	// get non secure content of entry
 	pub fn get_by_id(&self, id: &usize) -> DbEntryNonSecure {
 		let entry = self.get_by_id_secure(id);
@@ -534,7 +1041,165 @@ impl Db {
 		}
 	}
 
-	// get the latest entry of a field
+	// get the generator profile last used on a field
+	pub fn get_generator_profile(
+		&self,
+		id: &usize,
+		field: &DbFields,
+	) -> Option<usize> {
+		let entry = self.get_by_id_secure(id);
+		match field {
+			DbFields::Id | DbFields::Title => None,
+			DbFields::Fields(field_id) => {
+				self.get_field_by_id(&entry, field_id).generator_profile
+			},
+		}
+	}
+
+	// remember the generator profile of a field
+	pub fn set_generator_profile(
+		&self,
+		id: &usize,
+		field: &DbFields,
+		profile: usize,
+	) {
+		if let DbFields::Fields(field_id) = field {
+			self.contents.write().iter_mut().for_each(|item| {
+				if item.id == *id {
+					if let Some(dyn_field) =
+						item.fields.iter_mut().find(|field| field.id == *field_id)
+					{
+						dyn_field.generator_profile = Some(profile);
+					}
+				}
+			});
+		}
+	}
+
+	// get the kind of field a reference resolves to or the kind of the field itself
+	pub fn get_resolved_field_kind(
+		&self,
+		id: &usize,
+		field: &DbFields,
+	) -> DynFieldKind {
+		match self.get_field_kind(id, field) {
+			DynFieldKind::Reference => {
+				let entry = self.get_by_id_secure(id);
+				let field_id = match field {
+					DbFields::Fields(idx) => idx,
+					_ => &0,
+				};
+				self
+					.resolve_reference(&self.get_field_by_id(&entry, field_id))
+					.map(|(_, _, source)| source.kind)
+					.unwrap_or_default()
+			},
+			kind => kind,
+		}
+	}
+
+	// get the entry title and field title a reference field points to
+	pub fn get_reference_source(
+		&self,
+		id: &usize,
+		field: &DbFields,
+	) -> Option<(String, String)> {
+		let entry = self.get_by_id_secure(id);
+
+		match field {
+			DbFields::Fields(field_id) => {
+				let dyn_field = self.get_field_by_id(&entry, field_id);
+				if dyn_field.kind != DynFieldKind::Reference {
+					return None;
+				}
+				self
+					.resolve_reference(&dyn_field)
+					.map(|(_, title, source)| (title, source.title))
+			},
+			_ => None,
+		}
+	}
+
+	// get all fields a reference can point to as a label and the reference value
+	pub fn get_reference_targets(&self) -> Vec<(String, String)> {
+		self
+			.contents
+			.read()
+			.iter()
+			.rev()
+			.flat_map(|entry| {
+				entry
+					.fields
+					.iter()
+					.filter(|field| {
+						field.visible
+							&& !matches!(
+								field.kind,
+								DynFieldKind::Heading | DynFieldKind::Reference
+							)
+					})
+					.map(|field| {
+						(
+							format!("{} › {}", entry.title, field.title),
+							make_reference(&entry.id, &field.id),
+						)
+					})
+					.collect::<Vec<(String, String)>>()
+			})
+			.collect()
+	}
+
+	// get all reference fields that point to a missing or deleted field
+	pub fn get_dangling_references(&self) -> Vec<(usize, String, String)> {
+		let entries = self.contents.read().clone();
+
+		entries
+			.iter()
+			.flat_map(|entry| {
+				entry
+					.fields
+					.iter()
+					.filter(|field| {
+						field.visible
+							&& field.kind == DynFieldKind::Reference
+							&& self
+								.resolve_reference(field)
+								.map_or(true, |(_, _, source)| !source.visible)
+					})
+					.map(|field| (entry.id, entry.title.clone(), field.title.clone()))
+					.collect::<Vec<(usize, String, String)>>()
+			})
+			.collect()
+	}
+
+	// get the current value of every visible field for the security audit
+	pub fn get_audit_fields(&self) -> Vec<AuditField> {
+		self
+			.contents
+			.read()
+			.iter()
+			.flat_map(|entry| {
+				entry
+					.fields
+					.iter()
+					.filter(|field| field.visible)
+					.filter_map(|field| {
+						field.value.last().map(|(date, value)| AuditField {
+							id: entry.id,
+							entry_title: entry.title.clone(),
+							field: DbFields::Fields(field.id),
+							field_title: field.title.clone(),
+							kind: field.kind.clone(),
+							value: value.clone(),
+							date: *date,
+						})
+					})
+					.collect::<Vec<AuditField>>()
+			})
+			.collect()
+	}
+
+	// get the latest entry of a field (references resolve to the live value of their source)
 	pub fn get_last_by_field(&self, id: &usize, field: &DbFields) -> String {
 		let entry = self.get_by_id_secure(id);
 
@@ -542,7 +1207,17 @@ impl Db {
 			DbFields::Id => format!("{:?}", entry.id),
 			DbFields::Title => entry.title,
 			DbFields::Fields(field_id) => {
-				self.get_field_by_id(&entry, field_id).value.last().unwrap().1.clone()
+				let dyn_field = self.get_field_by_id(&entry, field_id);
+				match dyn_field.kind {
+					DynFieldKind::Reference => {
+						self
+							.resolve_reference(&dyn_field)
+							.and_then(|(_, _, source)| source.value.last().cloned())
+							.unwrap_or_default()
+							.1
+					},
+					_ => dyn_field.value.last().unwrap().1.clone(),
+				}
 			},
 		}
 	}
@@ -625,6 +1300,11 @@ impl Db {
 				id: 1,
 				title: String::from("New Entry"),
 				fields: vec![DynField::default()],
+				history: Vec::new(),
+				last_used: 0,
+				tags: Vec::new(),
+				generator_rule: None,
+				expiry: None,
 			})
 			.id + 1;
 
@@ -632,7 +1312,13 @@ impl Db {
 			id: new_id,
 			title,
 			fields: Vec::new(),
+			history: Vec::new(),
+			last_used: 0,
+			tags: Vec::new(),
+			generator_rule: None,
+			expiry: None,
 		});
+		self.reindex(&new_id);
 
 		new_id
 	}
@@ -644,6 +1330,22 @@ impl Db {
 		kind: DynFieldKind,
 		title_value: String,
 		field_value: String,
+	) -> DbFields {
+		self.add_field_with_history(
+			id,
+			kind,
+			title_value,
+			vec![(get_timestamp(), field_value)],
+		)
+	}
+
+	// add a new field to an entry that keeps the dates of all its values
+	pub fn add_field_with_history(
+		&self,
+		id: &usize,
+		kind: DynFieldKind,
+		title_value: String,
+		history: Vec<SecureField>,
 	) -> DbFields {
 		let mut field = DbFields::Id;
 		self.contents.write().iter_mut().for_each(|item| {
@@ -654,24 +1356,204 @@ impl Db {
 					.max_by_key(|s| s.id)
 					.unwrap_or(&DynField::default())
 					.id + 1;
-				let timestamp: u64 = SystemTime::now()
-					.duration_since(UNIX_EPOCH)
-					.unwrap_or(Duration::new(0, 0))
-					.as_secs();
 				item.fields.push(DynField {
 					id,
 					kind: kind.clone(),
 					title: title_value.clone(),
 					visible: true,
-					value: vec![(timestamp, field_value.clone())],
+					value: history.clone(),
+					generator_profile: None,
+					expiry: None,
 				});
 				field = DbFields::Fields(id);
 			}
 		});
+		self.reindex(id);
 
 		field
 	}
 
+	// overwrite all values of a field with a history ordered from old to new
+	pub fn set_field_history(
+		&self,
+		id: &usize,
+		field: &DbFields,
+		history: Vec<SecureField>,
+	) {
+		if let DbFields::Fields(field_id) = field {
+			self.contents.write().iter_mut().for_each(|item| {
+				if item.id == *id {
+					if let Some(dyn_field) =
+						item.fields.iter_mut().find(|field| field.id == *field_id)
+					{
+						dyn_field.value.clone_from(&history);
+					}
+				}
+			});
+		}
+		self.reindex(id);
+	}
+
+	// duplicate an entry, optionally with the history of all its fields
+	pub fn duplicate_entry(&self, id: &usize, with_history: bool) -> usize {
+		let entry = self.get_by_id_secure(id);
+		let timestamp = get_timestamp();
+		let new_id = self.add(format!("{} (copy)", entry.title));
+
+		let fields = entry
+			.fields
+			.iter()
+			.map(|field| {
+				let mut new_field = field.clone();
+				if !with_history {
+					let last = field.value.last().cloned().unwrap_or_default();
+					new_field.value = vec![(timestamp, last.1)];
+				}
+				new_field
+			})
+			.collect::<Vec<DynField>>();
+
+		let history =
+			vec![(timestamp, format!("Duplicated from \"{}\"", entry.title))];
+
+		self.contents.write().iter_mut().for_each(|item| {
+			if item.id == new_id {
+				item.fields = fields.clone();
+				item.history.clone_from(&history);
+				item.tags.clone_from(&entry.tags);
+				item.generator_rule.clone_from(&entry.generator_rule);
+				item.expiry = entry.expiry;
+			}
+			if item.id == *id {
+				item.history.push((
+					timestamp,
+					format!("Duplicated to \"{} (copy)\"", entry.title),
+				));
+			}
+		});
+		self.reindex(&new_id);
+		self.reindex(id);
+
+		new_id
+	}
+
+	// PRIVATE: copy a field into another entry and optionally remove it from the source
+	fn transfer_field(
+		&self,
+		id: &usize,
+		field: &DbFields,
+		target_id: &usize,
+		is_move: bool,
+	) -> Option<DbFields> {
+		let field_id = match field {
+			DbFields::Fields(field_id) => field_id,
+			_ => return None,
+		};
+		if id == target_id {
+			return None;
+		}
+
+		let entry = self.get_by_id_secure(id);
+		let dyn_field = entry.fields.iter().find(|item| item.id == *field_id)?;
+		let target_title = self
+			.contents
+			.read()
+			.iter()
+			.find(|item| item.id == *target_id)?
+			.title
+			.clone();
+
+		let timestamp = get_timestamp();
+		let action = if is_move { "Moved" } else { "Copied" };
+		let mut new_field = None;
+
+		self.contents.write().iter_mut().for_each(|item| {
+			if item.id == *target_id {
+				let new_id = item
+					.fields
+					.iter()
+					.max_by_key(|s| s.id)
+					.unwrap_or(&DynField::default())
+					.id + 1;
+				item.fields.push(DynField {
+					id: new_id,
+					..dyn_field.clone()
+				});
+				item.history.push((
+					timestamp,
+					format!(
+						"{} field \"{}\" from \"{}\"",
+						action, dyn_field.title, entry.title
+					),
+				));
+				new_field = Some(DbFields::Fields(new_id));
+			}
+			if item.id == *id {
+				if is_move {
+					item.fields.retain(|item_field| item_field.id != *field_id);
+				}
+				item.history.push((
+					timestamp,
+					format!(
+						"{} field \"{}\" to \"{}\"",
+						action, dyn_field.title, target_title
+					),
+				));
+			}
+		});
+
+		// keep references to a moved field pointing at its new home
+		if let (true, Some(DbFields::Fields(new_id))) = (is_move, new_field) {
+			let old_reference = make_reference(id, field_id);
+			let new_reference = make_reference(target_id, &new_id);
+			let mut referencing_ids = Vec::new();
+			self.contents.write().iter_mut().for_each(|item| {
+				item
+					.fields
+					.iter_mut()
+					.filter(|item_field| {
+						item_field.kind == DynFieldKind::Reference
+							&& item_field.value.last().map(|value| &value.1)
+								== Some(&old_reference)
+					})
+					.for_each(|item_field| {
+						item_field.value.push((timestamp, new_reference.clone()));
+						referencing_ids.push(item.id);
+					});
+			});
+			referencing_ids.iter().for_each(|item_id| self.reindex(item_id));
+		}
+		self.reindex(id);
+		self.reindex(target_id);
+
+		new_field
+	}
+
+	// copy a field to another entry
+	pub fn copy_field(
+		&self,
+		id: &usize,
+		field: &DbFields,
+		target_id: &usize,
+	) -> Option<DbFields> {
+		self.transfer_field(id, field, target_id, false)
+	}
+
+	// move a field to another entry
+	pub fn move_field(
+		&self,
+		id: &usize,
+		field: &DbFields,
+		target_id: &usize,
+	) -> Option<DbFields> {
+		self.transfer_field(id, field, target_id, true)
+	}
+
+	// get the history of operations on an entry
+	pub fn get_entry_history(&self, id: &usize) -> Vec<SecureField> {
+		self.get_by_id_secure(id).history.into_iter().rev().collect()
+	}
+
 	// change the title of a field
 	pub fn edit_field_title(&self, id: &usize, field: &DbFields, title: String) {
 		self.contents.write().iter_mut().for_each(|item| {
@@ -687,12 +1569,15 @@ impl Db {
 							title: String::from("Notes"),
 							visible: true,
 							value: vec![(0, String::from("My Notes"))],
+							generator_profile: None,
+							expiry: None,
 						})
 						.title
 						.clone_from(&title);
 				}
 			}
 		});
+		self.reindex(id);
 	}
 
 	pub fn edit_field_visbility(
@@ -714,17 +1599,32 @@ impl Db {
 							title: String::from("Notes"),
 							visible,
 							value: vec![(0, String::from("My Notes"))],
+							generator_profile: None,
+							expiry: None,
 						})
 						.visible = visible;
 				}
 			}
 		});
+		self.reindex(id);
 
 		self.get_hidden_fields(id)
 	}
 
-	// edit a field
+	// edit a field (editing a reference writes through to its source)
 	pub fn edit_field(&self, id: usize, field: &DbFields, new_content: String) {
+		if let DbFields::Fields(field_id) = field {
+			let dyn_field =
+				self.get_field_by_id(&self.get_by_id_secure(&id), field_id);
+			if dyn_field.kind == DynFieldKind::Reference {
+				if let Some((source_id, _, source)) = self.resolve_reference(&dyn_field)
+				{
+					self.edit_field(source_id, &DbFields::Fields(source.id), new_content);
+				}
+				return;
+			}
+		}
+
 		let mut index: usize = 0;
 		self.contents.read().iter().enumerate().find(|(idx, item)| {
 			if item.id == id {
@@ -736,10 +1636,7 @@ impl Db {
 		});
 
 		if let Some(entry) = self.contents.write().get_mut(index) {
-			let timestamp: u64 = SystemTime::now()
-				.duration_since(UNIX_EPOCH)
-				.unwrap_or(Duration::new(0, 0))
-				.as_secs();
+			let timestamp = get_timestamp();
 
 			match field {
 				DbFields::Id => {
@@ -749,6 +1646,27 @@ impl Db {
 					entry.title = new_content;
 				},
 				DbFields::Fields(field_id) => {
+					// a new secret restarts the rotation clock of the entry
+					let last_secret_change = entry.last_secret_change();
+					let is_secret = entry
+						.fields
+						.iter()
+						.any(|field| field.id == *field_id && field.kind.is_secret());
+					if let Some(expiry) = entry.expiry {
+						if is_secret {
+							entry.expiry = Some(expiry.reset(last_secret_change, timestamp));
+						}
+					}
+					if let Some(dyn_field) =
+						entry.fields.iter_mut().find(|field| field.id == *field_id)
+					{
+						let last_change =
+							dyn_field.value.last().map(|value| value.0).unwrap_or(0);
+						if let Some(expiry) = dyn_field.expiry {
+							dyn_field.expiry = Some(expiry.reset(last_change, timestamp));
+						}
+					}
+
 					entry
 						.fields
 						.iter_mut()
@@ -759,12 +1677,15 @@ impl Db {
 							title: String::from("Notes"),
 							visible: true,
 							value: vec![(0, String::from("My Notes"))],
+							generator_profile: None,
+							expiry: None,
 						})
 						.value
 						.push((timestamp, new_content));
 				},
 			}
 		}
+		self.reindex(&id);
 	}
 
 	// save order of dyn fields
@@ -796,5 +1717,437 @@ impl Db {
 				item.fields.clone_from(&all_fields_new_order);
 			}
 		});
+		self.reindex(id);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn export_entries_test() {
+		let field = |title: &str, value: &str| {
+			DynField::new(
+				DynFieldKind::TextLineSecret,
+				String::from(title),
+				vec![(10, String::from(value))],
+			)
+		};
+		let db = Db::from_new_entries(vec![
+			NewDbEntry {
+				title: String::from("Mail"),
+				fields: vec![field("Password", "hunter2")],
+				tags: vec![String::from("work")],
+			},
+			NewDbEntry {
+				title: String::from("Shared"),
+				fields: vec![field("PIN", "1234")],
+				tags: Vec::new(),
+			},
+			NewDbEntry {
+				title: String::from("Private"),
+				fields: vec![field("Password", "secret")],
+				tags: Vec::new(),
+			},
+		]);
+		db.add_field(
+			&1,
+			DynFieldKind::Reference,
+			String::from("Shared PIN"),
+			make_reference(&2, &1),
+		);
+		db.add_field(
+			&1,
+			DynFieldKind::Reference,
+			String::from("Private password"),
+			make_reference(&3, &1),
+		);
+
+		let exported =
+			db.export_entries(&[1, 2], String::from("contractor")).unwrap();
+		let file = toml::from_str::<DbFile>(&exported).unwrap();
+		assert!(file.db.encrypted);
+		assert_ne!(file.db.salt, db.config_db.read().salt);
+
+		let imported: Db = file.into();
+		assert!(imported.decrypt_database(String::from("vault")).is_err());
+		imported.decrypt_database(String::from("contractor")).unwrap();
+		assert_eq!(
+			imported
+				.get_sidebar_list()
+				.iter()
+				.map(|(_, title, _)| title.as_str())
+				.collect::<Vec<&str>>(),
+			vec!["Shared", "Mail"]
+		);
+
+		// the reference to the exported entry stays, the other one is resolved
+		let fields = imported.get_fields(&1);
+		assert_eq!(
+			imported.get_field_kind(&1, &fields[1].0),
+			DynFieldKind::Reference
+		);
+		assert_eq!(imported.get_last_by_field(&1, &fields[1].0), "1234");
+		assert_eq!(
+			imported.get_field_kind(&1, &fields[2].0),
+			DynFieldKind::TextLineSecret
+		);
+		assert_eq!(imported.get_last_by_field(&1, &fields[2].0), "secret");
+		assert_eq!(imported.get_all_tags(), vec!["work"]);
+	}
+
+	#[test]
+	fn deep_search_test() {
+		let db = Db::from_new_entries(vec![
+			NewDbEntry {
+				title: String::from("Mail"),
+				fields: vec![
+					DynField::new(
+						DynFieldKind::TextLineSecret,
+						String::from("Password"),
+						vec![
+							(10, String::from("Zanzibar")),
+							(20, String::from("current")),
+						],
+					),
+					DynField::new(
+						DynFieldKind::TextLine,
+						String::from("Recovery"),
+						vec![(10, String::from("backup code"))],
+					)
+					.with_visibility(false),
+				],
+				tags: Vec::new(),
+			},
+			NewDbEntry {
+				title: String::from("Shared"),
+				fields: vec![DynField::new(
+					DynFieldKind::TextLine,
+					String::from("PIN"),
+					vec![(10, String::from("zanzibar"))],
+				)],
+				tags: Vec::new(),
+			},
+		]);
+		let search = |input: &str, deep: bool| {
+			db.search(&Query::plain_text(input), deep)
+				.into_iter()
+				.map(|hit| (hit.id, hit.origin))
+				.collect::<Vec<(usize, Option<MatchOrigin>)>>()
+		};
+
+		assert_eq!(search("zanzibar", false), vec![(2, None)]);
+		assert!(search("backup", false).is_empty());
+		assert!(search("recovery", false).is_empty());
+
+		// an older value is found with the n to open it in the history
+		let hits = search("zanzibar", true);
+		assert_eq!(hits.len(), 2);
+		assert!(hits.contains(&(2, None)));
+		let origin = hits
+			.into_iter()
+			.find_map(|(id, origin)| (id == 1).then_some(origin))
+			.flatten()
+			.unwrap();
+		assert_eq!(origin.field, DbFields::Fields(1));
+		assert_eq!(origin.date, 10);
+		assert!(!origin.is_archived);
+		assert_eq!(
+			db.get_n_by_field(&1, &origin.field, origin.history_idx),
+			"Zanzibar"
+		);
+
+		// archived fields are found by their value and their title
+		for input in ["backup", "recovery"] {
+			let hits = search(input, true);
+			assert_eq!(hits.len(), 1);
+			let origin = hits[0].1.clone().unwrap();
+			assert_eq!(origin.field, DbFields::Fields(2));
+			assert!(origin.is_archived);
+			assert_eq!(
+				db.get_n_by_field(&1, &origin.field, origin.history_idx),
+				"backup code"
+			);
+		}
+	}
+
+	#[test]
+	fn duplicate_entry_test() {
+		let db = Db::from_new_entries(vec![NewDbEntry {
+			title: String::from("Mail"),
+			fields: vec![
+				DynField::new(
+					DynFieldKind::TextLineSecret,
+					String::from("Password"),
+					vec![(10, String::from("old")), (20, String::from("new"))],
+				),
+				DynField::new(
+					DynFieldKind::TextLine,
+					String::from("Recovery"),
+					vec![(10, String::from("codes"))],
+				)
+				.with_visibility(false),
+			],
+			tags: vec![String::from("work")],
+		}]);
+		db.set_expiry(&1, &DbFields::Title, Some(Expiry::Interval(90)));
+
+		let copy = db.duplicate_entry(&1, true);
+		assert_eq!(copy, 2);
+		assert_eq!(db.get_by_id(&copy).title, "Mail (copy)");
+		assert_eq!(db.get_tags(&copy), vec!["work"]);
+		assert_eq!(
+			db.get_expiry(&copy, &DbFields::Title),
+			Some(Expiry::Interval(90))
+		);
+		assert_eq!(
+			db.get_history(&copy, &DbFields::Fields(1)).unwrap(),
+			im::vector![(20, String::from("new")), (10, String::from("old"))]
+		);
+		assert_eq!(db.get_visible_fields(&copy), vec![DbFields::Fields(1)]);
+		assert_eq!(db.get_hidden_fields(&copy), vec![DbFields::Fields(2)]);
+		assert_eq!(
+			db.get_entry_history(&copy)
+				.into_iter()
+				.map(|(_, action)| action)
+				.collect::<Vec<String>>(),
+			vec!["Duplicated from \"Mail\""]
+		);
+		assert_eq!(db.get_entry_history(&1)[0].1, "Duplicated to \"Mail (copy)\"");
+
+		// without history only the latest value is copied with a new date
+		let copy = db.duplicate_entry(&1, false);
+		let history = db.get_history(&copy, &DbFields::Fields(1)).unwrap();
+		assert_eq!(history.len(), 1);
+		assert_eq!(history[0].1, "new");
+		assert!(history[0].0 > 20);
+		assert_eq!(db.get_history(&1, &DbFields::Fields(1)).unwrap().len(), 2);
+	}
+
+	#[test]
+	fn transfer_field_test() {
+		let db = Db::from_new_entries(vec![
+			NewDbEntry {
+				title: String::from("Mail"),
+				fields: vec![DynField::new(
+					DynFieldKind::TextLineSecret,
+					String::from("Password"),
+					vec![(10, String::from("old")), (20, String::from("new"))],
+				)],
+				tags: Vec::new(),
+			},
+			NewDbEntry {
+				title: String::from("Bank"),
+				fields: vec![DynField::new(
+					DynFieldKind::TextLine,
+					String::from("Username"),
+					vec![(10, String::from("me"))],
+				)],
+				tags: Vec::new(),
+			},
+		]);
+		let actions = |id: &usize| {
+			db.get_entry_history(id)
+				.into_iter()
+				.map(|(_, action)| action)
+				.collect::<Vec<String>>()
+		};
+
+		assert_eq!(db.copy_field(&1, &DbFields::Fields(1), &1), None);
+		assert_eq!(db.copy_field(&1, &DbFields::Title, &2), None);
+
+		let copied = db.copy_field(&1, &DbFields::Fields(1), &2).unwrap();
+		assert_eq!(copied, DbFields::Fields(2));
+		assert_eq!(db.get_fields(&1).len(), 1);
+		assert_eq!(db.get_name_of_field(&2, &copied), "Password");
+		assert_eq!(
+			db.get_history(&2, &copied).unwrap(),
+			db.get_history(&1, &DbFields::Fields(1)).unwrap()
+		);
+		assert_eq!(actions(&1), vec!["Copied field \"Password\" to \"Bank\""]);
+		assert_eq!(actions(&2), vec!["Copied field \"Password\" from \"Mail\""]);
+
+		let moved = db.move_field(&1, &DbFields::Fields(1), &2).unwrap();
+		assert_eq!(moved, DbFields::Fields(3));
+		assert!(db.get_fields(&1).is_empty());
+		assert_eq!(
+			db.get_history(&2, &moved).unwrap(),
+			im::vector![(20, String::from("new")), (10, String::from("old"))]
+		);
+		assert_eq!(
+			actions(&1),
+			vec![
+				"Moved field \"Password\" to \"Bank\"",
+				"Copied field \"Password\" to \"Bank\""
+			]
+		);
+		assert_eq!(actions(&2)[0], "Moved field \"Password\" from \"Mail\"");
+	}
+
+	#[test]
+	fn generator_profile_test() {
+		let db = Db::from_new_entries(vec![NewDbEntry {
+			title: String::from("Mail"),
+			fields: vec![DynField::new(
+				DynFieldKind::TextLineSecret,
+				String::from("Password"),
+				vec![(10, String::from("hunter2"))],
+			)],
+			tags: Vec::new(),
+		}]);
+
+		assert_eq!(db.get_generator_profile(&1, &DbFields::Fields(1)), None);
+		db.set_generator_profile(&1, &DbFields::Fields(1), 2);
+		db.set_generator_profile(&1, &DbFields::Title, 3);
+		assert_eq!(db.get_generator_profile(&1, &DbFields::Fields(1)), Some(2));
+		assert_eq!(db.get_generator_profile(&1, &DbFields::Title), None);
+
+		// the profile is remembered for the next value of the field
+		db.edit_field(1, &DbFields::Fields(1), String::from("new"));
+		assert_eq!(db.get_generator_profile(&1, &DbFields::Fields(1)), Some(2));
+	}
+
+	#[test]
+	fn reference_test() {
+		let field = |title: &str, value: &str| {
+			DynField::new(
+				DynFieldKind::TextLineSecret,
+				String::from(title),
+				vec![(10, String::from(value))],
+			)
+		};
+		let db = Db::from_new_entries(vec![
+			NewDbEntry {
+				title: String::from("Mail"),
+				fields: vec![field("Password", "hunter2")],
+				tags: Vec::new(),
+			},
+			NewDbEntry {
+				title: String::from("Shared"),
+				fields: vec![field("PIN", "1234")],
+				tags: Vec::new(),
+			},
+			NewDbEntry {
+				title: String::from("Loop"),
+				fields: Vec::new(),
+				tags: Vec::new(),
+			},
+		]);
+
+		assert_eq!(parse_reference(" {REF:1:2} "), Some((1, 2)));
+		assert_eq!(parse_reference("{REF:one:2}"), None);
+		assert_eq!(parse_reference("REF:1:2"), None);
+
+		let shared = db.add_field(
+			&2,
+			DynFieldKind::Reference,
+			String::from("Mail password"),
+			make_reference(&1, &1),
+		);
+		let chained = db.add_field(
+			&1,
+			DynFieldKind::Reference,
+			String::from("Chained"),
+			make_reference(&2, &2),
+		);
+		db.add_field(
+			&3,
+			DynFieldKind::Reference,
+			String::from("Self"),
+			make_reference(&3, &1),
+		);
+
+		// a reference to a reference resolves to the field at the end of the chain
+		let DbFields::Fields(chained_id) = chained else {
+			panic!("The reference field wasn't added");
+		};
+		let (source_id, source_title, source) = db
+			.resolve_reference(
+				&db.get_field_by_id(&db.get_by_id_secure(&1), &chained_id),
+			)
+			.unwrap();
+		assert_eq!((source_id, source_title.as_str(), source.id), (1, "Mail", 1));
+		assert_eq!(db.get_last_by_field(&1, &chained), "hunter2");
+		assert_eq!(
+			db.get_resolved_field_kind(&1, &chained),
+			DynFieldKind::TextLineSecret
+		);
+		assert_eq!(
+			db.get_dangling_references(),
+			vec![(3, String::from("Loop"), String::from("Self"))]
+		);
+
+		// moving a field keeps the references to it pointing at its new home
+		let pin_reference = db.add_field(
+			&3,
+			DynFieldKind::Reference,
+			String::from("PIN"),
+			make_reference(&2, &1),
+		);
+		let moved = db.move_field(&2, &DbFields::Fields(1), &1).unwrap();
+		assert_eq!(moved, DbFields::Fields(3));
+		assert_eq!(
+			db.get_history(&3, &pin_reference).unwrap()[0].1,
+			make_reference(&1, &3)
+		);
+		assert_eq!(db.get_last_by_field(&3, &pin_reference), "1234");
+		assert_eq!(db.get_last_by_field(&2, &shared), "hunter2");
+
+		// hiding the source leaves every reference to it dangling
+		db.edit_field_visbility(&1, &DbFields::Fields(1), false);
+		assert_eq!(
+			db.get_dangling_references(),
+			vec![
+				(1, String::from("Mail"), String::from("Chained")),
+				(2, String::from("Shared"), String::from("Mail password")),
+				(3, String::from("Loop"), String::from("Self")),
+			]
+		);
+	}
+
+	#[test]
+	fn edit_field_expiry_test() {
+		let db = Db::from_new_entries(vec![NewDbEntry {
+			title: String::from("Mail"),
+			fields: vec![
+				DynField::new(
+					DynFieldKind::TextLineSecret,
+					String::from("Password"),
+					vec![(10, String::from("old"))],
+				),
+				DynField::new(
+					DynFieldKind::TextLine,
+					String::from("Username"),
+					vec![(10, String::from("me"))],
+				),
+			],
+			tags: Vec::new(),
+		}]);
+
+		// only a new secret moves the date of the entry
+		db.set_expiry(&1, &DbFields::Title, Some(Expiry::Date(1000)));
+		db.edit_field(1, &DbFields::Fields(2), String::from("you"));
+		assert_eq!(db.get_expiry(&1, &DbFields::Title), Some(Expiry::Date(1000)));
+
+		db.set_expiry(&1, &DbFields::Title, Some(Expiry::Interval(30)));
+		assert_eq!(
+			db.get_due_date(&1, &DbFields::Title),
+			Some(10 + 30 * 60 * 60 * 24)
+		);
+
+		db.set_expiry(&1, &DbFields::Title, Some(Expiry::Date(1000)));
+		db.edit_field(1, &DbFields::Fields(1), String::from("new"));
+		let changed = db.get_history_dates(&1, &DbFields::Fields(1))[0].1;
+		assert_eq!(
+			db.get_expiry(&1, &DbFields::Title),
+			Some(Expiry::Date(changed + 990))
+		);
+
+		// a date that passed before the last secret was set still moves forward
+		db.set_expiry(&1, &DbFields::Title, Some(Expiry::Date(5)));
+		db.edit_field(1, &DbFields::Fields(1), String::from("newer"));
+		let changed = db.get_history_dates(&1, &DbFields::Fields(1))[0].1;
+		assert!(db.get_due_date(&1, &DbFields::Title).unwrap() > changed);
 	}
 }
@@ -6,6 +6,7 @@ use aes_gcm_siv::{
 use argon2::Argon2;
 use base64::{engine::general_purpose, Engine as _};
 use std::str::from_utf8;
+use zeroize::Zeroize;
 
 #[derive(thiserror::Error, Debug)]
 pub enum CryptError {
@@ -62,15 +63,18 @@ pub fn encrypt_vault(
 }
 
 pub fn password_hash(
-	password: String,
+	mut password: String,
 	salt: String,
 ) -> Result<[u8; 32], CryptError> {
 	let mut okm = [0u8; 32];
-	Argon2::default().hash_password_into(
+	let result = Argon2::default().hash_password_into(
 		password.as_bytes(),
 		salt.as_bytes(),
 		&mut okm,
-	)?;
+	);
+	// the password is ours now so it's cleared on every path
+	password.zeroize();
+	result?;
 	Ok(okm)
 }
 
@@ -0,0 +1,238 @@
+use chrono::{DateTime, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+const DAY: u64 = 60 * 60 * 24;
+// how many days before the due date an entry shows up as due soon
+const DUE_SOON_DAYS: u64 = 14;
+// how far a fixed date that had already passed moves past a new value
+const RESET_DAYS: u64 = 90;
+// how many titles the unlock toast lists before it shortens the list
+const SUMMARY_TITLES: usize = 3;
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum ExpiryError {
+	#[error("Use a number of days like \"90d\" or a date like \"2030-12-31\"")]
+	Format,
+}
+
+// when an entry or field needs a new value
+#[derive(Debug, Deserialize, Serialize, Copy, Clone, PartialEq, Eq)]
+pub enum Expiry {
+	// a fixed date as unix timestamp
+	Date(u64),
+	// a number of days after the value last changed
+	Interval(u64),
+}
+
+impl std::fmt::Display for Expiry {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match *self {
+			Expiry::Date(date) => write!(f, "{}", format_date(date)),
+			Expiry::Interval(days) => write!(f, "{}d", days),
+		}
+	}
+}
+
+impl Expiry {
+	// the date this expires given the last time the value changed
+	pub fn get_due_date(&self, last_change: u64) -> u64 {
+		match *self {
+			Expiry::Date(date) => date,
+			Expiry::Interval(days) => last_change + days * DAY,
+		}
+	}
+
+	// a new value resets the clock, a fixed date moves forward by the same span
+	// it had from the previous value or by the default span if it had passed
+	// before that value was set
+	pub fn reset(&self, last_change: u64, now: u64) -> Self {
+		match *self {
+			Expiry::Date(date) if date > last_change => {
+				Expiry::Date(now + (date - last_change))
+			},
+			Expiry::Date(_) => Expiry::Date(now + RESET_DAYS * DAY),
+			Expiry::Interval(_) => *self,
+		}
+	}
+}
+
+// parse what the user typed into an expiry, an empty value removes it
+pub fn parse_expiry(value: &str) -> Result<Option<Expiry>, ExpiryError> {
+	let value = value.trim();
+	if value.is_empty() {
+		return Ok(None);
+	}
+
+	let days = value.strip_suffix('d').unwrap_or(value);
+	if let Ok(days) = days.trim().parse::<u64>() {
+		return match days {
+			0 => Err(ExpiryError::Format),
+			_ => Ok(Some(Expiry::Interval(days))),
+		};
+	}
+
+	NaiveDate::parse_from_str(value, "%Y-%m-%d")
+		.ok()
+		.and_then(|date| date.and_hms_opt(0, 0, 0))
+		.map(|date| Some(Expiry::Date(date.and_utc().timestamp().max(0) as u64)))
+		.ok_or(ExpiryError::Format)
+}
+
+pub fn format_date(timestamp: u64) -> String {
+	DateTime::from_timestamp(timestamp as i64, 0)
+		.unwrap_or_default()
+		.format("%Y-%m-%d")
+		.to_string()
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ExpiryStatus {
+	DueSoon,
+	Expired,
+}
+
+pub fn get_status(due_date: u64, now: u64) -> Option<ExpiryStatus> {
+	if due_date <= now {
+		Some(ExpiryStatus::Expired)
+	} else if due_date <= now + DUE_SOON_DAYS * DAY {
+		Some(ExpiryStatus::DueSoon)
+	} else {
+		None
+	}
+}
+
+// an entry or one of its fields that is expired or due soon
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DueItem {
+	pub id: usize,
+	pub entry_title: String,
+	// none when the expiry is set on the entry itself
+	pub field_title: Option<String>,
+	pub due_date: u64,
+	pub status: ExpiryStatus,
+}
+
+impl std::fmt::Display for DueItem {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match &self.field_title {
+			Some(field_title) => write!(f, "{} › {}", self.entry_title, field_title),
+			None => write!(f, "{}", self.entry_title),
+		}
+	}
+}
+
+// the text of the toast shown after unlocking
+pub fn get_due_summary(items: &[DueItem]) -> Option<String> {
+	if items.is_empty() {
+		return None;
+	}
+
+	let expired =
+		items.iter().filter(|item| item.status == ExpiryStatus::Expired).count();
+	let due_soon = items.len() - expired;
+
+	let mut counts = Vec::new();
+	if expired > 0 {
+		counts.push(format!("{} expired", expired));
+	}
+	if due_soon > 0 {
+		counts.push(format!("{} due within {} days", due_soon, DUE_SOON_DAYS));
+	}
+
+	let mut sorted = items.to_vec();
+	sorted
+		.sort_by(|a, b| b.status.cmp(&a.status).then(a.due_date.cmp(&b.due_date)));
+	let mut titles = sorted
+		.iter()
+		.take(SUMMARY_TITLES)
+		.map(|item| item.to_string())
+		.collect::<Vec<String>>();
+	if sorted.len() > SUMMARY_TITLES {
+		titles.push(format!("and {} more", sorted.len() - SUMMARY_TITLES));
+	}
+
+	Some(format!(
+		"Rotation reminder: {}\n{}",
+		counts.join(", "),
+		titles.join("\n")
+	))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn item(title: &str, due_date: u64, status: ExpiryStatus) -> DueItem {
+		DueItem {
+			id: 1,
+			entry_title: String::from(title),
+			field_title: None,
+			due_date,
+			status,
+		}
+	}
+
+	#[test]
+	fn parse_expiry_test() {
+		assert_eq!(parse_expiry(""), Ok(None));
+		assert_eq!(parse_expiry(" 90d "), Ok(Some(Expiry::Interval(90))));
+		assert_eq!(parse_expiry("30"), Ok(Some(Expiry::Interval(30))));
+		assert_eq!(parse_expiry("2024-01-02"), Ok(Some(Expiry::Date(1704153600))));
+		assert_eq!(parse_expiry("0d"), Err(ExpiryError::Format));
+		assert_eq!(parse_expiry("soon"), Err(ExpiryError::Format));
+		assert_eq!(parse_expiry("2024-13-01"), Err(ExpiryError::Format));
+
+		assert_eq!(Expiry::Interval(90).to_string(), "90d");
+		assert_eq!(Expiry::Date(1704153600).to_string(), "2024-01-02");
+	}
+
+	#[test]
+	fn due_date_test() {
+		assert_eq!(Expiry::Interval(2).get_due_date(100), 100 + 2 * DAY);
+		assert_eq!(Expiry::Date(500).get_due_date(100), 500);
+
+		assert_eq!(
+			Expiry::Date(100 + 90 * DAY).reset(100, 1000),
+			Expiry::Date(1000 + 90 * DAY)
+		);
+		assert_eq!(
+			Expiry::Date(50).reset(100, 1000),
+			Expiry::Date(1000 + RESET_DAYS * DAY)
+		);
+		assert_eq!(
+			Expiry::Date(100).reset(100, 1000),
+			Expiry::Date(1000 + RESET_DAYS * DAY)
+		);
+		assert_eq!(Expiry::Interval(90).reset(100, 1000), Expiry::Interval(90));
+
+		let now = 1000 * DAY;
+		assert_eq!(get_status(now - 1, now), Some(ExpiryStatus::Expired));
+		assert_eq!(get_status(now, now), Some(ExpiryStatus::Expired));
+		assert_eq!(get_status(now + DAY, now), Some(ExpiryStatus::DueSoon));
+		assert_eq!(get_status(now + DUE_SOON_DAYS * DAY + 1, now), None);
+	}
+
+	#[test]
+	fn summary_test() {
+		assert_eq!(get_due_summary(&[]), None);
+		assert_eq!(
+			get_due_summary(&[
+				item("Mail", 30, ExpiryStatus::DueSoon),
+				item("Bank", 20, ExpiryStatus::Expired),
+			]),
+			Some(String::from(
+				"Rotation reminder: 1 expired, 1 due within 14 days\nBank\nMail"
+			))
+		);
+
+		let items = (0..5)
+			.map(|i| item(&format!("Server {}", i), i, ExpiryStatus::DueSoon))
+			.collect::<Vec<DueItem>>();
+		assert_eq!(
+			get_due_summary(&items),
+			Some(String::from(
+				"Rotation reminder: 5 due within 14 days\nServer 0\nServer 1\nServer 2\nand 2 more"
+			))
+		);
+	}
+}
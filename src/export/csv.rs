@@ -0,0 +1,208 @@
+use crate::db::{Db, DynFieldKind};
+
+const DELIMITER: char = ',';
+
+// PRIVATE: quote cells that would otherwise break the row apart
+fn quote(cell: &str) -> String {
+	if cell.contains([DELIMITER, '"', '\n', '\r'])
+		|| cell.starts_with(' ')
+		|| cell.ends_with(' ')
+	{
+		format!("\"{}\"", cell.replace('"', "\"\""))
+	} else {
+		String::from(cell)
+	}
+}
+
+// export the latest value of every visible field, each field title gets its own
+// column and entries with the same title twice fill a second column of it
+pub fn to_csv(db: &Db) -> String {
+	let rows = db
+		.get_sidebar_list()
+		.iter()
+		.rev()
+		.map(|(id, title, _)| {
+			let fields = db
+				.get_visible_fields(id)
+				.iter()
+				.filter(|field| db.get_field_kind(id, field) != DynFieldKind::Heading)
+				.map(|field| {
+					(db.get_name_of_field(id, field), db.get_last_by_field(id, field))
+				})
+				.collect::<Vec<(String, String)>>();
+			(title.clone(), fields)
+		})
+		.collect::<Vec<(String, Vec<(String, String)>)>>();
+
+	// the columns are a field title and how often it came up in the entry before
+	let mut columns: Vec<(String, usize)> = Vec::new();
+	for (_, fields) in &rows {
+		for (idx, (title, _)) in fields.iter().enumerate() {
+			let column = (
+				title.clone(),
+				fields[..idx].iter().filter(|(other, _)| other == title).count(),
+			);
+			if !columns.contains(&column) {
+				columns.push(column);
+			}
+		}
+	}
+
+	let mut csv = std::iter::once(String::from("Title"))
+		.chain(columns.iter().map(|(title, _)| quote(title)))
+		.collect::<Vec<String>>()
+		.join(&DELIMITER.to_string());
+	csv.push('\n');
+
+	for (title, fields) in rows {
+		let mut cells = vec![quote(&title)];
+		for (column_title, occurrence) in &columns {
+			cells.push(
+				fields
+					.iter()
+					.filter(|(title, _)| title == column_title)
+					.nth(*occurrence)
+					.map(|(_, value)| quote(value))
+					.unwrap_or_default(),
+			);
+		}
+		csv.push_str(&cells.join(&DELIMITER.to_string()));
+		csv.push('\n');
+	}
+
+	csv
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		db::{DynField, NewDbEntry},
+		import::csv::{guess_mapping, parse, to_entries, ColumnMapping},
+	};
+
+	#[test]
+	fn quote_test() {
+		assert_eq!(quote("plain"), "plain");
+		assert_eq!(quote("a,b"), "\"a,b\"");
+		assert_eq!(quote("say \"hi\""), "\"say \"\"hi\"\"\"");
+		assert_eq!(quote("line\nbreak"), "\"line\nbreak\"");
+		assert_eq!(quote(" padded"), "\" padded\"");
+	}
+
+	#[test]
+	fn round_trip_test() {
+		let db = Db::from_new_entries(vec![
+			NewDbEntry {
+				title: String::from("Mail, private"),
+				fields: vec![
+					DynField::new(
+						DynFieldKind::TextLine,
+						String::from("Username"),
+						vec![(10, String::from("me"))],
+					),
+					DynField::new(
+						DynFieldKind::TextLineSecret,
+						String::from("Password"),
+						vec![(10, String::from("old")), (20, String::from("new \"one\""))],
+					),
+					DynField::new(
+						DynFieldKind::Heading,
+						String::from("Recovery"),
+						vec![(10, String::from("Recovery"))],
+					),
+					DynField::new(
+						DynFieldKind::TextLineSecret,
+						String::from("Password"),
+						vec![(10, String::from("second"))],
+					),
+					DynField::new(
+						DynFieldKind::TextLine,
+						String::from("Old"),
+						vec![(10, String::from("hidden"))],
+					)
+					.with_visibility(false),
+				],
+				tags: Vec::new(),
+			},
+			NewDbEntry {
+				title: String::from("Shop"),
+				fields: vec![
+					DynField::new(
+						DynFieldKind::Url,
+						String::from("URL"),
+						vec![(10, String::from("https://shop.com"))],
+					),
+					DynField::new(
+						DynFieldKind::MultiLine,
+						String::from("Notes"),
+						vec![(10, String::from("line one\nline two"))],
+					),
+				],
+				tags: Vec::new(),
+			},
+		]);
+
+		let csv = to_csv(&db);
+		assert_eq!(
+			csv.lines().next(),
+			Some("Title,Username,Password,Password,URL,Notes")
+		);
+
+		let rows = parse(&csv, DELIMITER).unwrap();
+		let mappings = rows[0]
+			.iter()
+			.map(|header| guess_mapping(header))
+			.collect::<Vec<ColumnMapping>>();
+		let entries = to_entries(&rows, &mappings, true, 10);
+		assert_eq!(entries.len(), 2);
+		assert_eq!(entries[0].title, "Mail, private");
+		assert_eq!(entries[1].title, "Shop");
+
+		let imported = Db::from_new_entries(entries);
+		let values = |id: &usize| {
+			imported
+				.get_fields(id)
+				.iter()
+				.map(|(field, _)| {
+					(
+						imported.get_name_of_field(id, field),
+						imported.get_field_kind(id, field),
+						imported.get_last_by_field(id, field),
+					)
+				})
+				.collect::<Vec<(String, DynFieldKind, String)>>()
+		};
+		assert_eq!(
+			values(&1),
+			vec![
+				(String::from("Username"), DynFieldKind::TextLine, String::from("me")),
+				(
+					String::from("Password"),
+					DynFieldKind::TextLineSecret,
+					String::from("new \"one\"")
+				),
+				(
+					String::from("Password"),
+					DynFieldKind::TextLineSecret,
+					String::from("second")
+				),
+			]
+		);
+		assert_eq!(
+			values(&2),
+			vec![
+				(
+					String::from("URL"),
+					DynFieldKind::Url,
+					String::from("https://shop.com")
+				),
+				(
+					String::from("Notes"),
+					DynFieldKind::MultiLine,
+					String::from("line one\nline two")
+				),
+			]
+		);
+	}
+}
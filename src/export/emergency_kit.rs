@@ -0,0 +1,173 @@
+use crate::{
+	db::{Db, DynFieldKind},
+	expiry::format_date,
+	qr::{to_svg, totp_uri},
+};
+
+// everything is inlined so the file works offline and prints on its own
+const STYLE: &str = "
+	body { font-family: sans-serif; font-size: 11pt; color: #000; max-width: 800px; margin: 0 auto; padding: 20px; }
+	h1 { margin-bottom: 0; }
+	h2 { font-size: 14pt; margin: 0 0 5px; }
+	dt { font-weight: bold; }
+	dd { margin: 0 0 5px; font-family: monospace; word-break: break-all; }
+	.warning { border: 2px solid #000; padding: 10px; font-weight: bold; }
+	.entry { border-top: 1px solid #000; padding: 10px 0; break-inside: avoid; page-break-inside: avoid; }
+	.tags { margin: 0 0 5px; color: #555; }
+	table { width: 100%; border-collapse: collapse; }
+	th, td { text-align: left; vertical-align: top; padding: 3px 5px; border-bottom: 1px solid #ccc; }
+	th { width: 30%; font-weight: normal; color: #555; }
+	td { font-family: monospace; white-space: pre-wrap; word-break: break-all; }
+	th.heading { color: #000; font-weight: bold; padding-top: 10px; }
+	.qr svg { display: block; width: 35mm; height: 35mm; margin-top: 5px; }
+	@media print { body { padding: 0; max-width: none; } }
+";
+
+// PRIVATE: the values end up in the markup so nothing in them may be read as html
+fn escape(value: &str) -> String {
+	value
+		.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;")
+		.replace('\'', "&#39;")
+}
+
+// PRIVATE: the visible fields of an entry as table rows, totp seeds get a qr code
+fn entry_rows(db: &Db, id: &usize, title: &str) -> String {
+	let mut rows = String::new();
+
+	for field in db.get_visible_fields(id) {
+		let name = db.get_name_of_field(id, &field);
+		if db.get_field_kind(id, &field) == DynFieldKind::Heading {
+			rows.push_str(&format!(
+				"<tr><th colspan=\"2\" class=\"heading\">{}</th></tr>\n",
+				escape(&name)
+			));
+			continue;
+		}
+
+		let value = db.get_last_by_field(id, &field);
+		let qr = match totp_uri(title, &name, &value).map(|uri| to_svg(&uri)) {
+			Some(Ok(svg)) => format!("<div class=\"qr\">{}</div>", svg),
+			Some(Err(error)) => format!("<div>{}</div>", escape(&error.to_string())),
+			None => String::new(),
+		};
+		rows.push_str(&format!(
+			"<tr><th>{}</th><td>{}{}</td></tr>\n",
+			escape(&name),
+			escape(&value),
+			qr
+		));
+	}
+
+	rows
+}
+
+// a printable page of the given entries with their current values for when the
+// vault itself can't be opened anymore
+pub fn to_html(
+	db: &Db,
+	ids: &[usize],
+	vault_path: &str,
+	timestamp: u64,
+) -> String {
+	let mut html = format!(
+		"<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Vault emergency kit</title>\n<style>{}</style>\n</head>\n<body>\n",
+		STYLE
+	);
+	html.push_str("<h1>Vault emergency kit</h1>\n<dl>\n");
+	html.push_str(&format!(
+		"<dt>Created</dt><dd>{}</dd>\n<dt>Vault location</dt><dd>{}</dd>\n<dt>Entries</dt><dd>{}</dd>\n</dl>\n",
+		format_date(timestamp),
+		escape(vault_path),
+		ids.len()
+	));
+	html.push_str("<p class=\"warning\">This page holds your secrets unencrypted. Print it, keep the paper somewhere safe and delete this file.</p>\n");
+
+	// in the order they were added, like the exports
+	for (id, title, _) in db.get_sidebar_list().iter().rev() {
+		if !ids.contains(id) {
+			continue;
+		}
+
+		html.push_str(&format!(
+			"<section class=\"entry\">\n<h2>{}</h2>\n",
+			escape(title)
+		));
+		let tags = db.get_tags(id);
+		if !tags.is_empty() {
+			html.push_str(&format!(
+				"<p class=\"tags\">{}</p>\n",
+				tags
+					.iter()
+					.map(|tag| format!("#{}", escape(tag)))
+					.collect::<Vec<String>>()
+					.join(" ")
+			));
+		}
+		html.push_str(&format!(
+			"<table>\n{}</table>\n</section>\n",
+			entry_rows(db, id, title)
+		));
+	}
+
+	html.push_str("</body>\n</html>\n");
+	html
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::db::{DynField, NewDbEntry};
+
+	#[test]
+	fn to_html_test() {
+		let db = Db::from_new_entries(vec![
+			NewDbEntry {
+				title: String::from("Mail <work>"),
+				fields: vec![
+					DynField::new(
+						DynFieldKind::TextLineSecret,
+						String::from("Password"),
+						vec![(10, String::from("old")), (20, String::from("a&b"))],
+					),
+					DynField::new(
+						DynFieldKind::Heading,
+						String::from("Recovery"),
+						vec![(10, String::from("Recovery"))],
+					),
+					DynField::new(
+						DynFieldKind::TextLineSecret,
+						String::from("TOTP"),
+						vec![(10, String::from("JBSW Y3DP"))],
+					),
+					DynField::new(
+						DynFieldKind::TextLine,
+						String::from("Old"),
+						vec![(10, String::from("hidden value"))],
+					)
+					.with_visibility(false),
+				],
+				tags: vec![String::from("work")],
+			},
+			NewDbEntry {
+				title: String::from("Not selected"),
+				fields: Vec::new(),
+				tags: Vec::new(),
+			},
+		]);
+
+		let html = to_html(&db, &[1], "/home/me/vault.toml", 1704153600);
+		assert!(html.contains("<dd>2024-01-02</dd>"));
+		assert!(html.contains("<dd>/home/me/vault.toml</dd>"));
+		assert!(html.contains("<h2>Mail &lt;work&gt;</h2>"));
+		assert!(html.contains("<p class=\"tags\">#work</p>"));
+		assert!(html.contains("<tr><th>Password</th><td>a&amp;b</td></tr>"));
+		assert!(!html.contains(">old<"));
+		assert!(html.contains("<th colspan=\"2\" class=\"heading\">Recovery</th>"));
+		assert!(html.contains("<td>JBSW Y3DP<div class=\"qr\"><svg"));
+		assert!(!html.contains("hidden value"));
+		assert!(!html.contains("Not selected"));
+	}
+}
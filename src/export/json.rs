@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+
+use crate::db::{Db, DynFieldKind};
+
+// tells our own json exports apart from the ones of other managers
+pub const FORMAT: &str = "vault";
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonExport {
+	pub format: String,
+	pub version: u32,
+	pub entries: Vec<JsonEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonEntry {
+	pub title: String,
+	#[serde(default)]
+	pub tags: Vec<String>,
+	#[serde(default)]
+	pub fields: Vec<JsonField>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonField {
+	pub title: String,
+	pub kind: DynFieldKind,
+	#[serde(default = "default_visible")]
+	pub visible: bool,
+	// ordered from old to new
+	pub history: Vec<JsonValue>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonValue {
+	pub date: u64,
+	pub value: String,
+}
+
+fn default_visible() -> bool {
+	true
+}
+
+// export every entry with all its fields and their history, references are
+// replaced by the value they point to as the ids mean nothing outside this vault
+pub fn to_json(db: &Db) -> Result<String, serde_json::Error> {
+	let entries = db
+		.get_sidebar_list()
+		.iter()
+		.rev()
+		.map(|(id, title, _)| JsonEntry {
+			title: title.clone(),
+			tags: db.get_tags(id),
+			fields: db
+				.get_fields(id)
+				.iter()
+				.map(|(field, visible)| {
+					let history = match db.get_field_kind(id, field) {
+						DynFieldKind::Reference => vec![JsonValue {
+							date: db
+								.get_history_dates(id, field)
+								.first()
+								.map(|(_, date)| *date)
+								.unwrap_or_default(),
+							value: db.get_last_by_field(id, field),
+						}],
+						_ => db
+							.get_history(id, field)
+							.unwrap_or_default()
+							.into_iter()
+							.rev()
+							.map(|(date, value)| JsonValue { date, value })
+							.collect(),
+					};
+					JsonField {
+						title: db.get_name_of_field(id, field),
+						kind: db.get_resolved_field_kind(id, field),
+						visible: *visible,
+						history,
+					}
+				})
+				.collect(),
+		})
+		.collect();
+
+	serde_json::to_string_pretty(&JsonExport {
+		format: String::from(FORMAT),
+		version: VERSION,
+		entries,
+	})
+}
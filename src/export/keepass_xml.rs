@@ -0,0 +1,291 @@
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, SecondsFormat};
+
+use crate::db::{Db, DynFieldKind};
+
+// the strings keepass shows in its own columns
+const STANDARD_KEYS: [&str; 5] =
+	["Title", "UserName", "Password", "URL", "Notes"];
+
+// PRIVATE: escape text for xml and drop the control characters xml can't hold
+fn escape(value: &str) -> String {
+	let mut escaped = String::with_capacity(value.len());
+	for c in value.chars() {
+		match c {
+			'&' => escaped.push_str("&amp;"),
+			'<' => escaped.push_str("&lt;"),
+			'>' => escaped.push_str("&gt;"),
+			'"' => escaped.push_str("&quot;"),
+			'\'' => escaped.push_str("&apos;"),
+			'\t' | '\n' | '\r' => escaped.push(c),
+			c if c.is_control() => {},
+			c => escaped.push(c),
+		}
+	}
+	escaped
+}
+
+// PRIVATE: keepass identifies groups and entries by random uuids
+fn uuid() -> String {
+	general_purpose::STANDARD.encode(rand::random::<[u8; 16]>())
+}
+
+fn time(timestamp: u64) -> String {
+	DateTime::from_timestamp(timestamp as i64, 0)
+		.unwrap_or_default()
+		.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+// PRIVATE: a field of the entry and the keepass string it's exported as
+struct KeePassField {
+	key: String,
+	protected: bool,
+	// ordered from old to new
+	history: Vec<(u64, String)>,
+}
+
+// PRIVATE: the vault fields that match the standard keepass strings take their
+// place, all others become custom strings with unique keys
+fn to_fields(db: &Db, id: &usize) -> Vec<KeePassField> {
+	let mut keys = vec![String::from("Title")];
+	let mut fields = Vec::new();
+
+	for field in db.get_visible_fields(id) {
+		let kind = db.get_resolved_field_kind(id, &field);
+		if kind == DynFieldKind::Heading {
+			continue;
+		}
+
+		let title = db.get_name_of_field(id, &field);
+		let standard = match (title.to_lowercase().as_str(), &kind) {
+			("username" | "user name" | "login" | "user", _) => "UserName",
+			("password", _) => "Password",
+			(_, DynFieldKind::Url) => "URL",
+			("notes" | "note", _) => "Notes",
+			_ => "",
+		};
+		let key = if !standard.is_empty() && !keys.iter().any(|key| key == standard)
+		{
+			String::from(standard)
+		} else {
+			let mut key = title.clone();
+			let mut count = 1;
+			while key.is_empty()
+				|| STANDARD_KEYS.contains(&key.as_str())
+				|| keys.contains(&key)
+			{
+				count += 1;
+				key = format!("{} ({})", title, count);
+			}
+			key
+		};
+		keys.push(key.clone());
+
+		let history = match db.get_field_kind(id, &field) {
+			DynFieldKind::Reference => vec![(
+				db.get_history_dates(id, &field)
+					.first()
+					.map(|(_, date)| *date)
+					.unwrap_or_default(),
+				db.get_last_by_field(id, &field),
+			)],
+			_ => db
+				.get_history(id, &field)
+				.unwrap_or_default()
+				.into_iter()
+				.rev()
+				.collect(),
+		};
+
+		fields.push(KeePassField {
+			key,
+			protected: matches!(
+				kind,
+				DynFieldKind::TextLineSecret | DynFieldKind::MultiLineSecret
+			),
+			history,
+		});
+	}
+
+	fields
+}
+
+// PRIVATE: one version of an entry with the values its fields had at that time
+fn write_version(
+	xml: &mut String,
+	title: &str,
+	tags: &str,
+	fields: &[KeePassField],
+	timestamp: u64,
+	indent: &str,
+) {
+	xml.push_str(&format!("{indent}<UUID>{}</UUID>\n", uuid()));
+	xml.push_str(&format!("{indent}<Tags>{}</Tags>\n", escape(tags)));
+	xml.push_str(&format!(
+		"{indent}<Times>\n{indent}\t<LastModificationTime>{}</LastModificationTime>\n{indent}</Times>\n",
+		time(timestamp)
+	));
+	xml.push_str(&format!(
+		"{indent}<String>\n{indent}\t<Key>Title</Key>\n{indent}\t<Value>{}</Value>\n{indent}</String>\n",
+		escape(title)
+	));
+
+	for field in fields {
+		if let Some((_, value)) =
+			field.history.iter().rev().find(|(date, _)| *date <= timestamp)
+		{
+			let attribute = if field.protected {
+				" ProtectInMemory=\"True\""
+			} else {
+				""
+			};
+			xml.push_str(&format!(
+				"{indent}<String>\n{indent}\t<Key>{}</Key>\n{indent}\t<Value{}>{}</Value>\n{indent}</String>\n",
+				escape(&field.key),
+				attribute,
+				escape(value)
+			));
+		}
+	}
+}
+
+// export every entry as an unencrypted keepass 2 xml file, each older date of a
+// value becomes a copy of the entry in its history
+pub fn to_keepass_xml(db: &Db) -> String {
+	let mut xml = String::from(
+		"<?xml version=\"1.0\" encoding=\"utf-8\" standalone=\"yes\"?>\n<KeePassFile>\n",
+	);
+	xml.push_str("\t<Meta>\n\t\t<Generator>Vault</Generator>\n");
+	xml.push_str("\t\t<DatabaseName>Vault</DatabaseName>\n\t</Meta>\n");
+	xml.push_str("\t<Root>\n\t\t<Group>\n");
+	xml.push_str(&format!("\t\t\t<UUID>{}</UUID>\n", uuid()));
+	xml.push_str("\t\t\t<Name>Vault</Name>\n");
+
+	for (id, title, _) in db.get_sidebar_list().iter().rev() {
+		let tags = db.get_tags(id).join(";");
+		let fields = to_fields(db, id);
+		let mut dates = fields
+			.iter()
+			.flat_map(|field| field.history.iter().map(|(date, _)| *date))
+			.collect::<Vec<u64>>();
+		dates.sort();
+		dates.dedup();
+		let modified = dates.pop().unwrap_or_default();
+
+		xml.push_str("\t\t\t<Entry>\n");
+		write_version(&mut xml, title, &tags, &fields, modified, "\t\t\t\t");
+		if !dates.is_empty() {
+			xml.push_str("\t\t\t\t<History>\n");
+			for date in dates {
+				xml.push_str("\t\t\t\t\t<Entry>\n");
+				write_version(&mut xml, title, &tags, &fields, date, "\t\t\t\t\t\t");
+				xml.push_str("\t\t\t\t\t</Entry>\n");
+			}
+			xml.push_str("\t\t\t\t</History>\n");
+		}
+		xml.push_str("\t\t\t</Entry>\n");
+	}
+
+	xml.push_str("\t\t</Group>\n\t</Root>\n</KeePassFile>\n");
+	xml
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		db::{DynField, NewDbEntry},
+		import::kdbx::xml_to_entries,
+	};
+
+	#[test]
+	fn escape_test() {
+		assert_eq!(
+			escape("a & <b> \"c\" 'd'"),
+			"a &amp; &lt;b&gt; &quot;c&quot; &apos;d&apos;"
+		);
+		assert_eq!(escape("line\none\u{0}\u{1b}"), "line\none");
+	}
+
+	#[test]
+	fn round_trip_test() {
+		let db = Db::from_new_entries(vec![NewDbEntry {
+			title: String::from("Mail & co"),
+			fields: vec![
+				DynField::new(
+					DynFieldKind::TextLine,
+					String::from("Username"),
+					vec![(10, String::from("me"))],
+				),
+				DynField::new(
+					DynFieldKind::TextLineSecret,
+					String::from("Password"),
+					vec![(10, String::from("old")), (30, String::from("<new>"))],
+				),
+				DynField::new(
+					DynFieldKind::Url,
+					String::from("Website"),
+					vec![(10, String::from("https://mail.com"))],
+				),
+				DynField::new(
+					DynFieldKind::TextLineSecret,
+					String::from("PIN"),
+					vec![(20, String::from("1234"))],
+				),
+				DynField::new(
+					DynFieldKind::TextLine,
+					String::from("Title"),
+					vec![(10, String::from("Dr"))],
+				),
+				DynField::new(
+					DynFieldKind::MultiLine,
+					String::from("Notes"),
+					vec![(10, String::from("line one\nline two"))],
+				),
+				DynField::new(
+					DynFieldKind::TextLine,
+					String::from("Old"),
+					vec![(10, String::from("hidden"))],
+				)
+				.with_visibility(false),
+			],
+			tags: vec![String::from("work"), String::from("mail")],
+		}]);
+
+		let entries = xml_to_entries(&to_keepass_xml(&db)).unwrap();
+		assert_eq!(entries.len(), 1);
+		assert_eq!(entries[0].title, "Mail & co");
+		assert_eq!(entries[0].tags, vec!["work", "mail"]);
+
+		let imported = Db::from_new_entries(entries);
+		let fields = imported.get_fields(&1);
+		assert_eq!(
+			fields
+				.iter()
+				.map(|(field, _)| imported.get_name_of_field(&1, field))
+				.collect::<Vec<String>>(),
+			vec!["Username", "Password", "URL", "PIN", "Title (2)", "Notes"]
+		);
+		assert_eq!(
+			imported.get_history(&1, &fields[1].0).unwrap(),
+			im::vector![(30, String::from("<new>")), (10, String::from("old"))]
+		);
+		assert_eq!(
+			imported.get_field_kind(&1, &fields[1].0),
+			DynFieldKind::TextLineSecret
+		);
+		assert_eq!(
+			imported.get_history(&1, &fields[3].0).unwrap(),
+			im::vector![(20, String::from("1234"))]
+		);
+		assert_eq!(
+			imported.get_field_kind(&1, &fields[3].0),
+			DynFieldKind::TextLineSecret
+		);
+		assert_eq!(imported.get_field_kind(&1, &fields[2].0), DynFieldKind::Url);
+		assert_eq!(
+			imported.get_last_by_field(&1, &fields[5].0),
+			"line one\nline two"
+		);
+	}
+}
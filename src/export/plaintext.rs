@@ -0,0 +1,116 @@
+use std::{fs, io::Write, path::Path};
+
+use crate::{
+	db::Db,
+	export::{csv::to_csv, json::to_json, keepass_xml::to_keepass_xml},
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum ExportError {
+	#[error("Failed to create the JSON export: {0}")]
+	Json(#[from] serde_json::Error),
+	#[error("Failed to write the export file: {0}")]
+	Io(#[from] std::io::Error),
+}
+
+// the unencrypted formats the vault can be exported to
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum PlaintextFormat {
+	// all fields with their history
+	#[default]
+	Json,
+	// only the latest values
+	Csv,
+	KeePassXml,
+}
+
+impl std::fmt::Display for PlaintextFormat {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			PlaintextFormat::Json => write!(f, "JSON"),
+			PlaintextFormat::Csv => write!(f, "CSV"),
+			PlaintextFormat::KeePassXml => write!(f, "KeePass XML"),
+		}
+	}
+}
+
+impl PlaintextFormat {
+	pub fn all_values() -> Vec<PlaintextFormat> {
+		vec![
+			PlaintextFormat::Json,
+			PlaintextFormat::Csv,
+			PlaintextFormat::KeePassXml,
+		]
+	}
+
+	pub fn extension(&self) -> &'static str {
+		match self {
+			PlaintextFormat::Json => "json",
+			PlaintextFormat::Csv => "csv",
+			PlaintextFormat::KeePassXml => "xml",
+		}
+	}
+
+	// what the export holds so the warning can say what's at stake
+	pub fn description(&self) -> &'static str {
+		match self {
+			PlaintextFormat::Json => "every field and its whole history",
+			PlaintextFormat::Csv => "the latest value of every visible field",
+			PlaintextFormat::KeePassXml => {
+				"every visible field and its history in a format KeePass can import"
+			},
+		}
+	}
+}
+
+pub fn export(db: &Db, format: PlaintextFormat) -> Result<String, ExportError> {
+	Ok(match format {
+		PlaintextFormat::Json => to_json(db)?,
+		PlaintextFormat::Csv => to_csv(db),
+		PlaintextFormat::KeePassXml => to_keepass_xml(db),
+	})
+}
+
+// write a file only the current user can read, an existing file loses any wider
+// permissions before the secrets go into it
+pub fn write_private(path: &Path, content: &str) -> Result<(), ExportError> {
+	let mut options = fs::OpenOptions::new();
+	options.write(true).create(true).truncate(true);
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+		options.mode(0o600);
+		if path.exists() {
+			fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+		}
+	}
+
+	let mut file = options.open(path)?;
+	file.write_all(content.as_bytes())?;
+	file.sync_all()?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	#[cfg(unix)]
+	fn write_private_test() {
+		use std::os::unix::fs::PermissionsExt;
+
+		let path = std::env::temp_dir()
+			.join(format!("vault-plaintext-{}.json", rand::random::<u32>()));
+		fs::write(&path, "old content that is longer").unwrap();
+		fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+		write_private(&path, "secret").unwrap();
+		let mode = fs::metadata(&path).unwrap().permissions().mode();
+		let content = fs::read_to_string(&path).unwrap();
+		fs::remove_file(&path).unwrap();
+
+		assert_eq!(mode & 0o777, 0o600);
+		assert_eq!(content, "secret");
+	}
+}
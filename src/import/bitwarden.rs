@@ -0,0 +1,657 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose, Engine as _};
+use cbc::cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit};
+use chrono::DateTime;
+use hmac::{Hmac, Mac};
+use pbkdf2::pbkdf2_hmac;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use zeroize::Zeroize;
+
+use crate::db::{DynField, DynFieldKind, NewDbEntry};
+
+const KDF_PBKDF2: u8 = 0;
+const KDF_ARGON2ID: u8 = 1;
+// the item types of a bitwarden export
+const TYPE_LOGIN: u8 = 1;
+const TYPE_CARD: u8 = 3;
+const TYPE_IDENTITY: u8 = 4;
+const TYPE_SSH_KEY: u8 = 5;
+// the custom field types of a bitwarden export
+const FIELD_HIDDEN: u8 = 1;
+const FIELD_LINKED: u8 = 3;
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum BitwardenError {
+	#[error("Failed to read the Bitwarden export: {0}")]
+	Json(String),
+	#[error("This export is locked to a Bitwarden account, export it with a password instead")]
+	AccountRestricted,
+	#[error("Enter the password of the Bitwarden export")]
+	MissingPassword,
+	#[error("Wrong password")]
+	WrongPassword,
+	#[error("The Bitwarden export is damaged")]
+	Corrupt,
+	#[error("The Bitwarden export uses an unsupported key derivation")]
+	UnsupportedKdf,
+	#[error("Failed to derive the key: {0}")]
+	Argon2(#[from] argon2::Error),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Export {
+	#[serde(default)]
+	encrypted: bool,
+	#[serde(default)]
+	password_protected: bool,
+	salt: Option<String>,
+	kdf_type: Option<u8>,
+	kdf_iterations: Option<u32>,
+	kdf_memory: Option<u32>,
+	kdf_parallelism: Option<u32>,
+	#[serde(rename = "encKeyValidation_DO_NOT_EDIT")]
+	enc_key_validation: Option<String>,
+	data: Option<String>,
+	#[serde(default)]
+	folders: Vec<Folder>,
+	#[serde(default)]
+	collections: Vec<Folder>,
+	#[serde(default)]
+	items: Vec<Item>,
+}
+
+#[derive(Deserialize)]
+struct Folder {
+	id: String,
+	name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Item {
+	#[serde(rename = "type")]
+	kind: u8,
+	name: Option<String>,
+	notes: Option<String>,
+	folder_id: Option<String>,
+	collection_ids: Option<Vec<String>>,
+	fields: Option<Vec<CustomField>>,
+	login: Option<Login>,
+	card: Option<Card>,
+	identity: Option<Identity>,
+	ssh_key: Option<SshKey>,
+	password_history: Option<Vec<PasswordHistory>>,
+	revision_date: Option<String>,
+	deleted_date: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CustomField {
+	name: Option<String>,
+	value: Option<String>,
+	#[serde(rename = "type")]
+	kind: u8,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Login {
+	uris: Option<Vec<Uri>>,
+	username: Option<String>,
+	password: Option<String>,
+	totp: Option<String>,
+	password_revision_date: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Uri {
+	uri: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Card {
+	cardholder_name: Option<String>,
+	brand: Option<String>,
+	number: Option<String>,
+	exp_month: Option<String>,
+	exp_year: Option<String>,
+	code: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Identity {
+	title: Option<String>,
+	first_name: Option<String>,
+	middle_name: Option<String>,
+	last_name: Option<String>,
+	address1: Option<String>,
+	address2: Option<String>,
+	address3: Option<String>,
+	city: Option<String>,
+	state: Option<String>,
+	postal_code: Option<String>,
+	country: Option<String>,
+	company: Option<String>,
+	email: Option<String>,
+	phone: Option<String>,
+	ssn: Option<String>,
+	username: Option<String>,
+	passport_number: Option<String>,
+	license_number: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SshKey {
+	private_key: Option<String>,
+	public_key: Option<String>,
+	key_fingerprint: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PasswordHistory {
+	last_used_date: Option<String>,
+	password: Option<String>,
+}
+
+// PRIVATE: bitwarden stretches the derived key into one key for aes and one for the hmac
+fn expand_key(key: &[u8], info: &[u8]) -> [u8; 32] {
+	let mut hmac =
+		Hmac::<Sha256>::new_from_slice(key).expect("Hmac takes keys of any length");
+	hmac.update(info);
+	hmac.update(&[1]);
+	hmac.finalize().into_bytes().into()
+}
+
+// PRIVATE: an encrypted value looks like "2.iv|data|mac" with each part in base64
+fn decrypt_value(
+	value: &str,
+	enc_key: &[u8; 32],
+	mac_key: &[u8; 32],
+) -> Result<Vec<u8>, BitwardenError> {
+	let parts = value
+		.strip_prefix("2.")
+		.ok_or(BitwardenError::Corrupt)?
+		.split('|')
+		.map(|part| general_purpose::STANDARD.decode(part))
+		.collect::<Result<Vec<Vec<u8>>, base64::DecodeError>>()
+		.map_err(|_| BitwardenError::Corrupt)?;
+	let [iv, data, mac] = parts.as_slice() else {
+		return Err(BitwardenError::Corrupt);
+	};
+
+	let mut hmac = Hmac::<Sha256>::new_from_slice(mac_key)
+		.expect("Hmac takes keys of any length");
+	hmac.update(iv);
+	hmac.update(data);
+	hmac.verify_slice(mac).map_err(|_| BitwardenError::WrongPassword)?;
+
+	let mut data = data.clone();
+	let len = cbc::Decryptor::<aes::Aes256>::new_from_slices(enc_key, iv)
+		.map_err(|_| BitwardenError::Corrupt)?
+		.decrypt_padded_mut::<Pkcs7>(&mut data)
+		.map_err(|_| BitwardenError::Corrupt)?
+		.len();
+	data.truncate(len);
+	Ok(data)
+}
+
+// PRIVATE: derive the key from the password the export was protected with and
+// decrypt the plain export inside it
+fn decrypt_export(
+	export: &Export,
+	password: &str,
+) -> Result<String, BitwardenError> {
+	if !export.password_protected {
+		return Err(BitwardenError::AccountRestricted);
+	}
+	if password.is_empty() {
+		return Err(BitwardenError::MissingPassword);
+	}
+	let salt = export.salt.as_ref().ok_or(BitwardenError::Corrupt)?;
+	let iterations = export.kdf_iterations.ok_or(BitwardenError::Corrupt)?;
+
+	let mut key = [0u8; 32];
+	match export.kdf_type.unwrap_or(KDF_PBKDF2) {
+		KDF_PBKDF2 => pbkdf2_hmac::<Sha256>(
+			password.as_bytes(),
+			salt.as_bytes(),
+			iterations,
+			&mut key,
+		),
+		KDF_ARGON2ID => {
+			let params = Params::new(
+				export.kdf_memory.ok_or(BitwardenError::Corrupt)? * 1024,
+				iterations,
+				export.kdf_parallelism.ok_or(BitwardenError::Corrupt)?,
+				Some(32),
+			)?;
+			Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+				.hash_password_into(
+					password.as_bytes(),
+					&Sha256::digest(salt.as_bytes()),
+					&mut key,
+				)?;
+		},
+		_ => return Err(BitwardenError::UnsupportedKdf),
+	}
+	let mut enc_key = expand_key(&key, b"enc");
+	let mut mac_key = expand_key(&key, b"mac");
+	key.zeroize();
+
+	let result = decrypt_value(
+		export.enc_key_validation.as_deref().unwrap_or(""),
+		&enc_key,
+		&mac_key,
+	)
+	.and_then(|mut validation| {
+		validation.zeroize();
+		// the password was right so a bad hmac here means the data was changed
+		decrypt_value(export.data.as_deref().unwrap_or(""), &enc_key, &mac_key)
+			.map_err(|error| match error {
+				BitwardenError::WrongPassword => BitwardenError::Corrupt,
+				error => error,
+			})
+	});
+	enc_key.zeroize();
+	mac_key.zeroize();
+
+	String::from_utf8(result?).map_err(|_| BitwardenError::Corrupt)
+}
+
+// PRIVATE: the dates of an export are iso dates
+fn parse_date(value: &Option<String>) -> Option<u64> {
+	value
+		.as_deref()
+		.and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+		.map(|date| date.timestamp().max(0) as u64)
+}
+
+// PRIVATE: empty values aren't worth a field
+fn push_field(
+	fields: &mut Vec<DynField>,
+	kind: DynFieldKind,
+	title: &str,
+	value: &Option<String>,
+	timestamp: u64,
+) {
+	if let Some(value) = value.as_deref().filter(|value| !value.trim().is_empty())
+	{
+		fields.push(DynField::new(
+			kind.for_value(value),
+			String::from(title),
+			vec![(timestamp, String::from(value))],
+		));
+	}
+}
+
+// PRIVATE: join the parts that are set
+fn join(parts: &[&Option<String>], separator: &str) -> Option<String> {
+	let joined = parts
+		.iter()
+		.filter_map(|part| part.as_deref())
+		.map(|part| part.trim())
+		.filter(|part| !part.is_empty())
+		.collect::<Vec<&str>>()
+		.join(separator);
+	Some(joined).filter(|joined| !joined.is_empty())
+}
+
+// PRIVATE: the password keeps its history with the dates it was replaced
+fn password_field(
+	item: &Item,
+	login: &Login,
+	timestamp: u64,
+) -> Option<DynField> {
+	let password = login.password.as_deref().filter(|value| !value.is_empty())?;
+
+	let mut history = item
+		.password_history
+		.iter()
+		.flatten()
+		.filter_map(|old| {
+			old.password.as_ref().filter(|value| !value.is_empty()).map(|value| {
+				(parse_date(&old.last_used_date).unwrap_or(0), value.clone())
+			})
+		})
+		.collect::<Vec<(u64, String)>>();
+	history.sort_by_key(|(date, _)| *date);
+	history.push((
+		parse_date(&login.password_revision_date).unwrap_or(timestamp),
+		String::from(password),
+	));
+
+	Some(DynField::new(
+		DynFieldKind::TextLineSecret,
+		String::from("Password"),
+		history,
+	))
+}
+
+// PRIVATE: turn one item into an entry with the fields of its type
+fn to_entry(
+	item: &Item,
+	tags: &HashMap<&str, Vec<String>>,
+	timestamp: u64,
+) -> NewDbEntry {
+	let timestamp = parse_date(&item.revision_date).unwrap_or(timestamp);
+	let mut fields = Vec::new();
+
+	match item.kind {
+		TYPE_LOGIN => {
+			if let Some(login) = &item.login {
+				push_field(
+					&mut fields,
+					DynFieldKind::TextLine,
+					"Username",
+					&login.username,
+					timestamp,
+				);
+				fields.extend(password_field(item, login, timestamp));
+				push_field(
+					&mut fields,
+					DynFieldKind::TextLineSecret,
+					"TOTP",
+					&login.totp,
+					timestamp,
+				);
+				for uri in login.uris.iter().flatten() {
+					push_field(
+						&mut fields,
+						DynFieldKind::Url,
+						"URL",
+						&uri.uri,
+						timestamp,
+					);
+				}
+			}
+		},
+		TYPE_CARD => {
+			if let Some(card) = &item.card {
+				let month =
+					card.exp_month.as_deref().map(|month| format!("{:0>2}", month));
+				let expiry = join(&[&month, &card.exp_year], "/");
+				for (kind, title, value) in [
+					(DynFieldKind::TextLine, "Cardholder name", &card.cardholder_name),
+					(DynFieldKind::TextLine, "Brand", &card.brand),
+					(DynFieldKind::TextLineSecret, "Number", &card.number),
+					(DynFieldKind::TextLine, "Expiry", &expiry),
+					(DynFieldKind::TextLineSecret, "Security code", &card.code),
+				] {
+					push_field(&mut fields, kind, title, value, timestamp);
+				}
+			}
+		},
+		TYPE_IDENTITY => {
+			if let Some(identity) = &item.identity {
+				let name = join(
+					&[
+						&identity.title,
+						&identity.first_name,
+						&identity.middle_name,
+						&identity.last_name,
+					],
+					" ",
+				);
+				let city =
+					join(&[&identity.city, &identity.state, &identity.postal_code], " ");
+				let address = join(
+					&[
+						&identity.address1,
+						&identity.address2,
+						&identity.address3,
+						&city,
+						&identity.country,
+					],
+					"\n",
+				);
+				for (kind, title, value) in [
+					(DynFieldKind::TextLine, "Name", &name),
+					(DynFieldKind::TextLine, "Username", &identity.username),
+					(DynFieldKind::TextLine, "Company", &identity.company),
+					(DynFieldKind::TextLine, "Email", &identity.email),
+					(DynFieldKind::TextLine, "Phone", &identity.phone),
+					(DynFieldKind::MultiLine, "Address", &address),
+					(DynFieldKind::TextLineSecret, "SSN", &identity.ssn),
+					(
+						DynFieldKind::TextLineSecret,
+						"Passport number",
+						&identity.passport_number,
+					),
+					(
+						DynFieldKind::TextLineSecret,
+						"License number",
+						&identity.license_number,
+					),
+				] {
+					push_field(&mut fields, kind, title, value, timestamp);
+				}
+			}
+		},
+		TYPE_SSH_KEY => {
+			if let Some(ssh_key) = &item.ssh_key {
+				for (kind, title, value) in [
+					(DynFieldKind::MultiLineSecret, "Private key", &ssh_key.private_key),
+					(DynFieldKind::TextLine, "Public key", &ssh_key.public_key),
+					(DynFieldKind::TextLine, "Fingerprint", &ssh_key.key_fingerprint),
+				] {
+					push_field(&mut fields, kind, title, value, timestamp);
+				}
+			}
+		},
+		// secure notes only have the notes every item has
+		_ => {},
+	}
+
+	for field in item.fields.iter().flatten() {
+		if field.kind == FIELD_LINKED {
+			continue;
+		}
+		let kind = if field.kind == FIELD_HIDDEN {
+			DynFieldKind::TextLineSecret
+		} else {
+			DynFieldKind::TextLine
+		};
+		let title = field
+			.name
+			.as_deref()
+			.filter(|name| !name.trim().is_empty())
+			.unwrap_or("Custom field");
+		push_field(&mut fields, kind, title, &field.value, timestamp);
+	}
+	push_field(
+		&mut fields,
+		DynFieldKind::MultiLine,
+		"Notes",
+		&item.notes,
+		timestamp,
+	);
+
+	let title = item
+		.name
+		.as_deref()
+		.map(|name| String::from(name.trim()))
+		.filter(|name| !name.is_empty())
+		.unwrap_or_else(|| String::from("Untitled entry"));
+
+	let item_tags = item
+		.folder_id
+		.iter()
+		.chain(item.collection_ids.iter().flatten())
+		.filter_map(|id| tags.get(id.as_str()))
+		.flatten()
+		.cloned()
+		.collect();
+
+	NewDbEntry {
+		title,
+		fields,
+		tags: item_tags,
+	}
+}
+
+// turn a bitwarden json export into vault entries, the password is only needed
+// for exports that are password protected
+pub fn to_entries(
+	content: &str,
+	password: &str,
+	timestamp: u64,
+) -> Result<Vec<NewDbEntry>, BitwardenError> {
+	let parse = |content: &str| {
+		serde_json::from_str::<Export>(content)
+			.map_err(|error| BitwardenError::Json(error.to_string()))
+	};
+
+	let mut export = parse(content)?;
+	if export.encrypted {
+		let mut decrypted = decrypt_export(&export, password)?;
+		let result = parse(&decrypted);
+		decrypted.zeroize();
+		export = result?;
+	}
+
+	// nested folders are named "parent/child" and each part becomes a tag
+	let tags = export
+		.folders
+		.iter()
+		.chain(export.collections.iter())
+		.map(|folder| {
+			(
+				folder.id.as_str(),
+				folder
+					.name
+					.split('/')
+					.map(|part| String::from(part.trim()))
+					.filter(|part| !part.is_empty())
+					.collect::<Vec<String>>(),
+			)
+		})
+		.collect::<HashMap<&str, Vec<String>>>();
+
+	Ok(
+		export
+			.items
+			.iter()
+			.filter(|item| item.deleted_date.is_none())
+			.map(|item| to_entry(item, &tags, timestamp))
+			.collect(),
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const PLAIN_FIXTURE: &str = include_str!("fixtures/bitwarden.json");
+	const PBKDF2_FIXTURE: &str = include_str!("fixtures/bitwarden_pbkdf2.json");
+	const ARGON2_FIXTURE: &str = include_str!("fixtures/bitwarden_argon2.json");
+
+	fn field_names(entry: &NewDbEntry) -> Vec<String> {
+		let db = crate::db::Db::from_new_entries(vec![NewDbEntry {
+			title: entry.title.clone(),
+			fields: entry.fields.clone(),
+			tags: Vec::new(),
+		}]);
+		db.get_fields(&1)
+			.iter()
+			.map(|(field, _)| db.get_name_of_field(&1, field))
+			.collect()
+	}
+
+	#[test]
+	fn to_entries_test() {
+		let entries = to_entries(PLAIN_FIXTURE, "", 42).unwrap();
+		assert_eq!(
+			entries.iter().map(|entry| entry.title.as_str()).collect::<Vec<&str>>(),
+			vec!["Mail", "Wifi", "Visa", "Me"]
+		);
+
+		assert_eq!(entries[0].tags, vec!["Work", "Mail"]);
+		assert_eq!(
+			field_names(&entries[0]),
+			vec![
+				"Username", "Password", "TOTP", "URL", "URL", "PIN", "Recovery",
+				"Remember", "Notes"
+			]
+		);
+		assert_eq!(field_names(&entries[1]), vec!["Notes"]);
+		assert_eq!(
+			field_names(&entries[2]),
+			vec![
+				"Cardholder name",
+				"Brand",
+				"Number",
+				"Expiry",
+				"Security code"
+			]
+		);
+		assert_eq!(
+			field_names(&entries[3]),
+			vec!["Name", "Email", "Address", "SSN"]
+		);
+
+		let db = crate::db::Db::from_new_entries(entries);
+		let fields = db.get_fields(&1);
+		assert_eq!(
+			db.get_history(&1, &fields[1].0).unwrap(),
+			im::vector![
+				(1700000000, String::from("hunter2")),
+				(1600000000, String::from("hunter1"))
+			]
+		);
+		assert_eq!(db.get_field_kind(&1, &fields[3].0), DynFieldKind::Url);
+		assert_eq!(db.get_last_by_field(&1, &fields[4].0), "https://webmail.com");
+		assert_eq!(
+			db.get_field_kind(&1, &fields[5].0),
+			DynFieldKind::TextLineSecret
+		);
+		assert_eq!(db.get_field_kind(&1, &fields[6].0), DynFieldKind::TextLine);
+
+		let fields = db.get_fields(&3);
+		assert_eq!(db.get_last_by_field(&3, &fields[3].0), "04/2030");
+		let fields = db.get_fields(&4);
+		assert_eq!(db.get_last_by_field(&4, &fields[0].0), "Ms Jane Doe");
+		assert_eq!(db.get_field_kind(&4, &fields[2].0), DynFieldKind::MultiLine);
+		assert_eq!(
+			db.get_last_by_field(&4, &fields[2].0),
+			"1 Main St\nSpringfield 12345\nUS"
+		);
+	}
+
+	#[test]
+	fn encrypted_test() {
+		let plain = to_entries(PLAIN_FIXTURE, "", 42).unwrap();
+		for fixture in [PBKDF2_FIXTURE, ARGON2_FIXTURE] {
+			let entries = to_entries(fixture, "vault", 42).unwrap();
+			assert_eq!(
+				entries.iter().map(|entry| &entry.title).collect::<Vec<&String>>(),
+				plain.iter().map(|entry| &entry.title).collect::<Vec<&String>>(),
+			);
+			assert_eq!(
+				to_entries(fixture, "wrong", 42).err(),
+				Some(BitwardenError::WrongPassword)
+			);
+			assert_eq!(
+				to_entries(fixture, "", 42).err(),
+				Some(BitwardenError::MissingPassword)
+			);
+		}
+
+		assert_eq!(
+			to_entries(
+				r#"{"encrypted": true, "encKeyValidation_DO_NOT_EDIT": "2.x"}"#,
+				"",
+				0
+			)
+			.err(),
+			Some(BitwardenError::AccountRestricted)
+		);
+		assert!(matches!(to_entries("[]", "", 0), Err(BitwardenError::Json(_))));
+	}
+}
@@ -0,0 +1,334 @@
+use crate::db::{DynField, DynFieldKind, NewDbEntry};
+
+// the delimiters we try when detecting which one a file uses
+pub const DELIMITERS: [char; 4] = [',', ';', '\t', '|'];
+// how many lines are looked at to detect the delimiter
+const DETECT_LINES: usize = 10;
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum CsvError {
+	#[error("The file is empty")]
+	Empty,
+	#[error("A quoted cell starting on line {0} is never closed")]
+	UnclosedQuote(usize),
+}
+
+// what a column of the csv file becomes in the vault
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ColumnMapping {
+	#[default]
+	Skip,
+	Title,
+	Field(DynFieldKind),
+}
+
+impl std::fmt::Display for ColumnMapping {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			ColumnMapping::Skip => write!(f, "Skip"),
+			ColumnMapping::Title => write!(f, "Title"),
+			ColumnMapping::Field(kind) => write!(f, "{}", kind),
+		}
+	}
+}
+
+impl ColumnMapping {
+	pub fn all_values() -> Vec<ColumnMapping> {
+		let mut values = vec![ColumnMapping::Skip, ColumnMapping::Title];
+		values.extend(
+			DynFieldKind::all_values()
+				.into_iter()
+				.filter(|kind| {
+					*kind != DynFieldKind::Heading && *kind != DynFieldKind::Reference
+				})
+				.map(ColumnMapping::Field),
+		);
+		values
+	}
+}
+
+// PRIVATE: count how often a delimiter shows up outside of quotes per line
+fn delimiter_counts(content: &str, delimiter: char) -> Vec<usize> {
+	let mut counts = vec![0];
+	let mut in_quotes = false;
+
+	for c in content.chars() {
+		match c {
+			'"' => in_quotes = !in_quotes,
+			'\n' if !in_quotes => {
+				if counts.len() == DETECT_LINES {
+					break;
+				}
+				counts.push(0);
+			},
+			c if c == delimiter && !in_quotes => {
+				if let Some(count) = counts.last_mut() {
+					*count += 1;
+				}
+			},
+			_ => {},
+		}
+	}
+
+	if counts.len() > 1 && counts.last() == Some(&0) {
+		counts.pop();
+	}
+	counts
+}
+
+// pick the delimiter that splits the first lines into the same number of columns
+pub fn detect_delimiter(content: &str) -> char {
+	DELIMITERS
+		.iter()
+		.map(|delimiter| {
+			let counts = delimiter_counts(content, *delimiter);
+			let first = counts.first().copied().unwrap_or(0);
+			let consistent = counts.iter().all(|count| *count == first);
+			(*delimiter, consistent && first > 0, first)
+		})
+		.max_by_key(|(_, consistent, columns)| (*consistent, *columns))
+		.map(|(delimiter, _, columns)| if columns > 0 { delimiter } else { ',' })
+		.unwrap_or(',')
+}
+
+// parse csv into rows of cells, quoted cells may contain delimiters, newlines and
+// escaped quotes ("")
+pub fn parse(
+	content: &str,
+	delimiter: char,
+) -> Result<Vec<Vec<String>>, CsvError> {
+	let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+	if content.trim().is_empty() {
+		return Err(CsvError::Empty);
+	}
+
+	let mut rows = Vec::new();
+	let mut row = Vec::new();
+	let mut cell = String::new();
+	let mut in_quotes = false;
+	let mut line = 1;
+	let mut quote_line = 1;
+	let mut chars = content.chars().peekable();
+
+	while let Some(c) = chars.next() {
+		if in_quotes {
+			match c {
+				'"' if chars.peek() == Some(&'"') => {
+					chars.next();
+					cell.push('"');
+				},
+				'"' => in_quotes = false,
+				'\r' if chars.peek() == Some(&'\n') => {},
+				c => {
+					if c == '\n' {
+						line += 1;
+					}
+					cell.push(c);
+				},
+			}
+			continue;
+		}
+
+		match c {
+			'"' if cell.trim().is_empty() => {
+				cell.clear();
+				in_quotes = true;
+				quote_line = line;
+			},
+			c if c == delimiter => row.push(std::mem::take(&mut cell)),
+			'\r' if chars.peek() == Some(&'\n') => {},
+			'\n' => {
+				line += 1;
+				row.push(std::mem::take(&mut cell));
+				rows.push(std::mem::take(&mut row));
+			},
+			c => cell.push(c),
+		}
+	}
+
+	if in_quotes {
+		return Err(CsvError::UnclosedQuote(quote_line));
+	}
+	if !cell.is_empty() || !row.is_empty() {
+		row.push(cell);
+		rows.push(row);
+	}
+
+	// blank lines don't hold any entries
+	rows.retain(|row| row.iter().any(|cell| !cell.trim().is_empty()));
+	Ok(rows)
+}
+
+// guess what a column is from its header so the wizard starts with a sensible mapping
+pub fn guess_mapping(header: &str) -> ColumnMapping {
+	let header = header.trim().to_lowercase();
+
+	match header.as_str() {
+		"title" | "name" | "entry" | "account" => ColumnMapping::Title,
+		"url" | "uri" | "website" | "login_uri" | "site" => {
+			ColumnMapping::Field(DynFieldKind::Url)
+		},
+		"note" | "notes" | "comment" | "comments" | "extra" => {
+			ColumnMapping::Field(DynFieldKind::MultiLine)
+		},
+		_ if header.contains("password")
+			|| header.contains("secret")
+			|| header.contains("pin")
+			|| header.contains("totp") =>
+		{
+			ColumnMapping::Field(DynFieldKind::TextLineSecret)
+		},
+		_ => ColumnMapping::Field(DynFieldKind::TextLine),
+	}
+}
+
+// turn the parsed rows into entries, the header row names the fields
+pub fn to_entries(
+	rows: &[Vec<String>],
+	mappings: &[ColumnMapping],
+	has_header: bool,
+	timestamp: u64,
+) -> Vec<NewDbEntry> {
+	let headers = if has_header {
+		rows.first().cloned().unwrap_or_default()
+	} else {
+		Vec::new()
+	};
+	let skip = if has_header { 1 } else { 0 };
+
+	rows
+		.iter()
+		.skip(skip)
+		.enumerate()
+		.filter_map(|(idx, row)| {
+			let mut title = String::new();
+			let mut fields = Vec::new();
+
+			for (column, mapping) in mappings.iter().enumerate() {
+				let value = row.get(column).map(|cell| cell.trim()).unwrap_or("");
+				if value.is_empty() {
+					continue;
+				}
+
+				match mapping {
+					ColumnMapping::Skip => {},
+					ColumnMapping::Title => {
+						if !title.is_empty() {
+							title.push(' ');
+						}
+						title.push_str(value);
+					},
+					ColumnMapping::Field(kind) => {
+						// single line kinds can't hold the newlines of quoted cells
+						let kind = kind.clone().for_value(value);
+						let field_title = headers
+							.get(column)
+							.map(|header| String::from(header.trim()))
+							.filter(|header| !header.is_empty())
+							.unwrap_or_else(|| format!("Column {}", column + 1));
+						fields.push(DynField::new(
+							kind,
+							field_title,
+							vec![(timestamp, String::from(value))],
+						));
+					},
+				}
+			}
+
+			if title.is_empty() && fields.is_empty() {
+				return None;
+			}
+			if title.is_empty() {
+				title = format!("Imported row {}", idx + 1);
+			}
+
+			Some(NewDbEntry {
+				title,
+				fields,
+				tags: Vec::new(),
+			})
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse_test() {
+		assert_eq!(
+			parse("a,b,c\n1,2,3\n", ',').unwrap(),
+			vec![vec!["a", "b", "c"], vec!["1", "2", "3"]]
+		);
+		assert_eq!(
+			parse(
+				"name;note\r\n\"Mail\";\"line one\r\nline \"\"two\"\"\"\r\n\r\n",
+				';'
+			)
+			.unwrap(),
+			vec![vec!["name", "note"], vec!["Mail", "line one\nline \"two\""]]
+		);
+		assert_eq!(
+			parse("\u{feff}a\tb\n\"x,y\"\t\n", '\t').unwrap(),
+			vec![vec!["a", "b"], vec!["x,y", ""]]
+		);
+		assert_eq!(parse("a,b", ',').unwrap(), vec![vec!["a", "b"]]);
+		assert_eq!(parse(" \n", ','), Err(CsvError::Empty));
+		assert_eq!(parse("a\n\"b\nc", ','), Err(CsvError::UnclosedQuote(2)));
+	}
+
+	#[test]
+	fn detect_delimiter_test() {
+		assert_eq!(detect_delimiter("a,b,c\n1,2,3\n"), ',');
+		assert_eq!(detect_delimiter("a;b;c\n1;2,5;3\n"), ';');
+		assert_eq!(detect_delimiter("a\tb\n\"1,2,3\"\t4\n"), '\t');
+		assert_eq!(detect_delimiter("a|b\n1|2"), '|');
+		assert_eq!(detect_delimiter("single column\nrow"), ',');
+	}
+
+	#[test]
+	fn to_entries_test() {
+		let rows = parse(
+			"name,username,password,url,notes\n\
+			Mail,me,\"p,w\",https://mail.com,\"first\nsecond\"\n\
+			,,,,\n\
+			,nobody,secret,,\n",
+			',',
+		)
+		.unwrap();
+		let mappings = rows[0]
+			.iter()
+			.map(|header| guess_mapping(header.as_str()))
+			.collect::<Vec<ColumnMapping>>();
+		assert_eq!(
+			mappings,
+			vec![
+				ColumnMapping::Title,
+				ColumnMapping::Field(DynFieldKind::TextLine),
+				ColumnMapping::Field(DynFieldKind::TextLineSecret),
+				ColumnMapping::Field(DynFieldKind::Url),
+				ColumnMapping::Field(DynFieldKind::MultiLine),
+			]
+		);
+
+		let entries = to_entries(&rows, &mappings, true, 42);
+		assert_eq!(entries.len(), 2);
+		assert_eq!(entries[0].title, "Mail");
+		assert_eq!(entries[0].fields.len(), 4);
+		assert_eq!(entries[1].title, "Imported row 2");
+		assert_eq!(entries[1].fields.len(), 2);
+
+		let db = crate::db::Db::from_new_entries(entries);
+		let fields = db.get_fields(&1);
+		assert_eq!(db.get_name_of_field(&1, &fields[1].0), "password");
+		assert_eq!(db.get_last_by_field(&1, &fields[1].0), "p,w");
+		assert_eq!(db.get_last_by_field(&1, &fields[3].0), "first\nsecond");
+
+		let entries =
+			to_entries(&rows, &[ColumnMapping::Skip, ColumnMapping::Title], false, 0);
+		assert_eq!(entries.len(), 3);
+		assert_eq!(entries[0].title, "username");
+		assert!(entries[0].fields.is_empty());
+	}
+}
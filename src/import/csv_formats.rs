@@ -0,0 +1,272 @@
+use crate::db::{DynField, DynFieldKind, NewDbEntry};
+
+// lastpass marks secure notes with this url
+const LASTPASS_NOTE_URL: &str = "http://sn";
+
+// csv exports of other password managers we know the columns of
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CsvFormat {
+	LastPass,
+	Chrome,
+	Firefox,
+}
+
+impl std::fmt::Display for CsvFormat {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			CsvFormat::LastPass => write!(f, "LastPass"),
+			CsvFormat::Chrome => write!(f, "Chrome"),
+			CsvFormat::Firefox => write!(f, "Firefox"),
+		}
+	}
+}
+
+// find out which manager exported the csv by the columns of its header
+pub fn detect(header: &[String]) -> Option<CsvFormat> {
+	let columns = header
+		.iter()
+		.map(|column| column.trim().to_lowercase())
+		.collect::<Vec<String>>();
+	let has = |names: &[&str]| {
+		names.iter().all(|name| columns.iter().any(|column| column == name))
+	};
+
+	if has(&["url", "username", "password", "extra", "name", "grouping"]) {
+		Some(CsvFormat::LastPass)
+	} else if has(&["url", "username", "password", "guid", "timepasswordchanged"])
+	{
+		Some(CsvFormat::Firefox)
+	} else if has(&["name", "url", "username", "password"])
+		&& columns.iter().all(|column| {
+			matches!(
+				column.as_str(),
+				"name" | "url" | "username" | "password" | "note"
+			)
+		}) {
+		Some(CsvFormat::Chrome)
+	} else {
+		None
+	}
+}
+
+// PRIVATE: browsers don't name their logins so the host of the url is used instead
+fn host(url: &str) -> &str {
+	let without_scheme =
+		url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+	let host =
+		without_scheme.split(['/', '?', '#']).next().unwrap_or(without_scheme);
+	let host = host.rsplit_once('@').map(|(_, host)| host).unwrap_or(host);
+	host.strip_prefix("www.").unwrap_or(host)
+}
+
+// PRIVATE: empty cells aren't worth a field
+fn push_field(
+	fields: &mut Vec<DynField>,
+	kind: DynFieldKind,
+	title: &str,
+	value: Option<&str>,
+	timestamp: u64,
+) {
+	if let Some(value) = value {
+		fields.push(DynField::new(
+			kind.for_value(value),
+			String::from(title),
+			vec![(timestamp, String::from(value))],
+		));
+	}
+}
+
+// turn the rows of a known csv export into entries, the first row is the header
+pub fn to_entries(
+	format: CsvFormat,
+	rows: &[Vec<String>],
+	timestamp: u64,
+) -> Vec<NewDbEntry> {
+	let header = rows
+		.first()
+		.map(|header| {
+			header
+				.iter()
+				.map(|column| column.trim().to_lowercase())
+				.collect::<Vec<String>>()
+		})
+		.unwrap_or_default();
+
+	rows
+		.iter()
+		.skip(1)
+		.map(|row| {
+			let get = |name: &str| {
+				header
+					.iter()
+					.position(|column| column == name)
+					.and_then(|idx| row.get(idx))
+					.map(|cell| cell.trim())
+					.filter(|cell| !cell.is_empty())
+			};
+			let mut fields = Vec::new();
+			let mut tags = Vec::new();
+
+			let url = match format {
+				CsvFormat::LastPass => {
+					get("url").filter(|url| *url != LASTPASS_NOTE_URL)
+				},
+				_ => get("url"),
+			};
+			let title = match format {
+				CsvFormat::Firefox => url.map(host),
+				_ => get("name").or(url.map(host)),
+			};
+			let password_date = match format {
+				// firefox counts milliseconds
+				CsvFormat::Firefox => get("timepasswordchanged")
+					.and_then(|time| time.parse::<u64>().ok())
+					.map(|time| time / 1000)
+					.unwrap_or(timestamp),
+				_ => timestamp,
+			};
+
+			push_field(
+				&mut fields,
+				DynFieldKind::TextLine,
+				"Username",
+				get("username"),
+				timestamp,
+			);
+			push_field(
+				&mut fields,
+				DynFieldKind::TextLineSecret,
+				"Password",
+				get("password"),
+				password_date,
+			);
+			push_field(
+				&mut fields,
+				DynFieldKind::TextLineSecret,
+				"TOTP",
+				get("totp"),
+				timestamp,
+			);
+			push_field(&mut fields, DynFieldKind::Url, "URL", url, timestamp);
+			if format == CsvFormat::Firefox {
+				push_field(
+					&mut fields,
+					DynFieldKind::TextLine,
+					"HTTP realm",
+					get("httprealm"),
+					timestamp,
+				);
+			}
+			push_field(
+				&mut fields,
+				DynFieldKind::MultiLine,
+				"Notes",
+				get("extra").or(get("note")),
+				timestamp,
+			);
+
+			// lastpass nests folders with a backslash
+			if let Some(grouping) = get("grouping") {
+				tags.extend(
+					grouping
+						.split(['\\', '/'])
+						.map(|tag| String::from(tag.trim()))
+						.filter(|tag| !tag.is_empty()),
+				);
+			}
+
+			NewDbEntry {
+				title: String::from(title.unwrap_or("Untitled entry")),
+				fields,
+				tags,
+			}
+		})
+		.filter(|entry| !entry.fields.is_empty())
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::import::csv::parse;
+
+	#[test]
+	fn detect_test() {
+		let header = |line: &str| parse(line, ',').unwrap().remove(0);
+
+		assert_eq!(
+			detect(&header("url,username,password,totp,extra,name,grouping,fav")),
+			Some(CsvFormat::LastPass)
+		);
+		assert_eq!(
+			detect(&header("name,url,username,password,note")),
+			Some(CsvFormat::Chrome)
+		);
+		assert_eq!(
+			detect(&header("name,url,username,password")),
+			Some(CsvFormat::Chrome)
+		);
+		assert_eq!(
+			detect(&header(
+				"\"url\",\"username\",\"password\",\"httpRealm\",\"formActionOrigin\",\"guid\",\"timeCreated\",\"timeLastUsed\",\"timePasswordChanged\""
+			)),
+			Some(CsvFormat::Firefox)
+		);
+		assert_eq!(detect(&header("name,url,username,password,folder")), None);
+		assert_eq!(detect(&header("title,login")), None);
+	}
+
+	#[test]
+	fn host_test() {
+		assert_eq!(host("https://www.mail.com/login?next=1"), "mail.com");
+		assert_eq!(host("http://me@shop.com:8080"), "shop.com:8080");
+		assert_eq!(host("mail.com"), "mail.com");
+	}
+
+	#[test]
+	fn to_entries_test() {
+		let rows = parse(
+			"url,username,password,totp,extra,name,grouping,fav\n\
+			https://mail.com,me,hunter2,,\"first\nsecond\",Mail,Work\\Mail,0\n\
+			http://sn,,,,\"NoteType:Server\nHostname:box\",Server notes,,0\n",
+			',',
+		)
+		.unwrap();
+		let entries = to_entries(CsvFormat::LastPass, &rows, 42);
+		assert_eq!(entries.len(), 2);
+		assert_eq!(entries[0].title, "Mail");
+		assert_eq!(entries[0].tags, vec!["Work", "Mail"]);
+		assert_eq!(entries[0].fields.len(), 4);
+		assert_eq!(entries[1].title, "Server notes");
+		assert_eq!(entries[1].fields.len(), 1);
+
+		let rows = parse(
+			"name,url,username,password,note\n\
+			mail.com,https://mail.com/,me,hunter2,\n\
+			,https://www.shop.com/login,buyer,secret,remember me\n",
+			',',
+		)
+		.unwrap();
+		let entries = to_entries(CsvFormat::Chrome, &rows, 42);
+		assert_eq!(entries[0].title, "mail.com");
+		assert_eq!(entries[0].fields.len(), 3);
+		assert_eq!(entries[1].title, "shop.com");
+		assert_eq!(entries[1].fields.len(), 4);
+
+		let rows = parse(
+			"\"url\",\"username\",\"password\",\"httpRealm\",\"formActionOrigin\",\"guid\",\"timeCreated\",\"timeLastUsed\",\"timePasswordChanged\"\n\
+			\"https://mail.com\",\"me\",\"hunter2\",,\"https://mail.com\",\"{1}\",\"1600000000000\",\"1700000000000\",\"1650000000000\"\n",
+			',',
+		)
+		.unwrap();
+		let entries = to_entries(CsvFormat::Firefox, &rows, 42);
+		assert_eq!(entries[0].title, "mail.com");
+		let db = crate::db::Db::from_new_entries(entries);
+		let fields = db.get_fields(&1);
+		assert_eq!(
+			db.get_history(&1, &fields[1].0).unwrap(),
+			im::vector![(1650000000, String::from("hunter2"))]
+		);
+		assert_eq!(db.get_field_kind(&1, &fields[2].0), DynFieldKind::Url);
+	}
+}
@@ -0,0 +1,97 @@
+use crate::import::{
+	csv::{detect_delimiter, parse},
+	csv_formats::{detect, CsvFormat},
+	json::is_vault_json,
+	kdbx::is_kdbx,
+};
+
+// every zip file starts with these bytes
+const ZIP_SIGNATURE: [u8; 4] = [0x50, 0x4b, 0x03, 0x04];
+
+// the kinds of files we can import
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ImportFormat {
+	Vault,
+	// a plaintext json export of vault
+	Json,
+	KeePass,
+	// an unencrypted xml export of keepass
+	KeePassXml,
+	Bitwarden,
+	OnePassword,
+	// a csv export of a manager we know the columns of
+	KnownCsv(CsvFormat),
+	// any other csv that needs its columns mapped by hand
+	Csv,
+}
+
+// find out what a file holds by its content so the extension doesn't matter
+pub fn detect_format(data: &[u8]) -> ImportFormat {
+	if is_kdbx(data) {
+		return ImportFormat::KeePass;
+	}
+	// 1pux files are the only zip files we import
+	if data.starts_with(&ZIP_SIGNATURE) {
+		return ImportFormat::OnePassword;
+	}
+
+	let content = String::from_utf8_lossy(data);
+	let content = content.trim_start_matches('\u{feff}').trim_start();
+	if content.starts_with('{') {
+		if is_vault_json(content) {
+			return ImportFormat::Json;
+		}
+		return ImportFormat::Bitwarden;
+	}
+	if content.starts_with('<') && content.contains("<KeePassFile") {
+		return ImportFormat::KeePassXml;
+	}
+	// our own backups are toml files with a db table
+	if content.starts_with("[db]") || content.lines().any(|line| line == "[db]") {
+		return ImportFormat::Vault;
+	}
+
+	parse(content, detect_delimiter(content))
+		.ok()
+		.and_then(|rows| rows.first().and_then(|header| detect(header)))
+		.map(ImportFormat::KnownCsv)
+		.unwrap_or(ImportFormat::Csv)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn detect_format_test() {
+		assert_eq!(
+			detect_format(include_bytes!("fixtures/keepass_chacha20.kdbx")),
+			ImportFormat::KeePass
+		);
+		assert_eq!(
+			detect_format(include_bytes!("fixtures/1password.1pux")),
+			ImportFormat::OnePassword
+		);
+		assert_eq!(
+			detect_format(include_bytes!("fixtures/bitwarden.json")),
+			ImportFormat::Bitwarden
+		);
+		assert_eq!(
+			detect_format(br#"{"format": "vault", "version": 1, "entries": []}"#),
+			ImportFormat::Json
+		);
+		assert_eq!(
+			detect_format(b"<?xml version=\"1.0\"?>\n<KeePassFile></KeePassFile>"),
+			ImportFormat::KeePassXml
+		);
+		assert_eq!(
+			detect_format(b"[db]\nencrypted = true\nsalt = \"x\"\n"),
+			ImportFormat::Vault
+		);
+		assert_eq!(
+			detect_format(b"\xef\xbb\xbfname,url,username,password\nmail,,me,pw\n"),
+			ImportFormat::KnownCsv(CsvFormat::Chrome)
+		);
+		assert_eq!(detect_format(b"title;login\nmail;me\n"), ImportFormat::Csv);
+	}
+}
@@ -0,0 +1,136 @@
+use crate::{
+	db::{DynField, NewDbEntry},
+	export::json::{JsonExport, FORMAT},
+};
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum JsonError {
+	#[error("Failed to read the JSON export: {0}")]
+	Json(String),
+	#[error("This JSON file wasn't exported by Vault")]
+	NotVault,
+}
+
+// if the content is one of our own json exports
+pub fn is_vault_json(content: &str) -> bool {
+	serde_json::from_str::<serde_json::Value>(content).is_ok_and(|value| {
+		value.get("format").and_then(|format| format.as_str()) == Some(FORMAT)
+	})
+}
+
+// turn a json export of vault back into entries
+pub fn to_entries(content: &str) -> Result<Vec<NewDbEntry>, JsonError> {
+	let export = serde_json::from_str::<JsonExport>(content)
+		.map_err(|error| JsonError::Json(error.to_string()))?;
+	if export.format != FORMAT {
+		return Err(JsonError::NotVault);
+	}
+
+	Ok(
+		export
+			.entries
+			.into_iter()
+			.map(|entry| NewDbEntry {
+				title: entry.title,
+				fields: entry
+					.fields
+					.into_iter()
+					.filter(|field| !field.history.is_empty())
+					.map(|field| {
+						DynField::new(
+							field.kind,
+							field.title,
+							field
+								.history
+								.into_iter()
+								.map(|value| (value.date, value.value))
+								.collect(),
+						)
+						.with_visibility(field.visible)
+					})
+					.collect(),
+				tags: entry.tags,
+			})
+			.collect(),
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		db::{make_reference, Db, DynFieldKind},
+		export::json::to_json,
+	};
+
+	#[test]
+	fn round_trip_test() {
+		let db = Db::from_new_entries(vec![
+			NewDbEntry {
+				title: String::from("Mail"),
+				fields: vec![
+					DynField::new(
+						DynFieldKind::TextLineSecret,
+						String::from("Password"),
+						vec![(10, String::from("old")), (20, String::from("new \"one\""))],
+					),
+					DynField::new(
+						DynFieldKind::MultiLine,
+						String::from("Notes"),
+						vec![(10, String::from("line one\nline two"))],
+					)
+					.with_visibility(false),
+				],
+				tags: vec![String::from("work"), String::from("mail")],
+			},
+			NewDbEntry {
+				title: String::from("Shared"),
+				fields: Vec::new(),
+				tags: Vec::new(),
+			},
+		]);
+		db.add_field(
+			&2,
+			DynFieldKind::Reference,
+			String::from("Mail password"),
+			make_reference(&1, &1),
+		);
+
+		let json = to_json(&db).unwrap();
+		assert!(is_vault_json(&json));
+
+		let imported = Db::from_new_entries(to_entries(&json).unwrap());
+		assert_eq!(
+			imported
+				.get_sidebar_list()
+				.iter()
+				.map(|(_, title, _)| title.as_str())
+				.collect::<Vec<&str>>(),
+			vec!["Shared", "Mail"]
+		);
+		assert_eq!(imported.get_tags(&1), vec!["work", "mail"]);
+		assert_eq!(imported.get_fields(&1), db.get_fields(&1));
+		for (field, _) in db.get_fields(&1) {
+			assert_eq!(imported.get_history(&1, &field), db.get_history(&1, &field));
+			assert_eq!(
+				imported.get_field_kind(&1, &field),
+				db.get_field_kind(&1, &field)
+			);
+		}
+
+		// the reference is exported as the value it pointed to
+		let reference = imported.get_fields(&2)[0].0;
+		assert_eq!(
+			imported.get_field_kind(&2, &reference),
+			DynFieldKind::TextLineSecret
+		);
+		assert_eq!(imported.get_last_by_field(&2, &reference), "new \"one\"");
+
+		assert!(!is_vault_json(r#"{"encrypted": false, "items": []}"#));
+		assert_eq!(
+			to_entries(r#"{"format": "other", "version": 1, "entries": []}"#)
+				.unwrap_err(),
+			JsonError::NotVault
+		);
+	}
+}
@@ -0,0 +1,798 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose, Engine as _};
+use cbc::cipher::{
+	block_padding::Pkcs7, BlockDecryptMut, KeyIvInit, StreamCipher,
+};
+use chacha20::ChaCha20;
+use chrono::DateTime;
+use flate2::read::GzDecoder;
+use hmac::{Hmac, Mac};
+use roxmltree::{Document, Node, NodeId};
+use sha2::{Digest, Sha256, Sha512};
+use std::{collections::HashMap, io::Read};
+use zeroize::Zeroize;
+
+use crate::db::{DynField, DynFieldKind, NewDbEntry};
+
+const SIGNATURE: [u8; 8] = [0x03, 0xd9, 0xa2, 0x9a, 0x67, 0xfb, 0x4b, 0xb5];
+const CIPHER_AES256: [u8; 16] = [
+	0x31, 0xc1, 0xf2, 0xe6, 0xbf, 0x71, 0x43, 0x50, 0xbe, 0x58, 0x05, 0x21, 0x6a,
+	0xfc, 0x5a, 0xff,
+];
+const CIPHER_CHACHA20: [u8; 16] = [
+	0xd6, 0x03, 0x8a, 0x2b, 0x8b, 0x6f, 0x4c, 0xb5, 0xa5, 0x24, 0x33, 0x9a, 0x31,
+	0xdb, 0xb5, 0x9a,
+];
+const KDF_ARGON2D: [u8; 16] = [
+	0xef, 0x63, 0x6d, 0xdf, 0x8c, 0x29, 0x44, 0x4b, 0x91, 0xf7, 0xa9, 0xa4, 0x03,
+	0xe3, 0x0a, 0x0c,
+];
+const KDF_ARGON2ID: [u8; 16] = [
+	0x9e, 0x29, 0x8b, 0x19, 0x56, 0xdb, 0x47, 0x73, 0xb2, 0x3d, 0xfc, 0x3e, 0xc6,
+	0xf0, 0xa1, 0xe6,
+];
+// the protected values inside the xml are xored with a chacha20 stream
+const INNER_STREAM_CHACHA20: u32 = 3;
+// keepass counts seconds from 0001-01-01 instead of 1970-01-01
+const EPOCH_OFFSET: i64 = 62_135_596_800;
+// an empty uuid means the recycle bin is turned off
+const EMPTY_UUID: &str = "AAAAAAAAAAAAAAAAAAAAAA==";
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum KdbxError {
+	#[error("This is not a KeePass database")]
+	NotKdbx,
+	#[error(
+		"Only KeePass databases of version 4 can be imported, found version {0}"
+	)]
+	Version(u16),
+	#[error("The KeePass database is damaged")]
+	Corrupt,
+	#[error("Wrong password or key file")]
+	WrongKey,
+	#[error("The KeePass database uses a cipher other than AES or ChaCha20")]
+	UnsupportedCipher,
+	#[error("The KeePass database uses a key derivation other than Argon2")]
+	UnsupportedKdf,
+	#[error(
+		"The KeePass database protects its values with an unsupported stream"
+	)]
+	UnsupportedStream,
+	#[error("Failed to derive the key: {0}")]
+	Argon2(#[from] argon2::Error),
+	#[error("Failed to read the KeePass XML: {0}")]
+	Xml(String),
+}
+
+// PRIVATE: reads the little endian numbers and blocks the file is made of
+struct Reader<'a> {
+	data: &'a [u8],
+	pos: usize,
+}
+
+impl<'a> Reader<'a> {
+	fn new(data: &'a [u8]) -> Self {
+		Self { data, pos: 0 }
+	}
+
+	fn take(&mut self, len: usize) -> Result<&'a [u8], KdbxError> {
+		let end = self.pos.checked_add(len).ok_or(KdbxError::Corrupt)?;
+		let bytes = self.data.get(self.pos..end).ok_or(KdbxError::Corrupt)?;
+		self.pos = end;
+		Ok(bytes)
+	}
+
+	fn u8(&mut self) -> Result<u8, KdbxError> {
+		Ok(self.take(1)?[0])
+	}
+
+	fn u16(&mut self) -> Result<u16, KdbxError> {
+		Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+	}
+
+	fn u32(&mut self) -> Result<u32, KdbxError> {
+		Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+	}
+
+	fn rest(&self) -> &'a [u8] {
+		&self.data[self.pos..]
+	}
+}
+
+// PRIVATE: the unencrypted outer header
+#[derive(Default)]
+struct Header {
+	cipher: Vec<u8>,
+	compressed: bool,
+	master_seed: Vec<u8>,
+	iv: Vec<u8>,
+	kdf: HashMap<String, Vec<u8>>,
+}
+
+// PRIVATE: the variant dictionary holds the kdf parameters as raw bytes by name
+fn parse_variant_dictionary(
+	data: &[u8],
+) -> Result<HashMap<String, Vec<u8>>, KdbxError> {
+	let mut reader = Reader::new(data);
+	let mut dictionary = HashMap::new();
+
+	if reader.u16()? >> 8 != 1 {
+		return Err(KdbxError::Corrupt);
+	}
+	loop {
+		let kind = reader.u8()?;
+		if kind == 0 {
+			break;
+		}
+		let key_len = reader.u32()? as usize;
+		let key = String::from_utf8_lossy(reader.take(key_len)?).to_string();
+		let value_len = reader.u32()? as usize;
+		dictionary.insert(key, reader.take(value_len)?.to_vec());
+	}
+
+	Ok(dictionary)
+}
+
+// if the data starts like a keepass database of any version
+pub fn is_kdbx(data: &[u8]) -> bool {
+	data.starts_with(&SIGNATURE)
+}
+
+// PRIVATE: read the header and return it with its raw bytes for the checks
+fn parse_header<'a>(
+	reader: &mut Reader<'a>,
+) -> Result<(Header, &'a [u8]), KdbxError> {
+	if reader.take(8).map_err(|_| KdbxError::NotKdbx)? != SIGNATURE {
+		return Err(KdbxError::NotKdbx);
+	}
+	let _minor = reader.u16()?;
+	let major = reader.u16()?;
+	if major != 4 {
+		return Err(KdbxError::Version(major));
+	}
+
+	let mut header = Header::default();
+	loop {
+		let id = reader.u8()?;
+		let len = reader.u32()? as usize;
+		let data = reader.take(len)?;
+		match id {
+			0 => break,
+			2 => header.cipher = data.to_vec(),
+			3 => header.compressed = data.first() == Some(&1),
+			4 => header.master_seed = data.to_vec(),
+			7 => header.iv = data.to_vec(),
+			11 => header.kdf = parse_variant_dictionary(data)?,
+			_ => {},
+		}
+	}
+
+	Ok((header, &reader.data[..reader.pos]))
+}
+
+// PRIVATE: undo the hex encoding of key files, whitespace is ignored
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+	let digits = value
+		.chars()
+		.filter(|c| !c.is_whitespace())
+		.map(|c| c.to_digit(16).map(|digit| digit as u8))
+		.collect::<Option<Vec<u8>>>()?;
+	if digits.len() % 2 != 0 {
+		return None;
+	}
+	Some(digits.chunks(2).map(|pair| pair[0] << 4 | pair[1]).collect())
+}
+
+// PRIVATE: key files are xml, 32 raw bytes, 64 hex characters or any file that is hashed
+fn key_file_key(key_file: &[u8]) -> Vec<u8> {
+	if let Ok(text) = std::str::from_utf8(key_file) {
+		if let Ok(doc) = Document::parse(text) {
+			let version = doc
+				.descendants()
+				.find(|node| node.has_tag_name("Version"))
+				.and_then(|node| node.text())
+				.unwrap_or("");
+			let data = doc
+				.descendants()
+				.find(|node| node.has_tag_name("Data"))
+				.and_then(|node| node.text())
+				.unwrap_or("");
+			let key = if version.starts_with('1') {
+				general_purpose::STANDARD.decode(data.trim()).ok()
+			} else {
+				decode_hex(data)
+			};
+			if let Some(key) = key {
+				return key;
+			}
+		}
+
+		if key_file.len() == 64 {
+			if let Some(key) = decode_hex(text) {
+				return key;
+			}
+		}
+	}
+
+	if key_file.len() == 32 {
+		return key_file.to_vec();
+	}
+	Sha256::digest(key_file).to_vec()
+}
+
+// PRIVATE: run the composite key through argon2 as the kdf parameters describe
+fn transform_key(
+	header: &Header,
+	password: &str,
+	key_file: Option<&[u8]>,
+) -> Result<[u8; 32], KdbxError> {
+	let mut composite = Sha256::new();
+	if !password.is_empty() || key_file.is_none() {
+		composite.update(Sha256::digest(password.as_bytes()));
+	}
+	if let Some(key_file) = key_file {
+		let mut key = key_file_key(key_file);
+		composite.update(&key);
+		key.zeroize();
+	}
+	let mut composite: [u8; 32] = composite.finalize().into();
+
+	let kdf = &header.kdf;
+	let algorithm = match kdf.get("$UUID").map(|uuid| uuid.as_slice()) {
+		Some(uuid) if uuid == KDF_ARGON2D => Algorithm::Argon2d,
+		Some(uuid) if uuid == KDF_ARGON2ID => Algorithm::Argon2id,
+		_ => return Err(KdbxError::UnsupportedKdf),
+	};
+	let number = |key: &str| -> Result<u64, KdbxError> {
+		match kdf.get(key).map(|value| value.as_slice()) {
+			Some(value) if value.len() == 4 => {
+				Ok(u32::from_le_bytes(value.try_into().unwrap()) as u64)
+			},
+			Some(value) if value.len() == 8 => {
+				Ok(u64::from_le_bytes(value.try_into().unwrap()))
+			},
+			_ => Err(KdbxError::Corrupt),
+		}
+	};
+	let version = match number("V")? {
+		0x10 => Version::V0x10,
+		_ => Version::V0x13,
+	};
+	let params = Params::new(
+		(number("M")? / 1024) as u32,
+		number("I")? as u32,
+		number("P")? as u32,
+		Some(32),
+	)?;
+	let salt = kdf.get("S").ok_or(KdbxError::Corrupt)?;
+
+	let mut transformed = [0u8; 32];
+	let result = Argon2::new(algorithm, version, params).hash_password_into(
+		&composite,
+		salt,
+		&mut transformed,
+	);
+	composite.zeroize();
+	result?;
+
+	Ok(transformed)
+}
+
+// PRIVATE: every block and the header are signed with their own hmac key
+fn block_hmac(hmac_key: &[u8], idx: u64) -> Hmac<Sha256> {
+	let mut key = Sha512::new()
+		.chain_update(idx.to_le_bytes())
+		.chain_update(hmac_key)
+		.finalize();
+	let hmac = Hmac::<Sha256>::new_from_slice(&key)
+		.expect("Hmac takes keys of any length");
+	key.as_mut_slice().zeroize();
+	hmac
+}
+
+// PRIVATE: check the signature of each block and join them into the encrypted payload
+fn read_blocks(
+	reader: &mut Reader,
+	hmac_key: &[u8],
+) -> Result<Vec<u8>, KdbxError> {
+	let mut payload = Vec::new();
+
+	for idx in 0u64.. {
+		let signature = reader.take(32)?;
+		let len = reader.u32()?;
+		let data = reader.take(len as usize)?;
+
+		let mut hmac = block_hmac(hmac_key, idx);
+		hmac.update(&idx.to_le_bytes());
+		hmac.update(&len.to_le_bytes());
+		hmac.update(data);
+		hmac.verify_slice(signature).map_err(|_| KdbxError::Corrupt)?;
+
+		if data.is_empty() {
+			break;
+		}
+		payload.extend_from_slice(data);
+	}
+
+	Ok(payload)
+}
+
+// PRIVATE: decrypt and unzip the payload into the inner header and the xml
+fn decrypt_payload(
+	header: &Header,
+	key: &[u8],
+	mut payload: Vec<u8>,
+) -> Result<Vec<u8>, KdbxError> {
+	let mut plain = if header.cipher == CIPHER_CHACHA20 {
+		ChaCha20::new_from_slices(key, &header.iv)
+			.map_err(|_| KdbxError::Corrupt)?
+			.apply_keystream(&mut payload);
+		payload
+	} else if header.cipher == CIPHER_AES256 {
+		let len = cbc::Decryptor::<aes::Aes256>::new_from_slices(key, &header.iv)
+			.map_err(|_| KdbxError::Corrupt)?
+			.decrypt_padded_mut::<Pkcs7>(&mut payload)
+			.map_err(|_| KdbxError::Corrupt)?
+			.len();
+		payload.truncate(len);
+		payload
+	} else {
+		return Err(KdbxError::UnsupportedCipher);
+	};
+
+	if header.compressed {
+		let mut unzipped = Vec::new();
+		let result = GzDecoder::new(plain.as_slice()).read_to_end(&mut unzipped);
+		plain.zeroize();
+		result.map_err(|_| KdbxError::Corrupt)?;
+		plain = unzipped;
+	}
+
+	Ok(plain)
+}
+
+// PRIVATE: the inner header holds the key for the protected values
+fn parse_inner_header(reader: &mut Reader) -> Result<ChaCha20, KdbxError> {
+	let mut stream_id = 0;
+	let mut stream_key = Vec::new();
+
+	loop {
+		let id = reader.u8()?;
+		let len = reader.u32()? as usize;
+		let data = reader.take(len)?;
+		match id {
+			0 => break,
+			1 => {
+				stream_id =
+					u32::from_le_bytes(data.try_into().map_err(|_| KdbxError::Corrupt)?)
+			},
+			2 => stream_key = data.to_vec(),
+			_ => {},
+		}
+	}
+
+	if stream_id != INNER_STREAM_CHACHA20 {
+		return Err(KdbxError::UnsupportedStream);
+	}
+	let mut hash = Sha512::digest(&stream_key);
+	stream_key.zeroize();
+	let stream = ChaCha20::new_from_slices(&hash[..32], &hash[32..44])
+		.map_err(|_| KdbxError::Corrupt);
+	hash.as_mut_slice().zeroize();
+	stream
+}
+
+// PRIVATE: protected values share one stream so they're decrypted in document order
+fn unprotect_values(
+	doc: &Document,
+	mut stream: ChaCha20,
+) -> Result<HashMap<NodeId, String>, KdbxError> {
+	let mut values = HashMap::new();
+
+	for node in doc.descendants().filter(|node| {
+		node.has_tag_name("Value")
+			&& node
+				.attribute("Protected")
+				.is_some_and(|value| value.eq_ignore_ascii_case("true"))
+	}) {
+		let mut value = general_purpose::STANDARD
+			.decode(node.text().unwrap_or("").trim())
+			.map_err(|_| KdbxError::Corrupt)?;
+		stream.apply_keystream(&mut value);
+		values.insert(node.id(), String::from_utf8_lossy(&value).to_string());
+		value.zeroize();
+	}
+
+	Ok(values)
+}
+
+// PRIVATE: the text of the first child with the given name
+fn child_text<'a>(node: Node<'a, 'a>, name: &str) -> Option<&'a str> {
+	node
+		.children()
+		.find(|child| child.has_tag_name(name))
+		.and_then(|child| child.text())
+}
+
+// PRIVATE: times are base64 encoded seconds since year 1 or iso dates in older files
+fn parse_time(value: &str) -> u64 {
+	let value = value.trim();
+	if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+		return date.timestamp().max(0) as u64;
+	}
+
+	general_purpose::STANDARD
+		.decode(value)
+		.ok()
+		.and_then(|bytes| bytes.try_into().ok())
+		.map(|bytes: [u8; 8]| {
+			(i64::from_le_bytes(bytes) - EPOCH_OFFSET).max(0) as u64
+		})
+		.unwrap_or(0)
+}
+
+// PRIVATE: one version of an entry, either the current one or one out of its history
+struct EntryVersion {
+	modified: u64,
+	// key, value and if the value was protected
+	strings: Vec<(String, String, bool)>,
+}
+
+fn read_version(
+	entry: Node,
+	protected: &HashMap<NodeId, String>,
+) -> EntryVersion {
+	let modified = entry
+		.children()
+		.find(|child| child.has_tag_name("Times"))
+		.and_then(|times| child_text(times, "LastModificationTime"))
+		.map(parse_time)
+		.unwrap_or(0);
+
+	let strings = entry
+		.children()
+		.filter(|child| child.has_tag_name("String"))
+		.filter_map(|string| {
+			let key = child_text(string, "Key")?;
+			let value =
+				string.children().find(|child| child.has_tag_name("Value"))?;
+			Some(match protected.get(&value.id()) {
+				Some(text) => (String::from(key), text.clone(), true),
+				// xml exports mark the values that were protected instead
+				None => (
+					String::from(key),
+					String::from(value.text().unwrap_or("")),
+					value
+						.attribute("ProtectInMemory")
+						.is_some_and(|value| value.eq_ignore_ascii_case("true")),
+				),
+			})
+		})
+		.collect();
+
+	EntryVersion { modified, strings }
+}
+
+// PRIVATE: the standard keepass strings get the kind and title they have in vault
+fn field_kind(
+	key: &str,
+	value: &str,
+	protected: bool,
+) -> (DynFieldKind, String) {
+	match (key, protected, value.contains('\n')) {
+		("UserName", _, _) => (DynFieldKind::TextLine, String::from("Username")),
+		("Password", _, false) => {
+			(DynFieldKind::TextLineSecret, String::from("Password"))
+		},
+		("URL", _, _) => (DynFieldKind::Url, String::from("URL")),
+		("Notes", _, _) => (DynFieldKind::MultiLine, String::from("Notes")),
+		(_, true, false) => (DynFieldKind::TextLineSecret, String::from(key)),
+		(_, true, true) => (DynFieldKind::MultiLineSecret, String::from(key)),
+		(_, false, false) => (DynFieldKind::TextLine, String::from(key)),
+		(_, false, true) => (DynFieldKind::MultiLine, String::from(key)),
+	}
+}
+
+// PRIVATE: the history of an entry holds whole copies of it, we collect the
+// changes of each string with the time the change was made
+fn read_entry(
+	entry: Node,
+	group_tags: &[String],
+	protected: &HashMap<NodeId, String>,
+) -> NewDbEntry {
+	let current = read_version(entry, protected);
+	let mut versions = entry
+		.children()
+		.find(|child| child.has_tag_name("History"))
+		.map(|history| {
+			history
+				.children()
+				.filter(|child| child.has_tag_name("Entry"))
+				.map(|version| read_version(version, protected))
+				.collect::<Vec<EntryVersion>>()
+		})
+		.unwrap_or_default();
+	versions.sort_by_key(|version| version.modified);
+
+	let title = current
+		.strings
+		.iter()
+		.find(|(key, _, _)| key == "Title")
+		.map(|(_, value, _)| String::from(value.trim()))
+		.filter(|title| !title.is_empty())
+		.unwrap_or_else(|| String::from("Untitled entry"));
+
+	// the standard strings first, notes last and the custom ones in between
+	let mut keys = ["UserName", "Password", "URL"]
+		.iter()
+		.map(|key| String::from(*key))
+		.collect::<Vec<String>>();
+	keys.extend(current.strings.iter().map(|(key, _, _)| key.clone()).filter(
+		|key| {
+			!matches!(
+				key.as_str(),
+				"Title" | "UserName" | "Password" | "URL" | "Notes"
+			)
+		},
+	));
+	keys.push(String::from("Notes"));
+
+	let fields = keys
+		.iter()
+		.filter_map(|key| {
+			let (_, value, is_protected) =
+				current.strings.iter().find(|(name, _, _)| name == key)?;
+			if value.trim().is_empty() {
+				return None;
+			}
+
+			let mut values: Vec<(u64, String)> = Vec::new();
+			for version in versions.iter().chain(std::iter::once(&current)) {
+				if let Some((_, old_value, _)) =
+					version.strings.iter().find(|(name, _, _)| name == key)
+				{
+					if !old_value.is_empty()
+						&& values.last().map(|(_, last)| last) != Some(old_value)
+					{
+						values.push((version.modified, old_value.clone()));
+					}
+				}
+			}
+
+			let (kind, title) = field_kind(key, value, *is_protected);
+			Some(DynField::new(kind, title, values))
+		})
+		.collect();
+
+	let mut tags = group_tags.to_vec();
+	tags.extend(
+		child_text(entry, "Tags")
+			.unwrap_or("")
+			.split([';', ','])
+			.map(|tag| String::from(tag.trim()))
+			.filter(|tag| !tag.is_empty()),
+	);
+
+	NewDbEntry {
+		title,
+		fields,
+		tags,
+	}
+}
+
+// PRIVATE: walk the groups, each group a entry sits in becomes one of its tags
+fn read_group(
+	group: Node,
+	path: &mut Vec<String>,
+	recycle_bin: &str,
+	protected: &HashMap<NodeId, String>,
+	entries: &mut Vec<NewDbEntry>,
+) {
+	for child in group.children() {
+		if child.has_tag_name("Entry") {
+			entries.push(read_entry(child, path, protected));
+		} else if child.has_tag_name("Group")
+			&& child_text(child, "UUID") != Some(recycle_bin)
+		{
+			path.push(String::from(child_text(child, "Name").unwrap_or("").trim()));
+			read_group(child, path, recycle_bin, protected, entries);
+			path.pop();
+		}
+	}
+}
+
+// PRIVATE: the decrypted payload is the inner header followed by the xml
+fn read_payload(payload: &[u8]) -> Result<Vec<NewDbEntry>, KdbxError> {
+	let mut reader = Reader::new(payload);
+	let stream = parse_inner_header(&mut reader)?;
+	let xml =
+		std::str::from_utf8(reader.rest()).map_err(|_| KdbxError::Corrupt)?;
+	read_xml(xml, Some(stream))
+}
+
+// PRIVATE: without a stream the values are in plain text like in xml exports
+fn read_xml(
+	xml: &str,
+	stream: Option<ChaCha20>,
+) -> Result<Vec<NewDbEntry>, KdbxError> {
+	let doc =
+		Document::parse(xml).map_err(|error| KdbxError::Xml(error.to_string()))?;
+	let mut protected = match stream {
+		Some(stream) => unprotect_values(&doc, stream)?,
+		None => HashMap::new(),
+	};
+
+	let meta =
+		doc.root_element().children().find(|node| node.has_tag_name("Meta"));
+	let recycle_bin = meta
+		.and_then(|meta| child_text(meta, "RecycleBinUUID"))
+		.filter(|uuid| *uuid != EMPTY_UUID)
+		.unwrap_or("");
+
+	let mut entries = Vec::new();
+	// the top group is the database itself so its name isn't used as a tag
+	if let Some(top) = doc
+		.root_element()
+		.children()
+		.find(|node| node.has_tag_name("Root"))
+		.and_then(|root| root.children().find(|node| node.has_tag_name("Group")))
+	{
+		read_group(top, &mut Vec::new(), recycle_bin, &protected, &mut entries);
+	}
+
+	for value in protected.values_mut() {
+		value.zeroize();
+	}
+	Ok(entries)
+}
+
+// turn the entries of an unencrypted keepass 2 xml export into vault entries
+pub fn xml_to_entries(xml: &str) -> Result<Vec<NewDbEntry>, KdbxError> {
+	read_xml(xml.trim_start_matches('\u{feff}'), None)
+}
+
+// decrypt a keepass 4 database with its password and optional key file and
+// turn its entries into vault entries
+pub fn to_entries(
+	data: &[u8],
+	password: &str,
+	key_file: Option<&[u8]>,
+) -> Result<Vec<NewDbEntry>, KdbxError> {
+	let mut reader = Reader::new(data);
+	let (header, header_bytes) = parse_header(&mut reader)?;
+	if Sha256::digest(header_bytes).as_slice() != reader.take(32)? {
+		return Err(KdbxError::Corrupt);
+	}
+
+	let mut transformed = transform_key(&header, password, key_file)?;
+	let mut master_key = Sha256::new()
+		.chain_update(&header.master_seed)
+		.chain_update(transformed)
+		.finalize();
+	let mut hmac_key = Sha512::new()
+		.chain_update(&header.master_seed)
+		.chain_update(transformed)
+		.chain_update([1])
+		.finalize();
+	transformed.zeroize();
+
+	let mut header_hmac = block_hmac(&hmac_key, u64::MAX);
+	header_hmac.update(header_bytes);
+	let payload = reader.take(32).and_then(|signature| {
+		header_hmac.verify_slice(signature).map_err(|_| KdbxError::WrongKey)?;
+		let blocks = read_blocks(&mut reader, &hmac_key)?;
+		decrypt_payload(&header, &master_key, blocks)
+	});
+	master_key.as_mut_slice().zeroize();
+	hmac_key.as_mut_slice().zeroize();
+
+	let mut payload = payload?;
+	let entries = read_payload(&payload);
+	payload.zeroize();
+	entries
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const CHACHA20_FIXTURE: &[u8] =
+		include_bytes!("fixtures/keepass_chacha20.kdbx");
+	const AES_FIXTURE: &[u8] = include_bytes!("fixtures/keepass_aes.kdbx");
+	const AES_KEY_FILE: &[u8] = include_bytes!("fixtures/keepass_aes.keyx");
+
+	#[test]
+	fn key_file_test() {
+		let key =
+			decode_hex(&"0A1B2C3D4E5F60718293A4B5C6D7E8F9".repeat(2)).unwrap();
+		assert_eq!(key_file_key(AES_KEY_FILE), key);
+		assert_eq!(key_file_key(&[7; 32]), vec![7; 32]);
+		assert_eq!(key_file_key("ab".repeat(32).as_bytes()), vec![0xab; 32]);
+		assert_eq!(key_file_key(b"anything"), Sha256::digest(b"anything").to_vec());
+		assert_eq!(decode_hex("0a 1"), None);
+	}
+
+	#[test]
+	fn parse_time_test() {
+		assert_eq!(parse_time("2024-01-02T00:00:00Z"), 1704153600);
+		assert_eq!(parse_time("AAfw1g4AAAA="), 1600000000);
+		assert_eq!(parse_time("nonsense"), 0);
+	}
+
+	#[test]
+	fn to_entries_test() {
+		let entries = to_entries(CHACHA20_FIXTURE, "vault", None).unwrap();
+		assert_eq!(entries.len(), 2);
+
+		let mail = &entries[0];
+		assert_eq!(mail.title, "Mail");
+		assert_eq!(mail.tags, vec!["work", "personal"]);
+		let db = crate::db::Db::from_new_entries(entries);
+		let fields = db.get_fields(&1);
+		assert_eq!(
+			fields
+				.iter()
+				.map(|(field, _)| db.get_name_of_field(&1, field))
+				.collect::<Vec<String>>(),
+			vec!["Username", "Password", "URL", "PIN", "Recovery", "Notes"]
+		);
+		assert_eq!(
+			db.get_history(&1, &fields[1].0).unwrap(),
+			im::vector![
+				(1700000000, String::from("hunter2")),
+				(1600000000, String::from("hunter1"))
+			]
+		);
+		assert_eq!(
+			db.get_field_kind(&1, &fields[1].0),
+			DynFieldKind::TextLineSecret
+		);
+		assert_eq!(
+			db.get_history(&1, &fields[3].0).unwrap(),
+			im::vector![
+				(1700000000, String::from("1234")),
+				(1650000000, String::from("0000"))
+			]
+		);
+		assert_eq!(
+			db.get_field_kind(&1, &fields[3].0),
+			DynFieldKind::TextLineSecret
+		);
+		assert_eq!(db.get_field_kind(&1, &fields[4].0), DynFieldKind::TextLine);
+		assert_eq!(db.get_field_kind(&1, &fields[5].0), DynFieldKind::MultiLine);
+		assert_eq!(db.get_last_by_field(&1, &fields[5].0), "line one\nline two");
+
+		assert_eq!(db.get_by_id(&2).title, "Shop");
+		assert_eq!(db.get_tags(&2), vec!["Internet", "Shopping"]);
+		let fields = db.get_fields(&2);
+		assert_eq!(db.get_last_by_field(&2, &fields[0].0), "shop & co");
+		assert_eq!(db.get_last_by_field(&2, &fields[1].0), "shop-pass");
+	}
+
+	#[test]
+	fn key_file_and_aes_test() {
+		let entries = to_entries(AES_FIXTURE, "vault", Some(AES_KEY_FILE)).unwrap();
+		assert_eq!(
+			entries.iter().map(|entry| entry.title.as_str()).collect::<Vec<&str>>(),
+			vec!["Mail", "Shop"]
+		);
+
+		assert_eq!(
+			to_entries(AES_FIXTURE, "vault", None).err(),
+			Some(KdbxError::WrongKey)
+		);
+		assert_eq!(
+			to_entries(CHACHA20_FIXTURE, "wrong", None).err(),
+			Some(KdbxError::WrongKey)
+		);
+		assert_eq!(to_entries(b"vault", "", None).err(), Some(KdbxError::NotKdbx));
+
+		let mut damaged = CHACHA20_FIXTURE.to_vec();
+		let last = damaged.len() - 40;
+		damaged[last] ^= 1;
+		assert_eq!(
+			to_entries(&damaged, "vault", None).err(),
+			Some(KdbxError::Corrupt)
+		);
+	}
+}
@@ -0,0 +1,518 @@
+use chrono::Utc;
+use std::collections::HashMap;
+
+use crate::db::{Db, DbFields, DynFieldKind};
+
+// the field titles that hold the account of an entry
+const USERNAME_TITLES: [&str; 4] = ["username", "login", "user", "email"];
+
+// what to do with an imported entry that matches an existing one
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Hash)]
+pub enum MergeAction {
+	Skip,
+	Replace,
+	#[default]
+	Merge,
+	KeepBoth,
+}
+
+impl std::fmt::Display for MergeAction {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			MergeAction::Skip => write!(f, "Skip"),
+			MergeAction::Replace => write!(f, "Replace"),
+			MergeAction::Merge => write!(f, "Merge fields"),
+			MergeAction::KeepBoth => write!(f, "Keep both"),
+		}
+	}
+}
+
+impl MergeAction {
+	pub fn all_values() -> Vec<MergeAction> {
+		vec![
+			MergeAction::Merge,
+			MergeAction::Replace,
+			MergeAction::KeepBoth,
+			MergeAction::Skip,
+		]
+	}
+}
+
+// what an import did or is about to do
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MergeSummary {
+	pub added: usize,
+	pub merged: usize,
+	pub replaced: usize,
+	pub skipped: usize,
+	pub new_fields: usize,
+	pub new_values: usize,
+}
+
+impl std::fmt::Display for MergeSummary {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		writeln!(f, "{} new entries", self.added)?;
+		writeln!(
+			f,
+			"{} merged entries with {} new fields and {} new values",
+			self.merged, self.new_fields, self.new_values
+		)?;
+		writeln!(f, "{} replaced entries", self.replaced)?;
+		write!(f, "{} skipped entries", self.skipped)
+	}
+}
+
+// PRIVATE: a field of an imported entry ready to be added to another db
+struct ImportField {
+	kind: DynFieldKind,
+	title: String,
+	history: Vec<(u64, String)>,
+	visible: bool,
+}
+
+// PRIVATE: entries are the same account when title, url and username agree
+fn match_key(db: &Db, id: &usize) -> (String, String, String) {
+	let title = db.get_by_id(id).title.trim().to_lowercase();
+	let url = db
+		.get_urls(id)
+		.first()
+		.map(|url| url.trim().trim_end_matches('/').to_lowercase())
+		.unwrap_or_default();
+	let username = db
+		.get_fields(id)
+		.iter()
+		.find(|(field, _)| {
+			USERNAME_TITLES.contains(
+				&db.get_name_of_field(id, field).trim().to_lowercase().as_str(),
+			)
+		})
+		.map(|(field, _)| db.get_last_by_field(id, field).trim().to_lowercase())
+		.unwrap_or_default();
+
+	(title, url, username)
+}
+
+// find the existing entry each imported entry matches, keyed by the imported id
+pub fn find_matches(db: &Db, import_db: &Db) -> HashMap<usize, usize> {
+	let mut existing = HashMap::new();
+	for (id, _, _) in db.get_sidebar_list() {
+		existing.entry(match_key(db, &id)).or_insert(id);
+	}
+
+	import_db
+		.get_sidebar_list()
+		.into_iter()
+		.filter_map(|(import_id, _, _)| {
+			existing.get(&match_key(import_db, &import_id)).map(|id| (import_id, *id))
+		})
+		.collect()
+}
+
+// combine two histories ordered from old to new, the existing values are all
+// kept and an incoming value is dropped when it's already there with the same
+// date or sits right next to the same value
+pub fn merge_history(
+	existing: &[(u64, String)],
+	incoming: &[(u64, String)],
+) -> Vec<(u64, String)> {
+	let mut merged = existing.to_vec();
+	let mut incoming = incoming.to_vec();
+	incoming.sort_by_key(|(time, _)| *time);
+
+	for item in incoming {
+		if merged.contains(&item) {
+			continue;
+		}
+		let index = merged.partition_point(|(time, _)| *time <= item.0);
+		let previous = index.checked_sub(1).and_then(|index| merged.get(index));
+		let is_unchanged = [previous, merged.get(index)]
+			.into_iter()
+			.flatten()
+			.any(|(_, value)| *value == item.1);
+		if is_unchanged {
+			continue;
+		}
+		merged.insert(index, item);
+	}
+
+	merged
+}
+
+// PRIVATE: the history of a field ordered from old to new
+fn history(db: &Db, id: &usize, field: &DbFields) -> Vec<(u64, String)> {
+	let mut history = db
+		.get_history(id, field)
+		.unwrap_or_default()
+		.into_iter()
+		.collect::<Vec<(u64, String)>>();
+	history.reverse();
+	history
+}
+
+// PRIVATE: references point to ids of the imported db so we take their value instead
+fn import_fields(import_db: &Db, import_id: &usize) -> Vec<ImportField> {
+	import_db
+		.get_fields(import_id)
+		.iter()
+		.map(|(field, visible)| {
+			let title = import_db.get_name_of_field(import_id, field);
+			match import_db.get_field_kind(import_id, field) {
+				DynFieldKind::Reference => ImportField {
+					kind: import_db.get_resolved_field_kind(import_id, field),
+					title,
+					history: vec![(
+						Utc::now().timestamp() as u64,
+						import_db.get_last_by_field(import_id, field),
+					)],
+					visible: *visible,
+				},
+				kind => ImportField {
+					kind,
+					title,
+					history: history(import_db, import_id, field),
+					visible: *visible,
+				},
+			}
+		})
+		.collect()
+}
+
+// PRIVATE: add imported fields to an entry keeping their visibility
+fn add_fields(db: &Db, id: &usize, fields: Vec<ImportField>) {
+	for field in fields {
+		let new_field =
+			db.add_field_with_history(id, field.kind, field.title, field.history);
+		if !field.visible {
+			db.edit_field_visbility(id, &new_field, false);
+		}
+	}
+}
+
+// PRIVATE: fields with the same title get their histories merged, the rest is added
+fn merge_fields(
+	db: &Db,
+	id: &usize,
+	fields: Vec<ImportField>,
+	summary: &mut MergeSummary,
+	dry_run: bool,
+) {
+	let mut taken = Vec::new();
+
+	for field in fields {
+		let existing = db.get_fields(id).into_iter().find(|(existing, _)| {
+			!taken.contains(existing)
+				&& db.get_field_kind(id, existing) != DynFieldKind::Reference
+				&& db.get_name_of_field(id, existing).trim().to_lowercase()
+					== field.title.trim().to_lowercase()
+		});
+
+		match existing {
+			Some((existing, _)) => {
+				let old = history(db, id, &existing);
+				let merged = merge_history(&old, &field.history);
+				summary.new_values += merged.len().saturating_sub(old.len());
+				if !dry_run && merged != old {
+					db.set_field_history(id, &existing, merged);
+				}
+				taken.push(existing);
+			},
+			None => {
+				summary.new_fields += 1;
+				if !dry_run {
+					add_fields(db, id, vec![field]);
+				}
+			},
+		}
+	}
+}
+
+// PRIVATE: walk the selected entries and either count or do what their action says
+fn run(
+	db: &Db,
+	import_db: &Db,
+	import_list: &im::Vector<(usize, bool)>,
+	matches: &HashMap<usize, usize>,
+	actions: &HashMap<usize, MergeAction>,
+	dry_run: bool,
+) -> MergeSummary {
+	let mut summary = MergeSummary::default();
+
+	for (import_id, _) in import_list.iter().filter(|(_, selected)| *selected) {
+		let action = match matches.get(import_id) {
+			Some(id) => {
+				(Some(id), actions.get(import_id).copied().unwrap_or_default())
+			},
+			None => (None, MergeAction::KeepBoth),
+		};
+
+		match action {
+			(_, MergeAction::Skip) => summary.skipped += 1,
+			(Some(id), MergeAction::Merge) => {
+				summary.merged += 1;
+				merge_fields(
+					db,
+					id,
+					import_fields(import_db, import_id),
+					&mut summary,
+					dry_run,
+				);
+				if !dry_run {
+					// set_tags drops the tags both entries have
+					let mut tags = db.get_tags(id);
+					tags.extend(import_db.get_tags(import_id));
+					db.set_tags(id, tags);
+				}
+			},
+			// replaced fields are hidden rather than deleted so nothing gets lost
+			(Some(id), MergeAction::Replace) => {
+				summary.replaced += 1;
+				if !dry_run {
+					for (field, visible) in db.get_fields(id) {
+						if visible {
+							db.edit_field_visbility(id, &field, false);
+						}
+					}
+					add_fields(db, id, import_fields(import_db, import_id));
+					db.set_tags(id, import_db.get_tags(import_id));
+				}
+			},
+			_ => {
+				summary.added += 1;
+				if !dry_run {
+					let new_id = db.add(import_db.get_by_id(import_id).title);
+					add_fields(db, &new_id, import_fields(import_db, import_id));
+					db.set_tags(&new_id, import_db.get_tags(import_id));
+				}
+			},
+		}
+	}
+
+	summary
+}
+
+// what importing the selected entries would do without touching the db
+pub fn summarize(
+	db: &Db,
+	import_db: &Db,
+	import_list: &im::Vector<(usize, bool)>,
+	matches: &HashMap<usize, usize>,
+	actions: &HashMap<usize, MergeAction>,
+) -> MergeSummary {
+	run(db, import_db, import_list, matches, actions, true)
+}
+
+// import the selected entries, matched entries are handled by their action
+pub fn apply(
+	db: &Db,
+	import_db: &Db,
+	import_list: &im::Vector<(usize, bool)>,
+	matches: &HashMap<usize, usize>,
+	actions: &HashMap<usize, MergeAction>,
+) -> MergeSummary {
+	run(db, import_db, import_list, matches, actions, false)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::db::{DynField, NewDbEntry};
+
+	// kind, title and history of a field
+	type TestField<'a> = (DynFieldKind, &'a str, Vec<(u64, &'a str)>);
+
+	// PRIVATE: an entry with fields given as kind, title and history
+	fn entry(title: &str, fields: Vec<TestField>) -> NewDbEntry {
+		NewDbEntry {
+			title: String::from(title),
+			fields: fields
+				.into_iter()
+				.map(|(kind, title, history)| {
+					DynField::new(
+						kind,
+						String::from(title),
+						history
+							.into_iter()
+							.map(|(time, value)| (time, String::from(value)))
+							.collect(),
+					)
+				})
+				.collect(),
+			tags: vec![String::from(title)],
+		}
+	}
+
+	// PRIVATE: a vault with a mail and a bank login and an import of both plus one more
+	fn dbs() -> (Db, Db) {
+		let db = Db::from_new_entries(vec![
+			entry(
+				"Mail",
+				vec![
+					(DynFieldKind::TextLine, "Username", vec![(10, "me")]),
+					(
+						DynFieldKind::TextLineSecret,
+						"Password",
+						vec![(10, "a"), (30, "c")],
+					),
+					(DynFieldKind::Url, "URL", vec![(10, "https://mail.com/")]),
+				],
+			),
+			entry(
+				"Bank",
+				vec![(DynFieldKind::TextLineSecret, "PIN", vec![(10, "1234")])],
+			),
+		]);
+		let import_db = Db::from_new_entries(vec![
+			entry(
+				"mail",
+				vec![
+					(DynFieldKind::TextLine, "username", vec![(5, "me")]),
+					(
+						DynFieldKind::TextLineSecret,
+						"Password",
+						vec![(20, "b"), (30, "c")],
+					),
+					(DynFieldKind::Url, "URL", vec![(5, "https://mail.com")]),
+					(DynFieldKind::MultiLine, "Notes", vec![(5, "hi")]),
+				],
+			),
+			entry(
+				"Bank",
+				vec![(DynFieldKind::TextLineSecret, "PIN", vec![(20, "9999")])],
+			),
+			entry(
+				"Mail",
+				vec![(DynFieldKind::TextLine, "Username", vec![(5, "other")])],
+			),
+		]);
+		(db, import_db)
+	}
+
+	#[test]
+	fn find_matches_test() {
+		let (db, import_db) = dbs();
+		assert_eq!(find_matches(&db, &import_db), HashMap::from([(1, 1), (2, 2)]));
+	}
+
+	#[test]
+	fn merge_history_test() {
+		let history = |values: &[(u64, &str)]| {
+			values
+				.iter()
+				.map(|(time, value)| (*time, String::from(*value)))
+				.collect::<Vec<(u64, String)>>()
+		};
+
+		assert_eq!(
+			merge_history(
+				&history(&[(10, "a"), (30, "c")]),
+				&history(&[(20, "b"), (30, "c")])
+			),
+			history(&[(10, "a"), (20, "b"), (30, "c")])
+		);
+		assert_eq!(
+			merge_history(&history(&[(10, "a")]), &history(&[(40, "a")])),
+			history(&[(10, "a")])
+		);
+
+		// existing values are never collapsed even when they repeat
+		let existing = history(&[(10, "a"), (20, "a"), (30, "b"), (40, "b")]);
+		let merged = merge_history(
+			&existing,
+			&history(&[
+				(5, "a"),
+				(20, "a"),
+				(40, "b"),
+				(25, "c"),
+				(50, "b"),
+				(60, "d"),
+			]),
+		);
+		assert_eq!(
+			merged,
+			history(&[
+				(10, "a"),
+				(20, "a"),
+				(25, "c"),
+				(30, "b"),
+				(40, "b"),
+				(60, "d")
+			])
+		);
+		assert_eq!(merged.len() - existing.len(), 2);
+	}
+
+	#[test]
+	fn apply_test() {
+		let (db, import_db) = dbs();
+		let matches = find_matches(&db, &import_db);
+		let import_list = im::vector![(1, true), (2, true), (3, true)];
+		let actions = HashMap::from([(2, MergeAction::Replace)]);
+
+		let summary = summarize(&db, &import_db, &import_list, &matches, &actions);
+		assert_eq!(
+			summary,
+			MergeSummary {
+				added: 1,
+				merged: 1,
+				replaced: 1,
+				skipped: 0,
+				new_fields: 1,
+				new_values: 2,
+			}
+		);
+		assert_eq!(db.get_sidebar_list().len(), 2);
+
+		assert_eq!(
+			apply(&db, &import_db, &import_list, &matches, &actions),
+			summary
+		);
+		assert_eq!(db.get_sidebar_list().len(), 3);
+
+		let password = db.get_fields(&1)[1].0;
+		assert_eq!(
+			history(&db, &1, &password),
+			vec![
+				(10, String::from("a")),
+				(20, String::from("b")),
+				(30, String::from("c"))
+			]
+		);
+		assert_eq!(db.get_fields(&1).len(), 4);
+		assert_eq!(db.get_tags(&1), vec!["Mail"]);
+
+		assert_eq!(db.get_hidden_fields(&2).len(), 1);
+		assert_eq!(db.get_visible_fields(&2).len(), 1);
+		let pin = db.get_visible_fields(&2)[0];
+		assert_eq!(db.get_last_by_field(&2, &pin), "9999");
+
+		let actions = HashMap::from([(1, MergeAction::Skip)]);
+		let summary =
+			apply(&db, &import_db, &im::vector![(1, true)], &matches, &actions);
+		assert_eq!(summary.skipped, 1);
+		assert_eq!(db.get_sidebar_list().len(), 3);
+	}
+
+	#[test]
+	fn import_reference_test() {
+		let (db, _) = dbs();
+		let import_db = Db::from_new_entries(vec![entry(
+			"Server",
+			vec![
+				(DynFieldKind::TextLineSecret, "Password", vec![(10, "root")]),
+				(DynFieldKind::Reference, "Admin", vec![(10, "{REF:1:1}")]),
+			],
+		)]);
+
+		// the reference points into the imported vault so its value is copied
+		apply(
+			&db,
+			&import_db,
+			&im::vector![(1, true)],
+			&HashMap::new(),
+			&HashMap::new(),
+		);
+		let admin = db.get_fields(&3)[1].0;
+		assert_eq!(db.get_field_kind(&3, &admin), DynFieldKind::TextLineSecret);
+		assert_eq!(db.get_last_by_field(&3, &admin), "root");
+	}
+}
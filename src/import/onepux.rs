@@ -0,0 +1,395 @@
+use serde::Deserialize;
+use serde_json::Value;
+use std::io::{Cursor, Read};
+use zeroize::Zeroize;
+
+use crate::{
+	db::{DynField, DynFieldKind, NewDbEntry},
+	expiry::format_date,
+};
+
+// the file inside the 1pux zip that holds all vaults
+const DATA_FILE: &str = "export.data";
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum OnePuxError {
+	#[error("Failed to open the 1Password export: {0}")]
+	Zip(String),
+	#[error("Failed to read the 1Password export: {0}")]
+	Json(String),
+}
+
+#[derive(Deserialize)]
+struct Export {
+	accounts: Vec<Account>,
+}
+
+#[derive(Deserialize)]
+struct Account {
+	#[serde(default)]
+	vaults: Vec<Vault>,
+}
+
+#[derive(Deserialize)]
+struct Vault {
+	attrs: VaultAttrs,
+	#[serde(default)]
+	items: Vec<Item>,
+}
+
+#[derive(Deserialize)]
+struct VaultAttrs {
+	name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Item {
+	updated_at: Option<u64>,
+	details: Details,
+	overview: Overview,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Details {
+	#[serde(default)]
+	login_fields: Vec<LoginField>,
+	notes_plain: Option<String>,
+	#[serde(default)]
+	sections: Vec<Section>,
+	#[serde(default)]
+	password_history: Vec<PasswordHistory>,
+	password: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LoginField {
+	#[serde(default)]
+	value: String,
+	#[serde(default)]
+	name: String,
+	#[serde(default)]
+	field_type: String,
+	designation: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Section {
+	#[serde(default)]
+	fields: Vec<SectionField>,
+}
+
+#[derive(Deserialize)]
+struct SectionField {
+	#[serde(default)]
+	title: String,
+	#[serde(default)]
+	value: Value,
+}
+
+#[derive(Deserialize)]
+struct Overview {
+	title: Option<String>,
+	url: Option<String>,
+	#[serde(default)]
+	urls: Vec<OverviewUrl>,
+	#[serde(default)]
+	tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct OverviewUrl {
+	url: String,
+}
+
+#[derive(Deserialize)]
+struct PasswordHistory {
+	value: String,
+	time: u64,
+}
+
+// PRIVATE: a section field holds its value in an object keyed by its type
+fn section_value(value: &Value) -> Option<(DynFieldKind, String)> {
+	let (kind, value) = value.as_object()?.iter().next()?;
+	let text = |value: &Value| value.as_str().map(String::from);
+
+	let field = match kind.as_str() {
+		"concealed" | "totp" | "creditCardNumber" => {
+			(DynFieldKind::TextLineSecret, text(value)?)
+		},
+		"url" => (DynFieldKind::Url, text(value)?),
+		"string" | "phone" | "menu" | "creditCardType" | "gender" => {
+			(DynFieldKind::TextLine, text(value)?)
+		},
+		// newer exports put the address next to the provider
+		"email" => (
+			DynFieldKind::TextLine,
+			text(value).or_else(|| text(value.get("email_address")?))?,
+		),
+		"date" => (DynFieldKind::TextLine, format_date(value.as_u64()?)),
+		// 203004 is april 2030
+		"monthYear" => {
+			let month_year = value.as_u64()?;
+			(
+				DynFieldKind::TextLine,
+				format!("{:02}/{}", month_year % 100, month_year / 100),
+			)
+		},
+		"address" => (
+			DynFieldKind::MultiLine,
+			["street", "city", "state", "zip", "country"]
+				.iter()
+				.filter_map(|key| value.get(key)?.as_str())
+				.map(|part| part.trim())
+				.filter(|part| !part.is_empty())
+				.collect::<Vec<&str>>()
+				.join("\n"),
+		),
+		"sshKey" => {
+			(DynFieldKind::MultiLineSecret, text(value.get("privateKey")?)?)
+		},
+		_ => return None,
+	};
+
+	Some(field).filter(|(_, value)| !value.trim().is_empty())
+}
+
+// PRIVATE: turn one item into an entry, 1password keeps the same fields for all
+// categories so we only need to look at the type of each value
+fn to_entry(item: &Item, vault: &str, timestamp: u64) -> NewDbEntry {
+	let timestamp = item.updated_at.unwrap_or(timestamp);
+	let details = &item.details;
+	let mut fields = Vec::new();
+	let mut push =
+		|kind: DynFieldKind, title: &str, history: Vec<(u64, String)>| {
+			fields.push(DynField::new(kind, String::from(title), history))
+		};
+
+	let mut password = details.password.clone();
+	for login_field in &details.login_fields {
+		if login_field.value.trim().is_empty() {
+			continue;
+		}
+		match (login_field.designation.as_deref(), login_field.field_type.as_str())
+		{
+			(Some("password"), _) => password = Some(login_field.value.clone()),
+			(Some("username"), _) => push(
+				DynFieldKind::TextLine,
+				"Username",
+				vec![(timestamp, login_field.value.clone())],
+			),
+			(_, "P") => push(
+				DynFieldKind::TextLineSecret,
+				&login_field.name,
+				vec![(timestamp, login_field.value.clone())],
+			),
+			// checkboxes, buttons and the like only matter to the browser
+			(_, "T" | "E" | "U") => push(
+				DynFieldKind::TextLine,
+				&login_field.name,
+				vec![(timestamp, login_field.value.clone())],
+			),
+			_ => {},
+		}
+	}
+
+	if let Some(password) = password.filter(|password| !password.is_empty()) {
+		let mut history = details
+			.password_history
+			.iter()
+			.filter(|old| !old.value.is_empty())
+			.map(|old| (old.time, old.value.clone()))
+			.collect::<Vec<(u64, String)>>();
+		history.sort_by_key(|(time, _)| *time);
+		history.push((timestamp, password));
+		push(DynFieldKind::TextLineSecret, "Password", history);
+	}
+
+	let mut urls: Vec<&str> = Vec::new();
+	for url in item
+		.overview
+		.url
+		.iter()
+		.chain(item.overview.urls.iter().map(|url| &url.url))
+	{
+		let url = url.trim();
+		if !url.is_empty() && !urls.contains(&url) {
+			urls.push(url);
+			push(DynFieldKind::Url, "URL", vec![(timestamp, String::from(url))]);
+		}
+	}
+
+	for field in details.sections.iter().flat_map(|section| &section.fields) {
+		if let Some((kind, value)) = section_value(&field.value) {
+			let title = if field.title.trim().is_empty() {
+				"Field"
+			} else {
+				field.title.trim()
+			};
+			push(kind.for_value(&value), title, vec![(timestamp, value)]);
+		}
+	}
+
+	if let Some(notes) =
+		details.notes_plain.as_ref().filter(|notes| !notes.trim().is_empty())
+	{
+		push(DynFieldKind::MultiLine, "Notes", vec![(timestamp, notes.clone())]);
+	}
+
+	// the vault and nested tags like "work/mail" become tags
+	let mut tags = vec![String::from(vault)];
+	tags.extend(
+		item
+			.overview
+			.tags
+			.iter()
+			.flat_map(|tag| tag.split('/'))
+			.map(|tag| String::from(tag.trim())),
+	);
+	tags.retain(|tag| !tag.is_empty());
+
+	NewDbEntry {
+		title: item
+			.overview
+			.title
+			.as_deref()
+			.map(|title| String::from(title.trim()))
+			.filter(|title| !title.is_empty())
+			.unwrap_or_else(|| String::from("Untitled entry")),
+		fields,
+		tags,
+	}
+}
+
+// turn a 1password 1pux export, a zip with the vaults in json, into vault entries
+pub fn to_entries(
+	data: &[u8],
+	timestamp: u64,
+) -> Result<Vec<NewDbEntry>, OnePuxError> {
+	let mut archive = zip::ZipArchive::new(Cursor::new(data))
+		.map_err(|error| OnePuxError::Zip(error.to_string()))?;
+	let mut content = String::new();
+	archive
+		.by_name(DATA_FILE)
+		.map_err(|error| OnePuxError::Zip(error.to_string()))?
+		.read_to_string(&mut content)
+		.map_err(|error| OnePuxError::Zip(error.to_string()))?;
+
+	let export = serde_json::from_str::<Export>(&content)
+		.map_err(|error| OnePuxError::Json(error.to_string()));
+	content.zeroize();
+
+	Ok(
+		export?
+			.accounts
+			.iter()
+			.flat_map(|account| &account.vaults)
+			.flat_map(|vault| {
+				vault
+					.items
+					.iter()
+					.map(|item| to_entry(item, &vault.attrs.name, timestamp))
+			})
+			.collect(),
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const FIXTURE: &[u8] = include_bytes!("fixtures/1password.1pux");
+
+	#[test]
+	fn section_value_test() {
+		let value =
+			|json: &str| section_value(&serde_json::from_str(json).unwrap());
+
+		assert_eq!(
+			value(r#"{"concealed": "1234"}"#),
+			Some((DynFieldKind::TextLineSecret, String::from("1234")))
+		);
+		assert_eq!(
+			value(r#"{"monthYear": 203004}"#),
+			Some((DynFieldKind::TextLine, String::from("04/2030")))
+		);
+		assert_eq!(
+			value(r#"{"email": {"email_address": "a@b.com", "provider": null}}"#),
+			Some((DynFieldKind::TextLine, String::from("a@b.com")))
+		);
+		assert_eq!(value(r#"{"monthYear": null}"#), None);
+		assert_eq!(value(r#"{"string": ""}"#), None);
+		assert_eq!(value(r#"{"file": {"fileName": "a.txt"}}"#), None);
+	}
+
+	#[test]
+	fn to_entries_test() {
+		let entries = to_entries(FIXTURE, 42).unwrap();
+		assert_eq!(
+			entries.iter().map(|entry| entry.title.as_str()).collect::<Vec<&str>>(),
+			vec!["Mail", "Visa", "Router"]
+		);
+		assert_eq!(entries[0].tags, vec!["Private", "Work", "Mail"]);
+
+		let db = crate::db::Db::from_new_entries(entries);
+		let names = |id: usize| {
+			db.get_fields(&id)
+				.iter()
+				.map(|(field, _)| db.get_name_of_field(&id, field))
+				.collect::<Vec<String>>()
+		};
+		assert_eq!(
+			names(1),
+			vec![
+				"Username",
+				"Password",
+				"URL",
+				"URL",
+				"PIN",
+				"one-time password",
+				"recovery email",
+				"Notes"
+			]
+		);
+		let fields = db.get_fields(&1);
+		assert_eq!(
+			db.get_history(&1, &fields[1].0).unwrap(),
+			im::vector![
+				(1700000000, String::from("hunter2")),
+				(1600000000, String::from("hunter1"))
+			]
+		);
+		assert_eq!(
+			db.get_field_kind(&1, &fields[5].0),
+			DynFieldKind::TextLineSecret
+		);
+
+		assert_eq!(
+			names(2),
+			vec![
+				"cardholder name",
+				"type",
+				"number",
+				"verification number",
+				"expiry date",
+				"address",
+				"issued"
+			]
+		);
+		let fields = db.get_fields(&2);
+		assert_eq!(
+			db.get_field_kind(&2, &fields[2].0),
+			DynFieldKind::TextLineSecret
+		);
+		assert_eq!(
+			db.get_last_by_field(&2, &fields[5].0),
+			"1 Main St\nSpringfield\n12345\nus"
+		);
+		assert_eq!(db.get_last_by_field(&2, &fields[6].0), "2024-01-02");
+
+		assert_eq!(names(3), vec!["Password"]);
+		assert!(matches!(to_entries(b"not a zip", 0), Err(OnePuxError::Zip(_))));
+	}
+}
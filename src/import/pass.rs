@@ -0,0 +1,374 @@
+use std::{
+	fs,
+	io::{ErrorKind, Write},
+	path::{Path, PathBuf},
+	process::{Command, Stdio},
+	time::UNIX_EPOCH,
+};
+use zeroize::Zeroize;
+
+use crate::db::{DynField, DynFieldKind, NewDbEntry};
+
+// the binary we hand the decryption to
+const GPG: &str = "gpg";
+// the extension pass gives every entry
+const EXTENSION: &str = "gpg";
+// pass-otp keeps its secret as a plain uri line
+const OTP_PREFIX: &str = "otpauth://";
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum PassError {
+	#[error("Failed to read the password store: {0}")]
+	Io(String),
+	#[error("Could not run gpg, make sure it's installed")]
+	GpgMissing,
+	#[error("Failed to import the private key: {0}")]
+	Key(String),
+	#[error("Failed to decrypt \"{0}\": {1}")]
+	Decrypt(String, String),
+}
+
+// decrypts through the local gpg binary, either with the keyring of the user or
+// with a private key imported into a throwaway home directory
+pub struct Gpg {
+	home: Option<PathBuf>,
+	passphrase: String,
+}
+
+impl Gpg {
+	pub fn new(key: Option<&Path>, passphrase: &str) -> Result<Self, PassError> {
+		let mut gpg = Self {
+			home: None,
+			passphrase: String::from(passphrase),
+		};
+
+		if let Some(key) = key {
+			let home = std::env::temp_dir()
+				.join(format!("vault-gpg-{:016x}", rand::random::<u64>()));
+			let mut builder = fs::DirBuilder::new();
+			// gpg refuses homes other users can read
+			#[cfg(unix)]
+			std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+			builder
+				.create(&home)
+				.map_err(|error| PassError::Io(error.to_string()))?;
+			gpg.home = Some(home);
+
+			let mut command = gpg.command();
+			command.arg("--import").arg(key);
+			gpg.run(command).map_err(PassError::Key)?;
+		}
+
+		Ok(gpg)
+	}
+
+	// PRIVATE: without a passphrase we leave the asking to the gpg agent
+	fn command(&self) -> Command {
+		let mut command = Command::new(GPG);
+		if let Some(home) = &self.home {
+			command.arg("--homedir").arg(home);
+		}
+		command.args(["--quiet", "--yes"]);
+		if !self.passphrase.is_empty() {
+			command.args([
+				"--batch",
+				"--pinentry-mode",
+				"loopback",
+				"--passphrase-fd",
+				"0",
+			]);
+		}
+		command
+	}
+
+	// PRIVATE: run gpg and return its stdout or the complaint it printed
+	fn run(&self, mut command: Command) -> Result<Vec<u8>, String> {
+		let mut child = command
+			.stdin(Stdio::piped())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::piped())
+			.spawn()
+			.map_err(|error| match error.kind() {
+				ErrorKind::NotFound => PassError::GpgMissing.to_string(),
+				_ => error.to_string(),
+			})?;
+
+		// dropping stdin closes it so gpg doesn't wait for more
+		if let Some(mut stdin) = child.stdin.take() {
+			if !self.passphrase.is_empty() {
+				let mut input = Vec::with_capacity(self.passphrase.len() + 1);
+				input.extend_from_slice(self.passphrase.as_bytes());
+				input.push(b'\n');
+				let written = stdin.write_all(&input);
+				input.zeroize();
+				written.map_err(|error| error.to_string())?;
+			}
+		}
+
+		let output = child.wait_with_output().map_err(|error| error.to_string())?;
+		if output.status.success() {
+			Ok(output.stdout)
+		} else {
+			Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+		}
+	}
+
+	pub fn decrypt(&self, file: &Path) -> Result<String, PassError> {
+		let mut command = self.command();
+		command.arg("--decrypt").arg(file);
+		let mut output = self.run(command).map_err(|error| {
+			PassError::Decrypt(file.to_string_lossy().to_string(), error)
+		})?;
+		let content = String::from_utf8_lossy(&output).to_string();
+		output.zeroize();
+
+		Ok(content)
+	}
+}
+
+impl Drop for Gpg {
+	fn drop(&mut self) {
+		self.passphrase.zeroize();
+		if let Some(home) = &self.home {
+			// the agent gpg started for our home would otherwise keep running
+			let _ = Command::new("gpgconf")
+				.arg("--homedir")
+				.arg(home)
+				.args(["--kill", "gpg-agent"])
+				.stdout(Stdio::null())
+				.stderr(Stdio::null())
+				.status();
+			let _ = fs::remove_dir_all(home);
+		}
+	}
+}
+
+// PRIVATE: collect the entries below dir, hidden files like .gpg-id or .git
+// are skipped and symlinks aren't followed so we can't loop
+fn find_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+	let mut children = fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+	children.sort_by_key(|child| child.file_name());
+
+	for child in children {
+		if child.file_name().to_string_lossy().starts_with('.') {
+			continue;
+		}
+		let path = child.path();
+		if child.file_type()?.is_dir() {
+			find_files(&path, files)?;
+		} else if path.extension().is_some_and(|ext| ext == EXTENSION) {
+			files.push(path);
+		}
+	}
+
+	Ok(())
+}
+
+// PRIVATE: the keys people use in pass differ a lot so we only guess urls and secrets
+fn kind_for_key(key: &str) -> DynFieldKind {
+	let key = key.to_lowercase();
+	if matches!(key.as_str(), "url" | "website" | "site" | "link") {
+		DynFieldKind::Url
+	} else if ["pass", "pin", "secret", "cvv", "otp"]
+		.iter()
+		.any(|secret| key.contains(secret))
+	{
+		DynFieldKind::TextLineSecret
+	} else {
+		DynFieldKind::TextLine
+	}
+}
+
+// PRIVATE: the first line is the password, "key: value" lines become fields and
+// everything else ends up in the notes
+fn to_entry(
+	title: String,
+	tags: Vec<String>,
+	content: &str,
+	timestamp: u64,
+) -> NewDbEntry {
+	let mut lines = content.lines();
+	let mut fields = Vec::new();
+	let mut notes = Vec::new();
+	let mut push = |kind: DynFieldKind, title: &str, value: &str| {
+		fields.push(DynField::new(
+			kind,
+			String::from(title),
+			vec![(timestamp, String::from(value))],
+		))
+	};
+
+	if let Some(password) = lines.next().filter(|line| !line.trim().is_empty()) {
+		push(DynFieldKind::TextLineSecret, "Password", password);
+	}
+
+	for line in lines {
+		if line.trim().starts_with(OTP_PREFIX) {
+			push(DynFieldKind::TextLineSecret, "TOTP", line.trim());
+			continue;
+		}
+		// splitting on ": " keeps urls like https://mail.com in one piece
+		match line.split_once(": ") {
+			Some((key, value))
+				if !key.trim().is_empty()
+					&& !key.starts_with(char::is_whitespace)
+					&& !value.trim().is_empty() =>
+			{
+				push(kind_for_key(key.trim()), key.trim(), value.trim());
+			},
+			_ => notes.push(line),
+		}
+	}
+
+	let notes = notes.join("\n");
+	if !notes.trim().is_empty() {
+		push(DynFieldKind::MultiLine, "Notes", notes.trim());
+	}
+
+	NewDbEntry {
+		title,
+		fields,
+		tags,
+	}
+}
+
+// walk a password store and turn every file decrypt can open into an entry,
+// the folders an entry sits in become its tags
+pub fn to_entries<F>(
+	root: &Path,
+	mut decrypt: F,
+	timestamp: u64,
+) -> Result<Vec<NewDbEntry>, PassError>
+where
+	F: FnMut(&Path) -> Result<String, PassError>,
+{
+	let mut files = Vec::new();
+	find_files(root, &mut files)
+		.map_err(|error| PassError::Io(error.to_string()))?;
+
+	let mut entries = Vec::new();
+	for file in files {
+		let relative = file.strip_prefix(root).unwrap_or(&file);
+		let tags = relative
+			.parent()
+			.map(|parent| {
+				parent
+					.components()
+					.map(|folder| folder.as_os_str().to_string_lossy().to_string())
+					.collect::<Vec<String>>()
+			})
+			.unwrap_or_default();
+		let title = file
+			.file_stem()
+			.map(|stem| stem.to_string_lossy().to_string())
+			.unwrap_or_default();
+		// pass doesn't keep dates so the last change of the file is the best we get
+		let modified = fs::metadata(&file)
+			.and_then(|metadata| metadata.modified())
+			.ok()
+			.and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+			.map(|modified| modified.as_secs())
+			.unwrap_or(timestamp);
+
+		let mut content = decrypt(&file)?;
+		let entry = to_entry(title, tags, &content, modified);
+		content.zeroize();
+
+		if !entry.fields.is_empty() {
+			entries.push(entry);
+		}
+	}
+
+	Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::db::Db;
+
+	// PRIVATE: the name and kind of every field of an entry
+	fn fields(db: &Db, id: usize) -> Vec<(String, DynFieldKind)> {
+		db.get_fields(&id)
+			.iter()
+			.map(|(field, _)| {
+				(db.get_name_of_field(&id, field), db.get_field_kind(&id, field))
+			})
+			.collect()
+	}
+
+	#[test]
+	fn to_entry_test() {
+		let entry = to_entry(
+			String::from("mail.com"),
+			vec![String::from("email")],
+			"hunter2\nlogin: me@mail.com\nURL: https://mail.com\notpauth://totp/Mail?secret=ABC\nRecovery PIN: 1234\n  indented: note\nhttps://mail.com/help\n",
+			42,
+		);
+		let db = Db::from_new_entries(vec![entry]);
+		assert_eq!(
+			fields(&db, 1),
+			vec![
+				(String::from("Password"), DynFieldKind::TextLineSecret),
+				(String::from("login"), DynFieldKind::TextLine),
+				(String::from("URL"), DynFieldKind::Url),
+				(String::from("TOTP"), DynFieldKind::TextLineSecret),
+				(String::from("Recovery PIN"), DynFieldKind::TextLineSecret),
+				(String::from("Notes"), DynFieldKind::MultiLine),
+			]
+		);
+		let notes = db.get_fields(&1)[5].0;
+		assert_eq!(
+			db.get_last_by_field(&1, &notes),
+			"indented: note\nhttps://mail.com/help"
+		);
+
+		let entry = to_entry(String::from("empty"), Vec::new(), "\n\n", 42);
+		assert!(entry.fields.is_empty());
+	}
+
+	#[test]
+	fn to_entries_test() {
+		let fixtures = Path::new(file!()).with_file_name("fixtures");
+		let root = fixtures.join("password-store");
+		let key = fixtures.join("pass_key.asc");
+
+		// not every machine running the tests has gpg
+		if Command::new(GPG).arg("--version").output().is_err() {
+			return;
+		}
+
+		let gpg = Gpg::new(Some(&key), "vault").unwrap();
+		let entries = to_entries(&root, |file| gpg.decrypt(file), 42).unwrap();
+		assert_eq!(
+			entries
+				.iter()
+				.map(|entry| (entry.title.as_str(), entry.tags.join("/")))
+				.collect::<Vec<(&str, String)>>(),
+			vec![
+				("bank", String::new()),
+				("mail.com", String::from("email")),
+				("box", String::from("work/servers")),
+			]
+		);
+
+		let db = Db::from_new_entries(entries);
+		assert_eq!(fields(&db, 2).len(), 6);
+		let password = db.get_fields(&2)[0].0;
+		assert_eq!(db.get_last_by_field(&2, &password), "hunter2");
+		assert_eq!(
+			fields(&db, 3),
+			vec![
+				(String::from("Password"), DynFieldKind::TextLineSecret),
+				(String::from("user"), DynFieldKind::TextLine),
+				(String::from("host"), DynFieldKind::TextLine),
+			]
+		);
+
+		let gpg = Gpg::new(Some(&key), "wrong").unwrap();
+		assert!(matches!(
+			to_entries(&root, |file| gpg.decrypt(file), 42),
+			Err(PassError::Decrypt(_, _))
+		));
+	}
+}
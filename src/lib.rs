@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use floem::{
+	action::exec_after,
+	reactive::{use_context, RwSignal, SignalGet, SignalUpdate},
+};
+
+pub mod audit;
+pub mod breach;
+pub mod config;
+pub mod db;
+pub mod encryption;
+pub mod env;
+pub mod expiry;
+pub mod export {
+	pub mod csv;
+	pub mod emergency_kit;
+	pub mod json;
+	pub mod keepass_xml;
+	pub mod plaintext;
+}
+pub mod import {
+	pub mod bitwarden;
+	pub mod csv;
+	pub mod csv_formats;
+	pub mod detect;
+	pub mod json;
+	pub mod kdbx;
+	pub mod merge;
+	pub mod onepux;
+	pub mod pass;
+}
+pub mod password_gen;
+pub mod password_strength;
+pub mod qr;
+pub mod search {
+	pub mod fuzzy;
+	pub mod index;
+	pub mod query;
+}
+
+pub mod ui {
+	pub mod app_view;
+	pub mod audit_view;
+	pub mod clipboard;
+	pub mod colors;
+	pub mod keyboard;
+	pub mod details {
+		pub mod button_slots;
+		pub mod detail_view;
+		pub mod dyn_field_title_form;
+		pub mod heading;
+		pub mod hidden_fields;
+		pub mod list_item;
+		pub mod new_field;
+	}
+	pub mod entry_history_view;
+	pub mod expiry_view;
+	pub mod export_view;
+	pub mod generator_rule_view;
+	pub mod history_view;
+	pub mod import {
+		pub mod csv_import_view;
+		pub mod import_detail_view;
+		pub mod import_view;
+	}
+	pub mod onboard_view;
+	pub mod password_view;
+	pub mod qr_view;
+	pub mod settings {
+		pub mod database;
+		pub mod editing;
+		pub mod general;
+		pub mod generator;
+		pub mod settings_view;
+		pub mod shortcut;
+	}
+	pub mod transfer_view;
+	pub mod window_management;
+	pub mod primitives {
+		pub mod button;
+		pub mod checkbox;
+		pub mod debounce;
+		pub mod file_input;
+		pub mod input_button_field;
+		pub mod input_field;
+		pub mod logo;
+		pub mod multiline_input_field;
+		pub mod password_field;
+		pub mod que;
+		pub mod select;
+		pub mod strength_meter;
+		pub mod styles;
+		pub mod toast;
+		pub mod tooltip;
+	}
+}
+
+use crate::{
+	env::Environment,
+	ui::{
+		clipboard::clear_clipboard, primitives::que::Que,
+		window_management::close_all_windows,
+	},
+};
+
+pub const DEFAULT_DEBUG_PASSWORD: &str = "p";
+
+pub type TimeoutQueId = RwSignal<u8>;
+
+pub fn create_lock_timeout() {
+	let env = use_context::<Environment>().expect("No env context provider");
+	let que = use_context::<Que>().expect("No que context provider");
+	let timeout_que_id =
+		use_context::<TimeoutQueId>().expect("No timeout_que_id context provider");
+
+	let timeout = env.config.general.read().db_timeout;
+
+	let mut id = *que.lock.get().last().unwrap_or(&timeout_que_id.get());
+	if id == 255 {
+		id = 0;
+	} else {
+		id += 1;
+	}
+	que.lock.update(|item| item.push(id));
+	timeout_que_id.set(id);
+
+	exec_after(Duration::from_secs_f32(timeout), move |_| {
+		if que.lock.get().contains(&id) {
+			que.lock.update(|item| item.retain(|ids| *ids != id));
+
+			lock_app();
+		}
+	});
+}
+
+pub fn lock_app() {
+	let env = use_context::<Environment>().expect("No env context provider");
+	let que = use_context::<Que>().expect("No que context provider");
+	let app_state =
+		use_context::<RwSignal<AppState>>().expect("No app_state context provider");
+
+	close_all_windows();
+	clear_clipboard();
+	que.unque_all_tooltips();
+	env.db.lock();
+	*env.db.vault_unlocked.write() = false;
+	app_state.set(AppState::PassPrompting);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppState {
+	OnBoarding,
+	PassPrompting,
+	Ready,
+}
@@ -3,78 +3,30 @@
 	windows_subsystem = "windows"
 )]
 
+use chrono::Utc;
 use image::ImageReader;
-use std::{io::Cursor, time::Duration};
+use std::io::Cursor;
 use zeroize::Zeroize;
 
 use floem::{
-	action::exec_after,
 	event::{Event, EventListener},
 	keyboard::PhysicalKey,
 	kurbo::Size,
 	menu::{Menu, MenuItem},
 	reactive::{
 		create_effect, create_rw_signal, create_trigger, provide_context, untrack,
-		use_context, RwSignal, SignalGet, SignalUpdate,
+		SignalGet, SignalUpdate,
 	},
 	views::{container, dyn_container, Decorators},
 	window::{Icon, WindowConfig},
 	Application, IntoView, View,
 };
-pub mod config;
-pub mod db;
-mod encryption;
-mod env;
-mod password_gen;
 
-mod ui {
-	pub mod app_view;
-	pub mod colors;
-	pub mod keyboard;
-	pub mod details {
-		pub mod button_slots;
-		pub mod detail_view;
-		pub mod dyn_field_title_form;
-		pub mod heading;
-		pub mod hidden_fields;
-		pub mod list_item;
-		pub mod new_field;
-	}
-	pub mod history_view;
-	pub mod import {
-		pub mod import_detail_view;
-		pub mod import_view;
-	}
-	pub mod onboard_view;
-	pub mod password_view;
-	pub mod settings {
-		pub mod database;
-		pub mod editing;
-		pub mod general;
-		pub mod settings_view;
-		pub mod shortcut;
-	}
-	pub mod window_management;
-	pub mod primitives {
-		pub mod button;
-		pub mod checkbox;
-		pub mod debounce;
-		pub mod file_input;
-		pub mod input_button_field;
-		pub mod input_field;
-		pub mod logo;
-		pub mod multiline_input_field;
-		pub mod password_field;
-		pub mod que;
-		pub mod select;
-		pub mod styles;
-		pub mod toast;
-		pub mod tooltip;
-	}
-}
-
-use crate::{
+use vault::{
+	create_lock_timeout,
 	env::Environment,
+	expiry::get_due_summary,
+	lock_app,
 	ui::{
 		app_view::app_view,
 		keyboard::{
@@ -87,60 +39,11 @@ use crate::{
 			tooltip::TooltipSignals,
 		},
 		settings::settings_view::settings_view,
-		window_management::{close_all_windows, opening_window, WindowSpec},
+		window_management::{opening_window, WindowSpec},
 	},
+	AppState, TimeoutQueId, DEFAULT_DEBUG_PASSWORD,
 };
 
-pub const DEFAULT_DEBUG_PASSWORD: &str = "p";
-
-pub type TimeoutQueId = RwSignal<u8>;
-
-pub fn create_lock_timeout() {
-	let env = use_context::<Environment>().expect("No env context provider");
-	let que = use_context::<Que>().expect("No que context provider");
-	let timeout_que_id =
-		use_context::<TimeoutQueId>().expect("No timeout_que_id context provider");
-
-	let timeout = env.config.general.read().db_timeout;
-
-	let mut id = *que.lock.get().last().unwrap_or(&timeout_que_id.get());
-	if id == 255 {
-		id = 0;
-	} else {
-		id += 1;
-	}
-	que.lock.update(|item| item.push(id));
-	timeout_que_id.set(id);
-
-	exec_after(Duration::from_secs_f32(timeout), move |_| {
-		if que.lock.get().contains(&id) {
-			que.lock.update(|item| item.retain(|ids| *ids != id));
-
-			lock_app();
-		}
-	});
-}
-
-pub fn lock_app() {
-	let env = use_context::<Environment>().expect("No env context provider");
-	let que = use_context::<Que>().expect("No que context provider");
-	let app_state =
-		use_context::<RwSignal<AppState>>().expect("No app_state context provider");
-
-	close_all_windows();
-	que.unque_all_tooltips();
-	env.db.lock();
-	*env.db.vault_unlocked.write() = false;
-	app_state.set(AppState::PassPrompting);
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub enum AppState {
-	OnBoarding,
-	PassPrompting,
-	Ready,
-}
-
 fn window_icon() -> Icon {
 	let image =
 		ImageReader::new(Cursor::new(include_bytes!("../assets/64x64.png")))
@@ -218,6 +121,11 @@ fn main() {
 							password.update(|pass| pass.zeroize());
 							toast_signals.kill_all_toasts();
 							app_state.set(AppState::Ready);
+							if let Some(summary) = get_due_summary(
+								&env_closure.db.get_due_items(Utc::now().timestamp() as u64),
+							) {
+								toast_signals.add(summary);
+							}
 						});
 					},
 					Err(err) => {
@@ -304,10 +212,7 @@ fn main() {
 					if key == env_shortcuts.config.general.read().shortcuts.lock.0
 						&& modifier == env_shortcuts.config.general.read().shortcuts.lock.1
 					{
-						que.unque_all_tooltips();
-						env_shortcuts.db.lock();
-						*env_shortcuts.db.vault_unlocked.write() = false;
-						app_state.set(AppState::PassPrompting);
+						lock_app();
 					}
 
 					if key == env_shortcuts.config.general.read().shortcuts.search.0
@@ -1,49 +1,404 @@
 use rand::{rngs::OsRng, Rng};
 use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+use zeroize::Zeroize;
 
 use floem::reactive::use_context;
 
-use crate::env::Environment;
+use crate::{
+	config::{
+		Capitalization, GeneratorMode, GeneratorProfile, GeneratorRule,
+		PassphraseOptions,
+	},
+	env::Environment,
+};
+
+pub const UPPER: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+pub const LOWER: &str = "abcdefghijklmnopqrstuvwxyz";
+pub const DIGITS: &str = "0123456789";
+pub const SYMBOLS: &str = "!@#$%^&*()_-+=[]{}|;:,.<>/";
+// characters that are easily confused with each other in most fonts
+pub const LOOK_ALIKES: &str = "0O1lI|";
+// the BIP39 english wordlist (CC0-1.0, 2048 words, 11 bits per word) as
+// published with the rust-bitcoin bip39 crate; we bundle it instead of the
+// EFF large wordlist which we don't have a vetted copy of
+const WORDLIST: &str = include_str!("../assets/wordlists/english.txt");
+// how often we redraw a password before we call a rule unsatisfiable
+const MAX_RULE_ATTEMPTS: usize = 10_000;
+
+// PRIVATE: every character in the given strings exactly once
+fn dedup_alphabet<'a>(chars: impl Iterator<Item = &'a char>) -> Vec<char> {
+	let mut alphabet: Vec<char> = Vec::new();
+	for char in chars {
+		if !alphabet.contains(char) {
+			alphabet.push(*char);
+		}
+	}
+	alphabet
+}
+
+// PRIVATE: each character is picked uniformly from the alphabet
+fn sample_uniform<R: Rng>(
+	alphabet: &[char],
+	length: usize,
+	rng: &mut R,
+) -> Vec<char> {
+	(0..length).map(|_| alphabet[rng.gen_range(0..alphabet.len())]).collect()
+}
+
+// PRIVATE: a random string over all characters the generator knows about
+fn random_string<R: Rng>(length: usize, rng: &mut R) -> String {
+	let chars = format!("{}{}{}{}", UPPER, LOWER, DIGITS, SYMBOLS)
+		.chars()
+		.collect::<Vec<char>>();
+	let alphabet = dedup_alphabet(chars.iter());
+
+	sample_uniform(&alphabet, length, rng).into_iter().collect()
+}
 
 pub fn get_random_string(length: usize) -> String {
 	// Initialize RNG with system entropy
 	let mut rng = OsRng;
 
-	// Define the character set for the random string
-	#[rustfmt::skip]
-	let charset: [char; 160] = [
-		// 0-1 three times so we get some numbers
-		'0', '1', '2', '3', '4', '5', '6', '7', '8', '9',
-		'0', '1', '2', '3', '4', '5', '6', '7', '8', '9',
-		'0', '1', '2', '3', '4', '5', '6', '7', '8', '9',
-
-		// a-bA-Z
-		'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o',
-		'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', 'A', 'B', 'C', 'D',
-		'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R', 'S',
-		'T', 'U', 'V', 'W', 'X', 'Y', 'Z',
-
-		// a-b twice to bias over special characters
-		'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o',
-		'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z',
-		'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o',
-		'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z',
-
-		// special characters
-		'!', '@', '#', '$', '%', '^', '&', '*', '(', ')', '_', '-', '+', '=', '[',
-		']', '{', '}', '|', ';', ':', ',', '.', '<', '>', '/',
-	];
-
-	// Generate a string of n characters
-	(0..length)
+	random_string(length, &mut rng)
+}
+
+// PRIVATE: the de-duplicated characters of each enabled class
+fn get_classes(profile: &GeneratorProfile, forbidden: &str) -> Vec<Vec<char>> {
+	let symbols = if profile.custom_symbols.trim().is_empty() {
+		SYMBOLS
+	} else {
+		&profile.custom_symbols
+	};
+
+	[
+		(profile.upper, UPPER),
+		(profile.lower, LOWER),
+		(profile.digits, DIGITS),
+		(profile.symbols, symbols),
+	]
+	.into_iter()
+	.filter(|(enabled, _)| *enabled)
+	.map(|(_, chars)| {
+		let mut class = Vec::new();
+		for char in chars.chars() {
+			let is_look_alike =
+				profile.exclude_look_alikes && LOOK_ALIKES.contains(char);
+			if !char.is_whitespace()
+				&& !is_look_alike
+				&& !forbidden.contains(char)
+				&& !class.contains(&char)
+			{
+				class.push(char);
+			}
+		}
+		class
+	})
+	.filter(|class| !class.is_empty())
+	.collect()
+}
+
+pub fn get_wordlist() -> &'static [&'static str] {
+	static WORDS: OnceLock<Vec<&'static str>> = OnceLock::new();
+	WORDS.get_or_init(|| {
+		WORDLIST
+			.lines()
+			.map(|word| word.trim())
+			.filter(|word| !word.is_empty())
+			.collect()
+	})
+}
+
+// PRIVATE: a passphrase of random words from the wordlist
+fn random_passphrase<R: Rng>(
+	options: &PassphraseOptions,
+	rng: &mut R,
+) -> String {
+	let wordlist = get_wordlist();
+	let symbols = SYMBOLS.chars().collect::<Vec<char>>();
+
+	let mut words = (0..options.word_count)
 		.map(|_| {
-			let index = rng.gen_range(0..charset.len());
-			charset[index]
+			let word = wordlist[rng.gen_range(0..wordlist.len())];
+			let capitalize = match options.capitalization {
+				Capitalization::Lowercase => false,
+				Capitalization::FirstLetter => true,
+				Capitalization::Random => rng.gen_bool(0.5),
+			};
+
+			if capitalize {
+				let mut chars = word.chars();
+				chars
+					.next()
+					.map(|first| first.to_uppercase().chain(chars).collect())
+					.unwrap_or_default()
+			} else {
+				String::from(word)
+			}
 		})
-		.collect()
+		.collect::<Vec<String>>();
+
+	// the extra characters are attached to a random word each
+	if !words.is_empty() {
+		if options.add_digit {
+			let idx = rng.gen_range(0..words.len());
+			let digit = rng.gen_range(0..10);
+			words[idx].push_str(&digit.to_string());
+		}
+		if options.add_symbol {
+			let idx = rng.gen_range(0..words.len());
+			words[idx].push(symbols[rng.gen_range(0..symbols.len())]);
+		}
+	}
+
+	let passphrase = words.join(&options.separator);
+	for word in &mut words {
+		word.zeroize();
+	}
+
+	passphrase
+}
+
+pub fn get_random_passphrase(options: &PassphraseOptions) -> String {
+	// Initialize RNG with system entropy
+	let mut rng = OsRng;
+
+	random_passphrase(options, &mut rng)
 }
 
-pub fn generate_password(entropy: String) -> String {
+// the entropy in bits of a passphrase generated with these options
+pub fn get_passphrase_entropy(options: &PassphraseOptions) -> f64 {
+	if options.word_count == 0 {
+		return 0.0;
+	}
+
+	let words = options.word_count as f64;
+	let mut entropy = words * (get_wordlist().len() as f64).log2();
+	if options.capitalization == Capitalization::Random {
+		entropy += words;
+	}
+	if options.add_digit {
+		entropy += (10.0 * words).log2();
+	}
+	if options.add_symbol {
+		entropy += (SYMBOLS.chars().count() as f64 * words).log2();
+	}
+
+	entropy
+}
+
+// PRIVATE: whether every class shows up at least once
+fn has_each_class(password: &[char], classes: &[Vec<char>]) -> bool {
+	classes.iter().all(|class| password.iter().any(|char| class.contains(char)))
+}
+
+// PRIVATE: a password over the classes of a profile minus the forbidden characters
+fn generate_characters<R: Rng>(
+	profile: &GeneratorProfile,
+	forbidden: &str,
+	rng: &mut R,
+) -> String {
+	let classes = get_classes(profile, forbidden);
+	let alphabet = dedup_alphabet(classes.iter().flatten());
+
+	if alphabet.is_empty() {
+		return String::from("");
+	}
+
+	// we redraw the whole password instead of placing one character per class
+	// so every valid password stays equally likely
+	let enforce_classes =
+		profile.at_least_one_of_each && classes.len() <= profile.length;
+	loop {
+		let password = sample_uniform(&alphabet, profile.length, rng);
+		if !enforce_classes || has_each_class(&password, &classes) {
+			return password.into_iter().collect();
+		}
+	}
+}
+
+pub fn generate_from_profile<R: Rng>(
+	profile: &GeneratorProfile,
+	rng: &mut R,
+) -> String {
+	if profile.mode == GeneratorMode::Passphrase {
+		return random_passphrase(&profile.passphrase, rng);
+	}
+
+	generate_characters(profile, "", rng)
+}
+
+// PRIVATE: the profile adjusted so it can satisfy the rule, rules describe
+// character passwords so passphrase profiles fall back to characters
+fn apply_rule(
+	profile: &GeneratorProfile,
+	rule: &GeneratorRule,
+) -> GeneratorProfile {
+	let max_length = if rule.max_length == 0 {
+		usize::MAX
+	} else {
+		rule.max_length
+	};
+
+	GeneratorProfile {
+		mode: GeneratorMode::Characters,
+		length: profile.length.max(rule.min_length).min(max_length),
+		upper: profile.upper || rule.require_upper,
+		lower: profile.lower || rule.require_lower,
+		digits: profile.digits || rule.require_digits,
+		symbols: profile.symbols || rule.require_symbols,
+		..profile.clone()
+	}
+}
+
+// whether a password follows all constraints of a rule
+pub fn is_compliant(password: &str, rule: &GeneratorRule) -> bool {
+	let chars = password.chars().collect::<Vec<char>>();
+
+	chars.len() >= rule.min_length
+		&& (rule.max_length == 0 || chars.len() <= rule.max_length)
+		&& (!rule.require_upper || chars.iter().any(|char| char.is_uppercase()))
+		&& (!rule.require_lower || chars.iter().any(|char| char.is_lowercase()))
+		&& (!rule.require_digits || chars.iter().any(|char| char.is_ascii_digit()))
+		&& (!rule.require_symbols
+			|| chars.iter().any(|char| !char.is_alphanumeric()))
+		&& !chars.iter().any(|char| rule.forbidden.contains(*char))
+		&& (!rule.no_repeats || chars.windows(2).all(|pair| pair[0] != pair[1]))
+}
+
+// generate a password that follows the rule or None if the rule can't be met
+pub fn generate_with_rule<R: Rng>(
+	profile: &GeneratorProfile,
+	rule: &GeneratorRule,
+	rng: &mut R,
+) -> Option<String> {
+	let profile = apply_rule(profile, rule);
+
+	for _ in 0..MAX_RULE_ATTEMPTS {
+		let mut password = generate_characters(&profile, &rule.forbidden, rng);
+		if is_compliant(&password, rule) {
+			return Some(password);
+		}
+		password.zeroize();
+	}
+
+	None
+}
+
+// the entropy in bits of a password generated with this profile
+pub fn get_profile_entropy(profile: &GeneratorProfile) -> f64 {
+	if profile.mode == GeneratorMode::Passphrase {
+		return get_passphrase_entropy(&profile.passphrase);
+	}
+
+	characters_entropy(profile, "")
+}
+
+// PRIVATE: the entropy of a character password without the forbidden characters
+fn characters_entropy(profile: &GeneratorProfile, forbidden: &str) -> f64 {
+	let classes = get_classes(profile, forbidden);
+	let alphabet = dedup_alphabet(classes.iter().flatten());
+
+	if alphabet.is_empty() || profile.length == 0 {
+		return 0.0;
+	}
+
+	let length = profile.length as f64;
+	let size = alphabet.len() as f64;
+	if !profile.at_least_one_of_each || classes.len() > profile.length {
+		return length * size.log2();
+	}
+
+	// inclusion-exclusion over all passwords missing at least one class,
+	// relative to the size^length passwords without any rule
+	let mut share = 0.0;
+	for subset in 0..(1_usize << classes.len()) {
+		let missing = alphabet
+			.iter()
+			.filter(|char| {
+				classes
+					.iter()
+					.enumerate()
+					.any(|(idx, class)| subset & (1 << idx) != 0 && class.contains(char))
+			})
+			.count() as f64;
+		let ratio = ((size - missing) / size).powf(length);
+		if subset.count_ones() % 2 == 0 {
+			share += ratio;
+		} else {
+			share -= ratio;
+		}
+	}
+
+	length * size.log2() + share.log2()
+}
+
+// the entropy in bits of a password generated under a rule, the required
+// classes and the no-repeats constraint are approximated
+pub fn get_rule_entropy(
+	profile: &GeneratorProfile,
+	rule: &GeneratorRule,
+) -> f64 {
+	let profile = apply_rule(profile, rule);
+	let entropy = characters_entropy(&profile, &rule.forbidden);
+	let size =
+		dedup_alphabet(get_classes(&profile, &rule.forbidden).iter().flatten())
+			.len() as f64;
+
+	if rule.no_repeats && profile.length > 1 && size > 1.0 {
+		// every character after the first has one option less
+		entropy - (profile.length - 1) as f64 * (size / (size - 1.0)).log2()
+	} else {
+		entropy
+	}
+}
+
+// the lowercase host of a url without scheme, credentials, port or path
+pub fn get_host(url: &str) -> String {
+	let url = url.trim().to_lowercase();
+	let without_scheme =
+		url.split_once("://").map(|(_, rest)| rest).unwrap_or(&url);
+	let authority =
+		without_scheme.split(['/', '?', '#']).next().unwrap_or_default();
+	let host = authority.rsplit('@').next().unwrap_or_default();
+
+	String::from(
+		host.split(':').next().unwrap_or_default().trim_start_matches("www."),
+	)
+}
+
+// whether a url is on a domain or one of its subdomains
+pub fn matches_domain(url: &str, domain: &str) -> bool {
+	let host = get_host(url);
+	let domain = get_host(domain);
+
+	!domain.is_empty()
+		&& (host == domain || host.ends_with(&format!(".{}", domain)))
+}
+
+// the rule an entry's passwords follow, its own or a preset for one of its urls
+pub fn get_entry_rule(env: &Environment, id: &usize) -> Option<GeneratorRule> {
+	env.db.get_generator_rule(id).or_else(|| {
+		let urls = env.db.get_urls(id);
+		env
+			.config
+			.get_generator_rule_presets()
+			.into_iter()
+			.find(|preset| {
+				preset
+					.domains
+					.iter()
+					.any(|domain| urls.iter().any(|url| matches_domain(url, domain)))
+			})
+			.map(|preset| preset.rule)
+	})
+}
+
+pub fn generate_password(
+	entropy: String,
+	profile_id: usize,
+	rule: Option<&GeneratorRule>,
+) -> Option<String> {
 	let env = use_context::<Environment>().expect("No env context provider");
 
 	// Initialize RNG with system entropy
@@ -62,8 +417,317 @@ pub fn generate_password(entropy: String) -> String {
 	// Not the greatest way to do this
 	rng.fill(&mut hash_array);
 
-	// Generate a string of n characters
-	let length = env.config.general.read().pass_gen_letter_count;
+	let profile = env.config.get_generator_profile(profile_id);
+
+	match rule {
+		Some(rule) => generate_with_rule(&profile, rule, &mut rng),
+		None => Some(generate_from_profile(&profile, &mut rng)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rand::{rngs::StdRng, SeedableRng};
+
+	// chi-square statistic of the observed character counts against a uniform
+	// distribution over the alphabet
+	fn chi_square(samples: &str, alphabet: &[char]) -> f64 {
+		let total = samples.chars().count() as f64;
+		let expected = total / alphabet.len() as f64;
+		alphabet
+			.iter()
+			.map(|char| {
+				let observed =
+					samples.chars().filter(|item| item == char).count() as f64;
+				(observed - expected).powi(2) / expected
+			})
+			.sum()
+	}
+
+	fn profile(length: usize) -> GeneratorProfile {
+		GeneratorProfile {
+			length,
+			at_least_one_of_each: false,
+			..GeneratorProfile::default()
+		}
+	}
+
+	#[test]
+	fn random_string_distribution_test() {
+		let alphabet = dedup_alphabet(
+			format!("{}{}{}{}", UPPER, LOWER, DIGITS, SYMBOLS)
+				.chars()
+				.collect::<Vec<char>>()
+				.iter(),
+		);
+		assert_eq!(alphabet.len(), 88);
+
+		let mut rng = StdRng::seed_from_u64(3);
+		let samples = random_string(200_000, &mut rng);
+		assert!(samples.chars().all(|char| alphabet.contains(&char)));
+		// 87 degrees of freedom, p = 0.0001
+		assert!(chi_square(&samples, &alphabet) < 145.0);
+	}
+
+	#[test]
+	fn profile_distribution_test() {
+		let mut rng = StdRng::seed_from_u64(42);
+		let profile = profile(200_000);
+		let alphabet = dedup_alphabet(get_classes(&profile, "").iter().flatten());
+
+		let samples = generate_from_profile(&profile, &mut rng);
+		assert_eq!(samples.chars().count(), 200_000);
+		assert!(chi_square(&samples, &alphabet) < 145.0);
+
+		// each class is sampled by its size, not by a fixed weight
+		let digits = samples.chars().filter(|char| char.is_ascii_digit()).count();
+		let expected = 200_000.0 * 10.0 / 88.0;
+		assert!((digits as f64 - expected).abs() < expected * 0.05);
+	}
+
+	#[test]
+	fn profile_classes_test() {
+		let mut rng = StdRng::seed_from_u64(7);
+
+		let pin = GeneratorProfile {
+			upper: false,
+			lower: false,
+			symbols: false,
+			..profile(6)
+		};
+		let password = generate_from_profile(&pin, &mut rng);
+		assert_eq!(password.len(), 6);
+		assert!(password.chars().all(|char| char.is_ascii_digit()));
+
+		let strict = GeneratorProfile {
+			exclude_look_alikes: true,
+			at_least_one_of_each: true,
+			..profile(4)
+		};
+		for _ in 0..1_000 {
+			let password = generate_from_profile(&strict, &mut rng);
+			assert!(password.chars().any(|char| char.is_ascii_uppercase()));
+			assert!(password.chars().any(|char| char.is_ascii_lowercase()));
+			assert!(password.chars().any(|char| char.is_ascii_digit()));
+			assert!(password.chars().any(|char| SYMBOLS.contains(char)));
+			assert!(!password.chars().any(|char| LOOK_ALIKES.contains(char)));
+		}
+
+		let custom = GeneratorProfile {
+			upper: false,
+			lower: false,
+			digits: false,
+			custom_symbols: String::from("~ ~"),
+			..profile(3)
+		};
+		assert_eq!(generate_from_profile(&custom, &mut rng), "~~~");
+	}
+
+	#[test]
+	fn at_least_one_of_each_is_uniform_test() {
+		let mut rng = StdRng::seed_from_u64(1);
+		let profile = GeneratorProfile {
+			upper: false,
+			lower: false,
+			custom_symbols: String::from("!@"),
+			at_least_one_of_each: true,
+			..profile(2)
+		};
+
+		// the 40 valid passwords should show up equally often
+		let mut counts = std::collections::HashMap::new();
+		for _ in 0..40_000 {
+			*counts.entry(generate_from_profile(&profile, &mut rng)).or_insert(0) +=
+				1;
+		}
+		assert_eq!(counts.len(), 40);
+		let chi: f64 = counts
+			.values()
+			.map(|count| (*count as f64 - 1_000.0).powi(2) / 1_000.0)
+			.sum();
+		// 39 degrees of freedom, p = 0.0001
+		assert!(chi < 80.0);
+	}
+
+	#[test]
+	fn wordlist_test() {
+		let wordlist = get_wordlist();
+		assert_eq!(wordlist.len(), 2048);
+
+		let mut unique = wordlist.to_vec();
+		unique.sort_unstable();
+		unique.dedup();
+		assert_eq!(unique.len(), wordlist.len());
+		assert!(wordlist
+			.iter()
+			.all(|word| word.chars().all(|char| char.is_ascii_lowercase())));
+	}
+
+	#[test]
+	fn passphrase_test() {
+		let mut rng = StdRng::seed_from_u64(11);
+		let wordlist = get_wordlist();
+
+		let plain = PassphraseOptions {
+			word_count: 5,
+			separator: String::from(" "),
+			capitalization: Capitalization::Lowercase,
+			add_digit: false,
+			add_symbol: false,
+		};
+		let passphrase = random_passphrase(&plain, &mut rng);
+		let words = passphrase.split(' ').collect::<Vec<&str>>();
+		assert_eq!(words.len(), 5);
+		assert!(words.iter().all(|word| wordlist.contains(word)));
+		assert!((get_passphrase_entropy(&plain) - 55.0).abs() < 1e-9);
+
+		let decorated = PassphraseOptions {
+			word_count: 4,
+			separator: String::from(" "),
+			capitalization: Capitalization::FirstLetter,
+			add_digit: true,
+			add_symbol: true,
+		};
+		for _ in 0..200 {
+			let passphrase = random_passphrase(&decorated, &mut rng);
+			let words = passphrase.split(' ').collect::<Vec<&str>>();
+			assert_eq!(words.len(), 4);
+			assert!(words
+				.iter()
+				.all(|word| word.starts_with(|char: char| char.is_ascii_uppercase())));
+			assert_eq!(
+				passphrase.chars().filter(|char| char.is_ascii_digit()).count(),
+				1
+			);
+			assert_eq!(
+				passphrase.chars().filter(|char| SYMBOLS.contains(*char)).count(),
+				1
+			);
+		}
+		let expected = 44.0 + 40_f64.log2() + (26.0 * 4.0_f64).log2();
+		assert!((get_passphrase_entropy(&decorated) - expected).abs() < 1e-9);
+	}
+
+	#[test]
+	fn passphrase_distribution_test() {
+		let mut rng = StdRng::seed_from_u64(5);
+		let wordlist = get_wordlist();
+		let options = PassphraseOptions {
+			word_count: 1,
+			separator: String::from(""),
+			capitalization: Capitalization::Lowercase,
+			add_digit: false,
+			add_symbol: false,
+		};
+
+		let mut counts = std::collections::HashMap::new();
+		for _ in 0..204_800 {
+			*counts.entry(random_passphrase(&options, &mut rng)).or_insert(0) += 1;
+		}
+		assert_eq!(counts.len(), wordlist.len());
+		let chi: f64 = counts
+			.values()
+			.map(|count| (*count as f64 - 100.0).powi(2) / 100.0)
+			.sum();
+		// 2047 degrees of freedom, p = 0.0001
+		assert!(chi < 2_350.0);
+	}
+
+	#[test]
+	fn rule_test() {
+		let mut rng = StdRng::seed_from_u64(9);
+		let rule = GeneratorRule {
+			min_length: 8,
+			max_length: 16,
+			require_upper: true,
+			require_lower: true,
+			require_digits: true,
+			require_symbols: false,
+			forbidden: String::from("<>&'\""),
+			no_repeats: true,
+		};
+		assert!(is_compliant("Abcdefg1", &rule));
+		assert!(!is_compliant("Abcdef1", &rule));
+		assert!(!is_compliant("Abcdefgg1", &rule));
+		assert!(!is_compliant("Abcdefg1<", &rule));
+		assert!(!is_compliant("abcdefg12", &rule));
+		assert!(!is_compliant("Abcdefgh12345678x", &rule));
+
+		// a passphrase profile falls back to characters within the length range
+		let passphrase = GeneratorProfile {
+			mode: GeneratorMode::Passphrase,
+			..profile(22)
+		};
+		for profile in [profile(22), profile(4), passphrase] {
+			for _ in 0..200 {
+				let password = generate_with_rule(&profile, &rule, &mut rng).unwrap();
+				assert!(is_compliant(&password, &rule), "{}", password);
+			}
+		}
+
+		let impossible = GeneratorRule {
+			require_digits: true,
+			forbidden: String::from(DIGITS),
+			..GeneratorRule::default()
+		};
+		assert_eq!(generate_with_rule(&profile(10), &impossible, &mut rng), None);
+
+		let no_repeats = GeneratorRule {
+			no_repeats: true,
+			..GeneratorRule::default()
+		};
+		let pin = GeneratorProfile {
+			upper: false,
+			lower: false,
+			symbols: false,
+			..profile(6)
+		};
+		let expected = 10_f64.log2() + 5.0 * 9_f64.log2();
+		assert!((get_rule_entropy(&pin, &no_repeats) - expected).abs() < 1e-9);
+	}
+
+	#[test]
+	fn domain_test() {
+		assert!(matches_domain("https://example.com/login", "example.com"));
+		assert!(matches_domain("https://www.example.com", "example.com"));
+		assert!(matches_domain("accounts.example.com", "https://example.com"));
+		assert!(matches_domain("https://user@example.com:8080/a?b", "Example.com"));
+		assert!(!matches_domain("https://notexample.com", "example.com"));
+		assert!(!matches_domain("https://example.com.evil.io", "example.com"));
+		assert!(!matches_domain("https://example.com", ""));
+	}
+
+	#[test]
+	fn entropy_test() {
+		let all = profile(22);
+		assert!((get_profile_entropy(&all) - 22.0 * 88_f64.log2()).abs() < 1e-9);
+
+		let pin = GeneratorProfile {
+			upper: false,
+			lower: false,
+			symbols: false,
+			..profile(6)
+		};
+		assert!((get_profile_entropy(&pin) - 6.0 * 10_f64.log2()).abs() < 1e-9);
+
+		// 12^3 passwords minus the 10^3 without symbols and 2^3 without digits
+		let mixed = GeneratorProfile {
+			upper: false,
+			lower: false,
+			custom_symbols: String::from("!@"),
+			at_least_one_of_each: true,
+			..profile(3)
+		};
+		assert!((get_profile_entropy(&mixed) - 720_f64.log2()).abs() < 1e-9);
 
-	get_random_string(length)
+		let empty = GeneratorProfile {
+			upper: false,
+			lower: false,
+			digits: false,
+			symbols: false,
+			..profile(10)
+		};
+		assert_eq!(get_profile_entropy(&empty), 0.0);
+	}
 }
@@ -0,0 +1,513 @@
+use chrono::{Datelike, Local};
+use std::{collections::HashMap, sync::OnceLock};
+
+use crate::password_gen::get_wordlist;
+
+const COMMON_PASSWORDS: &str =
+	include_str!("../assets/wordlists/common_passwords.txt");
+
+// an offline attack against a slow hash like argon2
+const GUESSES_PER_SECOND: f64 = 10_000.0;
+const BRUTEFORCE_CARDINALITY: f64 = 10.0;
+const MIN_MATCH_GUESSES: f64 = 50.0;
+const MIN_MATCH_LENGTH: usize = 3;
+const MAX_WORD_LENGTH: usize = 24;
+const MIN_YEAR_SPACE: f64 = 20.0;
+const KEYBOARD_STARTING_KEYS: f64 = 94.0;
+const KEYBOARD_AVERAGE_DEGREE: f64 = 4.6;
+
+const KEYBOARD_ROWS: [&str; 4] = [
+	"1234567890-=",
+	"qwertyuiop[]\\",
+	"asdfghjkl;'",
+	"zxcvbnm,./",
+];
+const KEYBOARD_SHIFTED_ROWS: [&str; 4] = [
+	"!@#$%^&*()_+",
+	"QWERTYUIOP{}|",
+	"ASDFGHJKL:\"",
+	"ZXCVBNM<>?",
+];
+const L33T: [(char, char); 14] = [
+	('4', 'a'),
+	('@', 'a'),
+	('8', 'b'),
+	('(', 'c'),
+	('3', 'e'),
+	('6', 'g'),
+	('1', 'i'),
+	('!', 'i'),
+	('0', 'o'),
+	('$', 's'),
+	('5', 's'),
+	('7', 't'),
+	('+', 't'),
+	('2', 'z'),
+];
+
+pub const SCORE_NAMES: [&str; 5] =
+	["Very weak", "Weak", "Fair", "Strong", "Very strong"];
+
+// scores from a hand edited config could be out of range
+pub fn get_score_name(score: u8) -> &'static str {
+	SCORE_NAMES[(score as usize).min(SCORE_NAMES.len() - 1)]
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Strength {
+	// 0 (very weak) to 4 (very strong)
+	pub score: u8,
+	pub guesses_log10: f64,
+}
+
+impl Strength {
+	pub fn get_label(&self) -> &'static str {
+		get_score_name(self.score)
+	}
+
+	pub fn get_crack_seconds(&self) -> f64 {
+		10_f64.powf(self.guesses_log10) / GUESSES_PER_SECOND
+	}
+
+	pub fn get_crack_time(&self) -> String {
+		let seconds = self.get_crack_seconds();
+		let units = [
+			(60.0 * 60.0 * 24.0 * 365.0, "year"),
+			(60.0 * 60.0 * 24.0 * 31.0, "month"),
+			(60.0 * 60.0 * 24.0, "day"),
+			(60.0 * 60.0, "hour"),
+			(60.0, "minute"),
+			(1.0, "second"),
+		];
+
+		if seconds < 1.0 {
+			String::from("less than a second")
+		} else if seconds >= units[0].0 * 100.0 {
+			String::from("centuries")
+		} else {
+			let (size, unit) =
+				units.iter().find(|(size, _)| seconds >= *size).unwrap_or(&units[5]);
+			let count = (seconds / size).floor() as u64;
+			format!("{} {}{}", count, unit, if count == 1 { "" } else { "s" })
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Match {
+	start: usize,
+	end: usize,
+	guesses: f64,
+}
+
+// PRIVATE: common passwords ranked by how often they are used followed by
+// english words which all share the rank of the size of the wordlist
+fn get_dictionary() -> &'static HashMap<String, usize> {
+	static DICTIONARY: OnceLock<HashMap<String, usize>> = OnceLock::new();
+	DICTIONARY.get_or_init(|| {
+		let mut dictionary = HashMap::new();
+		COMMON_PASSWORDS
+			.lines()
+			.map(|word| word.trim())
+			.filter(|word| !word.is_empty())
+			.enumerate()
+			.for_each(|(idx, word)| {
+				dictionary.entry(word.to_lowercase()).or_insert(idx + 1);
+			});
+		let words = get_wordlist();
+		words.iter().for_each(|word| {
+			dictionary.entry(word.to_lowercase()).or_insert(words.len());
+		});
+		dictionary
+	})
+}
+
+// PRIVATE: the binomial coefficient as a float so it can't overflow
+fn binomial(n: usize, k: usize) -> f64 {
+	if k > n {
+		return 0.0;
+	}
+	(0..k).fold(1.0, |result, i| result * (n - i) as f64 / (i + 1) as f64)
+}
+
+// PRIVATE: how many ways a word could have been capitalized
+fn uppercase_variations(word: &[char]) -> f64 {
+	let upper = word.iter().filter(|c| c.is_uppercase()).count();
+	let lower = word.iter().filter(|c| c.is_lowercase()).count();
+
+	if upper == 0 {
+		return 1.0;
+	}
+
+	let first_only = upper == 1 && word[0].is_uppercase();
+	let last_only = upper == 1 && word[word.len() - 1].is_uppercase();
+	if first_only || last_only || lower == 0 {
+		return 2.0;
+	}
+
+	(1..=upper.min(lower)).map(|i| binomial(upper + lower, i)).sum()
+}
+
+// PRIVATE: a word with common l33t substitutions turned back into letters
+fn unleet(word: &[char]) -> (String, usize) {
+	let mut substitutions = 0;
+	let plain = word
+		.iter()
+		.map(|c| match L33T.iter().find(|(leet, _)| leet == c) {
+			Some((_, letter)) => {
+				substitutions += 1;
+				*letter
+			},
+			None => *c,
+		})
+		.collect::<String>();
+	(plain, substitutions)
+}
+
+fn dictionary_matches(chars: &[char]) -> Vec<Match> {
+	let dictionary = get_dictionary();
+	let mut matches = Vec::new();
+
+	for start in 0..chars.len() {
+		let max_end = chars.len().min(start + MAX_WORD_LENGTH);
+		for end in (start + MIN_MATCH_LENGTH)..=max_end {
+			let word = &chars[start..end];
+			let lower =
+				word.iter().flat_map(|c| c.to_lowercase()).collect::<String>();
+			let reversed = lower.chars().rev().collect::<String>();
+			let lower_chars = lower.chars().collect::<Vec<char>>();
+			let (plain, substitutions) = unleet(&lower_chars);
+			let variations = uppercase_variations(word);
+
+			let candidates = [
+				dictionary.get(&lower).map(|rank| *rank as f64),
+				dictionary.get(&reversed).map(|rank| *rank as f64 * 2.0),
+				if substitutions > 0 {
+					dictionary
+						.get(&plain)
+						.map(|rank| *rank as f64 * 2_f64.powi(substitutions as i32))
+				} else {
+					None
+				},
+			];
+
+			if let Some(guesses) =
+				candidates.iter().flatten().copied().reduce(f64::min)
+			{
+				matches.push(Match {
+					start,
+					end,
+					guesses: guesses * variations,
+				});
+			}
+		}
+	}
+
+	matches
+}
+
+// PRIVATE: the row, column and shift state of a character on a qwerty keyboard
+fn key_position(c: char) -> Option<(i32, i32, bool)> {
+	for (row, (keys, shifted)) in
+		KEYBOARD_ROWS.iter().zip(KEYBOARD_SHIFTED_ROWS.iter()).enumerate()
+	{
+		if let Some(col) = keys.chars().position(|key| key == c) {
+			return Some((row as i32, col as i32, false));
+		}
+		if let Some(col) = shifted.chars().position(|key| key == c) {
+			return Some((row as i32, col as i32, true));
+		}
+	}
+	None
+}
+
+// PRIVATE: the direction from one key to an adjacent one, rows are staggered
+fn key_direction(from: char, to: char) -> Option<(i32, i32)> {
+	let (from_row, from_col, _) = key_position(from)?;
+	let (to_row, to_col, _) = key_position(to)?;
+	let direction = (to_row - from_row, to_col - from_col);
+
+	match direction {
+		(0, -1) | (0, 1) | (1, -1) | (1, 0) | (-1, 0) | (-1, 1) => Some(direction),
+		_ => None,
+	}
+}
+
+fn spatial_guesses(walk: &[char], turns: usize) -> f64 {
+	let length = walk.len();
+	let mut guesses = 0.0;
+	for i in 2..=length {
+		for j in 1..=turns.min(i - 1) {
+			guesses += binomial(i - 1, j - 1)
+				* KEYBOARD_STARTING_KEYS
+				* KEYBOARD_AVERAGE_DEGREE.powi(j as i32);
+		}
+	}
+
+	let shifted = walk
+		.iter()
+		.filter(|c| matches!(key_position(**c), Some((_, _, true))))
+		.count();
+	if shifted == length {
+		guesses *= 2.0;
+	} else if shifted > 0 {
+		let unshifted = length - shifted;
+		guesses *=
+			(1..=shifted.min(unshifted)).map(|i| binomial(length, i)).sum::<f64>();
+	}
+
+	guesses
+}
+
+fn spatial_matches(chars: &[char]) -> Vec<Match> {
+	let mut matches = Vec::new();
+	let mut start = 0;
+
+	while start < chars.len() {
+		let mut end = start + 1;
+		let mut turns = 0;
+		let mut last_direction = None;
+
+		while end < chars.len() {
+			match key_direction(chars[end - 1], chars[end]) {
+				Some(direction) => {
+					if last_direction != Some(direction) {
+						turns += 1;
+						last_direction = Some(direction);
+					}
+					end += 1;
+				},
+				None => break,
+			}
+		}
+
+		if end - start >= MIN_MATCH_LENGTH {
+			matches.push(Match {
+				start,
+				end,
+				guesses: spatial_guesses(&chars[start..end], turns),
+			});
+		}
+		start = end;
+	}
+
+	matches
+}
+
+fn sequence_matches(chars: &[char]) -> Vec<Match> {
+	let lower =
+		chars.iter().map(|c| c.to_ascii_lowercase() as i64).collect::<Vec<i64>>();
+	let mut matches = Vec::new();
+	let mut start = 0;
+
+	while start + 1 < lower.len() {
+		let delta = lower[start + 1] - lower[start];
+		let mut end = start + 1;
+		while end < lower.len()
+			&& (delta == 1 || delta == -1)
+			&& lower[end] - lower[end - 1] == delta
+			&& chars[end].is_ascii_alphanumeric()
+			&& chars[end - 1].is_ascii_alphanumeric()
+		{
+			end += 1;
+		}
+
+		if end - start >= MIN_MATCH_LENGTH {
+			let first = chars[start];
+			let base = if "aAzZ019".contains(first) {
+				4.0
+			} else if first.is_ascii_digit() {
+				10.0
+			} else {
+				26.0
+			};
+			let direction = if delta < 0 { 2.0 } else { 1.0 };
+
+			matches.push(Match {
+				start,
+				end,
+				guesses: base * direction * (end - start) as f64,
+			});
+			start = end;
+		} else {
+			start += 1;
+		}
+	}
+
+	matches
+}
+
+fn repeat_matches(chars: &[char]) -> Vec<Match> {
+	let mut matches = Vec::new();
+
+	for start in 0..chars.len() {
+		for block in 1..=(chars.len() - start) / 2 {
+			let mut count = 1;
+			while start + (count + 1) * block <= chars.len()
+				&& chars[start..start + block]
+					== chars[start + count * block..start + (count + 1) * block]
+			{
+				count += 1;
+			}
+
+			if count > 1 && count * block >= MIN_MATCH_LENGTH {
+				let block_guesses =
+					10_f64.powf(guesses_log10(&chars[start..start + block]));
+				matches.push(Match {
+					start,
+					end: start + count * block,
+					guesses: block_guesses * count as f64,
+				});
+				break;
+			}
+		}
+	}
+
+	matches
+}
+
+fn year_matches(chars: &[char]) -> Vec<Match> {
+	let current_year = Local::now().year() as f64;
+
+	chars
+		.windows(4)
+		.enumerate()
+		.filter_map(|(start, window)| {
+			let year = window.iter().collect::<String>().parse::<u16>().ok()?;
+			if (1900..=2099).contains(&year)
+				&& window.iter().all(char::is_ascii_digit)
+			{
+				Some(Match {
+					start,
+					end: start + 4,
+					guesses: (year as f64 - current_year).abs().max(MIN_YEAR_SPACE),
+				})
+			} else {
+				None
+			}
+		})
+		.collect()
+}
+
+// PRIVATE: the fewest guesses needed for a sequence of patterns and brute force
+// that covers the whole password
+fn guesses_log10(chars: &[char]) -> f64 {
+	let mut matches = dictionary_matches(chars);
+	matches.extend(spatial_matches(chars));
+	matches.extend(sequence_matches(chars));
+	matches.extend(year_matches(chars));
+	if chars.len() > 1 {
+		matches.extend(repeat_matches(chars));
+	}
+
+	let mut best = vec![0.0; chars.len() + 1];
+	for end in 1..=chars.len() {
+		best[end] = best[end - 1] + BRUTEFORCE_CARDINALITY.log10();
+		for item in matches.iter().filter(|item| item.end == end) {
+			let guesses =
+				best[item.start] + item.guesses.max(MIN_MATCH_GUESSES).log10();
+			if guesses < best[end] {
+				best[end] = guesses;
+			}
+		}
+	}
+
+	best[chars.len()]
+}
+
+pub fn estimate(password: &str) -> Strength {
+	let chars = password.chars().collect::<Vec<char>>();
+	let guesses_log10 = guesses_log10(&chars);
+
+	let score = match guesses_log10 {
+		guesses if guesses < 3.0 => 0,
+		guesses if guesses < 6.0 => 1,
+		guesses if guesses < 8.0 => 2,
+		guesses if guesses < 10.0 => 3,
+		_ => 4,
+	};
+
+	Strength {
+		score,
+		guesses_log10,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rand::{rngs::StdRng, SeedableRng};
+
+	use crate::{
+		config::{GeneratorProfile, PassphraseOptions},
+		password_gen::{generate_from_profile, get_random_passphrase},
+	};
+
+	#[test]
+	fn estimate_test() {
+		assert_eq!(estimate("").score, 0);
+		assert_eq!(estimate("password").score, 0);
+		assert_eq!(estimate("Password").score, 0);
+		assert_eq!(estimate("P@ssw0rd").score, 0);
+		assert_eq!(estimate("drowssap").score, 0);
+		assert_eq!(estimate("123456").score, 0);
+		assert_eq!(estimate("qwertyuiop").score, 0);
+		assert!(estimate("1qaz2wsx3edc").score <= 1);
+		assert_eq!(estimate("aaaaaaaaaaaaaaaa").score, 0);
+		assert_eq!(estimate("abcdefghijklmnop").score, 0);
+		assert!(estimate("abcabcabcabc").score <= 1);
+		assert!(estimate("1987").score <= 1);
+		assert!(estimate("zxcvbnm1987").score <= 2);
+
+		assert!(estimate("Tr0ub4dor&3").score >= 2);
+		assert!(estimate("xK9#mQ2$vL7@pR4!").score == 4);
+	}
+
+	#[test]
+	fn pattern_test() {
+		let chars = |text: &str| text.chars().collect::<Vec<char>>();
+
+		assert_eq!(spatial_matches(&chars("qwerty")).len(), 1);
+		assert_eq!(spatial_matches(&chars("qaz")).len(), 1);
+		assert!(spatial_matches(&chars("qpz")).is_empty());
+		assert_eq!(sequence_matches(&chars("xx4567xx"))[0].start, 2);
+		assert_eq!(sequence_matches(&chars("xx4567xx"))[0].end, 6);
+		assert_eq!(repeat_matches(&chars("abab"))[0].end, 4);
+		assert_eq!(year_matches(&chars("me1999")).len(), 1);
+		assert_eq!(unleet(&chars("p4$$w0rd")), (String::from("password"), 4));
+		assert_eq!(uppercase_variations(&chars("word")), 1.0);
+		assert_eq!(uppercase_variations(&chars("Word")), 2.0);
+		assert_eq!(uppercase_variations(&chars("WORD")), 2.0);
+		assert_eq!(uppercase_variations(&chars("wOrD")), 10.0);
+	}
+
+	#[test]
+	fn generated_test() {
+		let mut rng = StdRng::seed_from_u64(36);
+
+		for _ in 0..20 {
+			let password =
+				generate_from_profile(&GeneratorProfile::default(), &mut rng);
+			assert_eq!(estimate(&password).score, 4, "{}", password);
+
+			let passphrase = get_random_passphrase(&PassphraseOptions::default());
+			assert_eq!(estimate(&passphrase).score, 4, "{}", passphrase);
+		}
+	}
+
+	#[test]
+	fn crack_time_test() {
+		let strength = |guesses_log10: f64| Strength {
+			score: 0,
+			guesses_log10,
+		};
+
+		assert_eq!(strength(2.0).get_crack_time(), "less than a second");
+		assert_eq!(strength(4.0).get_crack_time(), "1 second");
+		assert_eq!(strength(6.0).get_crack_time(), "1 minute");
+		assert_eq!(strength(8.0).get_crack_time(), "2 hours");
+		assert_eq!(strength(9.0).get_crack_time(), "1 day");
+		assert_eq!(strength(12.0).get_crack_time(), "3 years");
+		assert_eq!(strength(20.0).get_crack_time(), "centuries");
+	}
+}
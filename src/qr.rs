@@ -0,0 +1,267 @@
+use qrcode::{Color, QrCode};
+
+use crate::db::{Db, DbFields};
+
+// the empty border scanners need around the code, in modules
+const QUIET_ZONE: usize = 4;
+const OTP_PREFIX: &str = "otpauth://";
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum QrError {
+	#[error("The value is too long to fit into a QR code")]
+	TooLong,
+}
+
+// PRIVATE: one path for all dark modules with each run of a row drawn as one
+// rectangle keeps the svg small
+fn modules_to_svg(width: usize, dark: &[bool]) -> String {
+	let size = width + QUIET_ZONE * 2;
+	let mut path = String::new();
+
+	for (y, row) in dark.chunks(width).enumerate() {
+		let mut x = 0;
+		while x < row.len() {
+			if !row[x] {
+				x += 1;
+				continue;
+			}
+			let start = x;
+			while x < row.len() && row[x] {
+				x += 1;
+			}
+			path.push_str(&format!(
+				"M{},{}h{}v1h-{}z",
+				start + QUIET_ZONE,
+				y + QUIET_ZONE,
+				x - start,
+				x - start
+			));
+		}
+	}
+
+	format!(
+		"<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {size} {size}\" shape-rendering=\"crispEdges\"><rect width=\"{size}\" height=\"{size}\" fill=\"#fff\"/><path fill=\"#000\" d=\"{path}\"/></svg>"
+	)
+}
+
+// render a value as a qr code svg that scales to any size
+pub fn to_svg(value: &str) -> Result<String, QrError> {
+	let code = QrCode::new(value.as_bytes()).map_err(|_| QrError::TooLong)?;
+	let dark = code
+		.to_colors()
+		.into_iter()
+		.map(|color| color == Color::Dark)
+		.collect::<Vec<bool>>();
+	Ok(modules_to_svg(code.width(), &dark))
+}
+
+// PRIVATE: the wifi payload uses these characters as separators
+fn escape_wifi(value: &str) -> String {
+	let mut escaped = String::with_capacity(value.len());
+	for c in value.chars() {
+		if matches!(c, '\\' | ';' | ',' | ':' | '"') {
+			escaped.push('\\');
+		}
+		escaped.push(c);
+	}
+	escaped
+}
+
+// the payload phones read to join a wifi network, without a password the
+// network is open
+pub fn wifi_payload(
+	ssid: &str,
+	password: &str,
+	security: &str,
+	hidden: bool,
+) -> String {
+	let security = match security.trim().to_uppercase().as_str() {
+		_ if password.is_empty() => String::from("nopass"),
+		"" => String::from("WPA"),
+		security if security.starts_with("WPA") => String::from("WPA"),
+		security => String::from(security),
+	};
+
+	let mut payload = format!("WIFI:T:{};S:{};", security, escape_wifi(ssid));
+	if !password.is_empty() {
+		payload.push_str(&format!("P:{};", escape_wifi(password)));
+	}
+	if hidden {
+		payload.push_str("H:true;");
+	}
+	payload.push(';');
+	payload
+}
+
+// the otpauth uri of a totp field, a bare secret gets wrapped into one so
+// authenticator apps can scan it
+pub fn totp_uri(label: &str, title: &str, value: &str) -> Option<String> {
+	let value = value.trim();
+	if value.starts_with(OTP_PREFIX) {
+		return Some(String::from(value));
+	}
+
+	let is_totp = title
+		.to_lowercase()
+		.split(|c: char| !c.is_alphanumeric())
+		.any(|word| matches!(word, "otp" | "totp" | "2fa" | "mfa"));
+	// seeds are often shown in groups of four so the spaces are dropped
+	let secret = value
+		.chars()
+		.filter(|c| !c.is_whitespace())
+		.collect::<String>()
+		.to_uppercase();
+	let is_base32 = secret
+		.chars()
+		.all(|c| c.is_ascii_uppercase() || ('2'..='7').contains(&c) || c == '=');
+	if !is_totp || secret.is_empty() || !is_base32 {
+		return None;
+	}
+
+	Some(format!(
+		"otpauth://totp/{}?secret={}",
+		url_escape::encode_component(label),
+		secret
+	))
+}
+
+// what the qr code of a field holds, the password of a wifi entry becomes the
+// payload to join the network with and a totp seed its otpauth uri
+pub fn field_payload(db: &Db, id: &usize, field: &DbFields) -> String {
+	let entry_title = db.get_by_id(id).title;
+	let name = db.get_name_of_field(id, field);
+	let value = db.get_last_by_field(id, field);
+
+	let fields = db
+		.get_visible_fields(id)
+		.iter()
+		.map(|field| {
+			(
+				db.get_name_of_field(id, field).trim().to_lowercase(),
+				db.get_last_by_field(id, field),
+			)
+		})
+		.collect::<Vec<(String, String)>>();
+	let find = |names: &[&str]| {
+		fields
+			.iter()
+			.find(|(name, _)| names.contains(&name.as_str()))
+			.map(|(_, value)| value.clone())
+	};
+
+	let is_wifi = db
+		.get_tags(id)
+		.iter()
+		.any(|tag| matches!(tag.to_lowercase().as_str(), "wifi" | "wi-fi"))
+		|| find(&["ssid"]).is_some();
+	let is_password = matches!(
+		name.trim().to_lowercase().as_str(),
+		"password" | "passphrase" | "key" | "wifi password" | "wi-fi password"
+	);
+	if is_wifi && is_password {
+		return wifi_payload(
+			&find(&["ssid", "network", "network name"]).unwrap_or(entry_title),
+			&value,
+			&find(&["security", "encryption"]).unwrap_or_default(),
+			find(&["hidden"]).is_some_and(|hidden| {
+				matches!(hidden.trim().to_lowercase().as_str(), "true" | "yes" | "1")
+			}),
+		);
+	}
+
+	totp_uri(&entry_title, &name, &value).unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::db::{DynField, DynFieldKind, NewDbEntry};
+
+	#[test]
+	fn modules_to_svg_test() {
+		let svg = modules_to_svg(
+			3,
+			&[true, true, false, false, false, false, true, false, true],
+		);
+		assert!(svg.contains("viewBox=\"0 0 11 11\""));
+		assert!(svg.contains("d=\"M4,4h2v1h-2zM4,6h1v1h-1zM6,6h1v1h-1z\""));
+	}
+
+	#[test]
+	fn wifi_payload_test() {
+		assert_eq!(
+			wifi_payload("Home", "pass;word", "wpa2", false),
+			"WIFI:T:WPA;S:Home;P:pass\\;word;;"
+		);
+		assert_eq!(
+			wifi_payload("Cafe: \"free\"", "", "WPA", true),
+			"WIFI:T:nopass;S:Cafe\\: \\\"free\\\";H:true;;"
+		);
+		assert_eq!(
+			wifi_payload("Old", "key", "wep", false),
+			"WIFI:T:WEP;S:Old;P:key;;"
+		);
+	}
+
+	#[test]
+	fn totp_uri_test() {
+		assert_eq!(
+			totp_uri("Mail", "Secret", "otpauth://totp/Mail?secret=ABC"),
+			Some(String::from("otpauth://totp/Mail?secret=ABC"))
+		);
+		assert_eq!(
+			totp_uri("Mail & co", "TOTP seed", "abcd 2345"),
+			Some(String::from("otpauth://totp/Mail%20%26%20co?secret=ABCD2345"))
+		);
+		assert_eq!(totp_uri("Mail", "MFA", "recovery codes: 1, 8"), None);
+		assert_eq!(totp_uri("Mail", "Footprint", "abc"), None);
+		assert_eq!(totp_uri("Mail", "OTP", ""), None);
+	}
+
+	#[test]
+	fn field_payload_test() {
+		let field = |title: &str, value: &str| {
+			DynField::new(
+				DynFieldKind::TextLine,
+				String::from(title),
+				vec![(10, String::from(value))],
+			)
+		};
+		let db = Db::from_new_entries(vec![
+			NewDbEntry {
+				title: String::from("Home"),
+				fields: vec![
+					field("SSID", "home-net"),
+					field("Password", "secret"),
+					field("Hidden", "yes"),
+				],
+				tags: Vec::new(),
+			},
+			NewDbEntry {
+				title: String::from("Office"),
+				fields: vec![field("Password", "pw"), field("Security", "WEP")],
+				tags: vec![String::from("WiFi")],
+			},
+			NewDbEntry {
+				title: String::from("Mail"),
+				fields: vec![field("Password", "pw"), field("TOTP", "abcd")],
+				tags: Vec::new(),
+			},
+		]);
+
+		assert_eq!(
+			field_payload(&db, &1, &DbFields::Fields(2)),
+			"WIFI:T:WPA;S:home-net;P:secret;H:true;;"
+		);
+		assert_eq!(field_payload(&db, &1, &DbFields::Fields(1)), "home-net");
+		assert_eq!(
+			field_payload(&db, &2, &DbFields::Fields(1)),
+			"WIFI:T:WEP;S:Office;P:pw;;"
+		);
+		assert_eq!(field_payload(&db, &3, &DbFields::Fields(1)), "pw");
+		assert_eq!(
+			field_payload(&db, &3, &DbFields::Fields(2)),
+			"otpauth://totp/Mail?secret=ABCD"
+		);
+	}
+}
@@ -0,0 +1,305 @@
+use zeroize::Zeroize;
+
+const SCORE_MATCH: i64 = 16;
+const BONUS_CONSECUTIVE: i64 = 8;
+const BONUS_WORD_START: i64 = 10;
+const BONUS_FIRST_CHAR: i64 = 15;
+const PENALTY_GAP_START: i64 = 3;
+const PENALTY_GAP: i64 = 1;
+const PENALTY_TYPO: i64 = 20;
+const MAX_HAYSTACK_LEN: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+	pub score: i64,
+	// char indices of the haystack that matched the needle
+	pub positions: Vec<usize>,
+}
+
+// a needle lowercased once so it can be matched against many haystacks
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Needle {
+	chars: Vec<char>,
+}
+
+impl Needle {
+	pub fn new(input: &str) -> Self {
+		Self {
+			chars: lowercase_chars(input.trim()),
+		}
+	}
+
+	pub fn zeroize(&mut self) {
+		self.chars.zeroize();
+		self.chars.clear();
+	}
+}
+
+// a haystack lowercased once so it can be matched against many needles
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Haystack {
+	original: Vec<char>,
+	lowercase: Vec<char>,
+}
+
+impl Haystack {
+	pub fn new(input: &str) -> Self {
+		let original = input.chars().take(MAX_HAYSTACK_LEN).collect::<Vec<char>>();
+		let lowercase = original
+			.iter()
+			.map(|c| c.to_lowercase().next().unwrap_or(*c))
+			.collect::<Vec<char>>();
+
+		Self {
+			original,
+			lowercase,
+		}
+	}
+
+	pub fn zeroize(&mut self) {
+		self.original.zeroize();
+		self.lowercase.zeroize();
+		self.original.clear();
+		self.lowercase.clear();
+	}
+}
+
+fn lowercase_chars(input: &str) -> Vec<char> {
+	input.chars().map(|c| c.to_lowercase().next().unwrap_or(c)).collect()
+}
+
+fn is_word_start(haystack: &[char], original: &[char], idx: usize) -> bool {
+	if idx == 0 {
+		return true;
+	}
+
+	let prev = original[idx - 1];
+	!prev.is_alphanumeric()
+		|| (prev.is_lowercase() && original[idx].is_uppercase())
+		|| (!haystack[idx - 1].is_numeric() && haystack[idx].is_numeric())
+}
+
+fn score_positions(
+	haystack: &[char],
+	original: &[char],
+	positions: &[usize],
+) -> i64 {
+	let mut score = 0;
+
+	for (i, &pos) in positions.iter().enumerate() {
+		score += SCORE_MATCH;
+
+		if pos == 0 {
+			score += BONUS_FIRST_CHAR;
+		}
+		if is_word_start(haystack, original, pos) {
+			score += BONUS_WORD_START;
+		}
+		if i > 0 {
+			let gap = pos - positions[i - 1] - 1;
+			if gap == 0 {
+				score += BONUS_CONSECUTIVE;
+			} else {
+				score -= PENALTY_GAP_START + PENALTY_GAP * gap.min(10) as i64;
+			}
+		}
+	}
+
+	score
+}
+
+// PRIVATE: match all chars of the needle in order while allowing skipped chars in between
+fn subsequence_match(
+	needle: &[char],
+	haystack: &[char],
+	original: &[char],
+) -> Option<FuzzyMatch> {
+	let mut best: Option<FuzzyMatch> = None;
+
+	// try every occurrence of the first char as a starting point and keep the best scoring one
+	for start in (0..haystack.len()).filter(|&idx| haystack[idx] == needle[0]) {
+		let mut positions = vec![start];
+		let mut cursor = start + 1;
+
+		for &c in &needle[1..] {
+			match haystack[cursor..].iter().position(|&h| h == c) {
+				Some(offset) => {
+					positions.push(cursor + offset);
+					cursor += offset + 1;
+				},
+				None => break,
+			}
+		}
+
+		if positions.len() != needle.len() {
+			// later starting points can't match more chars
+			break;
+		}
+
+		let score = score_positions(haystack, original, &positions);
+		if best.as_ref().map_or(true, |found| score > found.score) {
+			best = Some(FuzzyMatch { score, positions });
+		}
+	}
+
+	best
+}
+
+// PRIVATE: find the substring with the least edits (incl. transpositions) to the needle
+fn typo_match(
+	needle: &[char],
+	haystack: &[char],
+	original: &[char],
+) -> Option<FuzzyMatch> {
+	let max_typos = match needle.len() {
+		0..=2 => return None,
+		3..=7 => 1,
+		_ => 2,
+	};
+
+	// cheap bail out before the dynamic programming below when too few chars exist at all
+	let present = needle.iter().filter(|c| haystack.contains(c)).count();
+	if present + max_typos < needle.len() {
+		return None;
+	}
+
+	let cols = haystack.len() + 1;
+	// distance and start column of the best alignment ending in each cell for the last three rows
+	let mut dist = [vec![0usize; cols], vec![0usize; cols], vec![0usize; cols]];
+	let mut start = [
+		(0..cols).collect::<Vec<usize>>(),
+		vec![0usize; cols],
+		vec![0usize; cols],
+	];
+
+	for i in 1..=needle.len() {
+		let (row, prev, prev2) = (i % 3, (i - 1) % 3, (i + 1) % 3);
+		dist[row][0] = i;
+		start[row][0] = 0;
+
+		for j in 1..cols {
+			let cost = usize::from(needle[i - 1] != haystack[j - 1]);
+			let mut best = (dist[prev][j - 1] + cost, start[prev][j - 1]);
+
+			if dist[prev][j] + 1 < best.0 {
+				best = (dist[prev][j] + 1, start[prev][j]);
+			}
+			if dist[row][j - 1] + 1 < best.0 {
+				best = (dist[row][j - 1] + 1, start[row][j - 1]);
+			}
+			if i > 1
+				&& j > 1
+				&& needle[i - 1] == haystack[j - 2]
+				&& needle[i - 2] == haystack[j - 1]
+				&& dist[prev2][j - 2] + 1 < best.0
+			{
+				best = (dist[prev2][j - 2] + 1, start[prev2][j - 2]);
+			}
+
+			dist[row][j] = best.0;
+			start[row][j] = best.1;
+		}
+	}
+
+	let last = needle.len() % 3;
+	let (end, distance) = (1..cols)
+		.map(|j| (j, dist[last][j]))
+		.min_by_key(|&(j, distance)| (distance, j))?;
+
+	if distance > max_typos {
+		return None;
+	}
+
+	let positions = (start[last][end]..end).collect::<Vec<usize>>();
+	let score = score_positions(haystack, original, &positions)
+		- PENALTY_TYPO * distance as i64;
+
+	Some(FuzzyMatch { score, positions })
+}
+
+// match a needle against a haystack allowing skipped chars and small typos
+pub fn fuzzy_match(needle: &str, haystack: &str) -> Option<FuzzyMatch> {
+	fuzzy_match_prepared(&Needle::new(needle), &Haystack::new(haystack))
+}
+
+// same as fuzzy_match but without lowercasing both sides on every call
+pub fn fuzzy_match_prepared(
+	needle: &Needle,
+	haystack: &Haystack,
+) -> Option<FuzzyMatch> {
+	if needle.chars.is_empty() {
+		return Some(FuzzyMatch {
+			score: 0,
+			positions: Vec::new(),
+		});
+	}
+
+	subsequence_match(&needle.chars, &haystack.lowercase, &haystack.original)
+		.or_else(|| {
+			typo_match(&needle.chars, &haystack.lowercase, &haystack.original)
+		})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn empty_needle_test() {
+		assert_eq!(
+			fuzzy_match("", "GitHub"),
+			Some(FuzzyMatch {
+				score: 0,
+				positions: Vec::new()
+			})
+		);
+	}
+
+	#[test]
+	fn exact_match_test() {
+		let found = fuzzy_match("hub", "GitHub").unwrap();
+		assert_eq!(found.positions, vec![3, 4, 5]);
+	}
+
+	#[test]
+	fn skipped_chars_test() {
+		let found = fuzzy_match("gthb", "GitHub").unwrap();
+		assert_eq!(found.positions, vec![0, 2, 3, 5]);
+	}
+
+	#[test]
+	fn typo_test() {
+		assert!(fuzzy_match("gihtub", "GitHub").is_some());
+		assert!(fuzzy_match("bamk", "My Bank").is_some());
+		assert!(fuzzy_match("xyz", "GitHub").is_none());
+	}
+
+	#[test]
+	fn ranking_test() {
+		let consecutive = fuzzy_match("bank", "Bank").unwrap();
+		let scattered = fuzzy_match("bank", "Be a nice kid").unwrap();
+		let typo = fuzzy_match("bank", "Bakn").unwrap();
+
+		assert!(consecutive.score > scattered.score);
+		assert!(consecutive.score > typo.score);
+	}
+
+	#[test]
+	fn prepared_test() {
+		let needle = Needle::new(" GitHb ");
+		let haystack = Haystack::new("My GitHub");
+
+		assert_eq!(
+			fuzzy_match_prepared(&needle, &haystack),
+			fuzzy_match("githb", "My GitHub")
+		);
+	}
+
+	#[test]
+	fn word_start_test() {
+		let word_start = fuzzy_match("gh", "Git Hub").unwrap();
+		let inside = fuzzy_match("gh", "Gotham").unwrap();
+
+		assert!(word_start.score > inside.score);
+	}
+}
@@ -0,0 +1,606 @@
+use std::{cmp::Reverse, collections::HashMap};
+use zeroize::Zeroize;
+
+use crate::{
+	db::{DbFields, DynFieldKind, MatchOrigin, SearchHit},
+	search::{
+		fuzzy::{fuzzy_match_prepared, Haystack, Needle},
+		query::{Expr, Filter, Query},
+	},
+};
+
+const RECENCY_WEIGHT: f64 = 50.0;
+const RECENCY_HALF_LIFE_DAYS: f64 = 30.0;
+
+#[derive(Debug, Clone, Default)]
+pub struct IndexedField {
+	pub id: usize,
+	pub kind: DynFieldKind,
+	pub visible: bool,
+	pub title: String,
+	// position of the title in the field titles of the index
+	pub title_slot: usize,
+	// lowercase values with their dates, oldest first like in the db
+	pub values: Vec<(u64, String)>,
+}
+
+impl IndexedField {
+	pub fn zeroize(&mut self) {
+		self.id.zeroize();
+		self.visible.zeroize();
+		self.title.zeroize();
+		self.title_slot.zeroize();
+		for value in &mut self.values {
+			value.0.zeroize();
+			value.1.zeroize();
+		}
+		self.values.clear();
+	}
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IndexedEntry {
+	pub id: usize,
+	pub title: String,
+	pub title_haystack: Haystack,
+	// lowercase tags
+	pub tags: Vec<String>,
+	pub fields: Vec<IndexedField>,
+	pub last_modified: u64,
+	pub last_activity: u64,
+}
+
+impl IndexedEntry {
+	pub fn zeroize(&mut self) {
+		self.id.zeroize();
+		self.title.zeroize();
+		self.title_haystack.zeroize();
+		for tag in &mut self.tags {
+			tag.zeroize();
+		}
+		self.tags.clear();
+		for field in &mut self.fields {
+			field.zeroize();
+		}
+		self.fields.clear();
+		self.last_modified.zeroize();
+		self.last_activity.zeroize();
+	}
+}
+
+// a lowercased copy of the unlocked vault so a keystroke in the search bar
+// doesn't have to walk and lowercase the whole db
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+	// kept in the same order as the db so the sidebar indices line up
+	entries: Vec<IndexedEntry>,
+	// field titles repeat a lot across entries so each is only stored and matched once
+	field_titles: Vec<Haystack>,
+	field_title_slots: HashMap<String, usize>,
+}
+
+struct PreparedTerm {
+	needle: Needle,
+	lowercase: String,
+	contains_score: i64,
+	// fuzzy score of the term against each field title of the index
+	title_scores: Vec<Option<i64>>,
+}
+
+struct SearchState<'a> {
+	now: u64,
+	deep: bool,
+	terms: HashMap<&'a str, PreparedTerm>,
+}
+
+// the terms are copies of what was typed into the search, which can be a secret
+impl Drop for SearchState<'_> {
+	fn drop(&mut self) {
+		for term in self.terms.values_mut() {
+			term.needle.zeroize();
+			term.lowercase.zeroize();
+		}
+	}
+}
+
+struct TermMatch {
+	is_title: bool,
+	score: i64,
+	positions: Vec<usize>,
+	origin: Option<MatchOrigin>,
+}
+
+// ranking terms are scored once per entry and reused when checking the query
+type TermMatches<'a> = [(&'a str, Option<TermMatch>)];
+
+struct RankedEntry<'a> {
+	entry: &'a IndexedEntry,
+	idx: usize,
+	is_title_match: bool,
+	score: i64,
+	title_positions: Vec<usize>,
+	origin: Option<MatchOrigin>,
+}
+
+// PRIVATE: every term of a query that gets matched against entries
+fn collect_terms<'a>(expr: &'a Expr, terms: &mut Vec<&'a str>) {
+	match expr {
+		Expr::Filter(Filter::Text(term))
+		| Expr::Filter(Filter::Title(term))
+		| Expr::Filter(Filter::Field(term))
+		| Expr::Filter(Filter::Tag(term)) => terms.push(term),
+		Expr::Not(inner) => collect_terms(inner, terms),
+		Expr::And(left, right) | Expr::Or(left, right) => {
+			collect_terms(left, terms);
+			collect_terms(right, terms);
+		},
+		Expr::All | Expr::Filter(_) => {},
+	}
+}
+
+// PRIVATE: look through older values and hidden fields for a term
+fn deep_match_field(
+	field: &IndexedField,
+	term: &PreparedTerm,
+) -> Option<MatchOrigin> {
+	if matches!(field.kind, DynFieldKind::Reference | DynFieldKind::Heading) {
+		return None;
+	}
+
+	let values = if field.visible {
+		// the last value was already looked at in the normal search
+		&field.values[..field.values.len().saturating_sub(1)]
+	} else {
+		&field.values[..]
+	};
+
+	let origin =
+		values.iter().rev().enumerate().find_map(|(n, (date, value))| {
+			value.contains(&term.lowercase).then_some(MatchOrigin {
+				field: DbFields::Fields(field.id),
+				field_title: field.title.clone(),
+				date: *date,
+				history_idx: if field.visible { n + 1 } else { n },
+				is_archived: !field.visible,
+			})
+		});
+
+	origin.or_else(|| {
+		(!field.visible && title_score(term, field).is_some()).then(|| {
+			MatchOrigin {
+				field: DbFields::Fields(field.id),
+				field_title: field.title.clone(),
+				date: field.values.last().map(|value| value.0).unwrap_or(0),
+				history_idx: 0,
+				is_archived: true,
+			}
+		})
+	})
+}
+
+fn title_score(term: &PreparedTerm, field: &IndexedField) -> Option<i64> {
+	term.title_scores.get(field.title_slot).copied().flatten()
+}
+
+// PRIVATE: score a free text term against an entry and return if it matched the title
+fn score_term(
+	entry: &IndexedEntry,
+	term: &PreparedTerm,
+	deep: bool,
+) -> Option<TermMatch> {
+	// look at entry title
+	if let Some(found) = fuzzy_match_prepared(&term.needle, &entry.title_haystack)
+	{
+		return Some(TermMatch {
+			is_title: true,
+			score: found.score,
+			positions: found.positions,
+			origin: None,
+		});
+	}
+
+	let score = entry
+		.fields
+		.iter()
+		.filter(|field| field.visible)
+		.filter_map(|field| {
+			// look at field title
+			title_score(term, field).or_else(|| {
+				// look at field value (important for notes) but we only look at the last value
+				let value = &field.values.last()?.1;
+				(field.kind != DynFieldKind::Reference
+					&& value.contains(&term.lowercase))
+				.then_some(term.contains_score)
+			})
+		})
+		.chain(
+			// look at tags
+			entry
+				.tags
+				.iter()
+				.filter(|tag| tag.contains(&term.lowercase))
+				.map(|_| term.contains_score),
+		)
+		.max();
+
+	match score {
+		Some(score) => Some(TermMatch {
+			is_title: false,
+			score,
+			positions: Vec::new(),
+			origin: None,
+		}),
+		None if deep => {
+			let origin =
+				entry.fields.iter().find_map(|field| deep_match_field(field, term))?;
+			Some(TermMatch {
+				is_title: false,
+				// matches in the past rank below everything current
+				score: term.contains_score / 2,
+				positions: Vec::new(),
+				origin: Some(origin),
+			})
+		},
+		None => None,
+	}
+}
+
+// PRIVATE: check if an entry matches a parsed search query
+fn matches_expr(
+	entry: &IndexedEntry,
+	expr: &Expr,
+	state: &SearchState,
+	known: &TermMatches,
+) -> bool {
+	let find_known = |term: &str| {
+		known.iter().find(|(known_term, _)| *known_term == term).map(|item| &item.1)
+	};
+
+	match expr {
+		Expr::All => true,
+		Expr::Not(inner) => !matches_expr(entry, inner, state, known),
+		Expr::And(left, right) => {
+			matches_expr(entry, left, state, known)
+				&& matches_expr(entry, right, state, known)
+		},
+		Expr::Or(left, right) => {
+			matches_expr(entry, left, state, known)
+				|| matches_expr(entry, right, state, known)
+		},
+		Expr::Filter(Filter::Text(term)) => match find_known(term) {
+			Some(found) => found.is_some(),
+			None => {
+				score_term(entry, &state.terms[term.as_str()], state.deep).is_some()
+			},
+		},
+		// score_term looks at the title first so a known match tells us if the title matched
+		Expr::Filter(Filter::Title(term)) => match find_known(term) {
+			Some(found) => matches!(found, Some(found) if found.is_title),
+			None => fuzzy_match_prepared(
+				&state.terms[term.as_str()].needle,
+				&entry.title_haystack,
+			)
+			.is_some(),
+		},
+		Expr::Filter(Filter::Field(term)) => {
+			let term = &state.terms[term.as_str()];
+			entry.fields.iter().any(|field| {
+				(field.visible || state.deep) && title_score(term, field).is_some()
+			})
+		},
+		Expr::Filter(Filter::Kind(kind)) => entry
+			.fields
+			.iter()
+			.any(|field| (field.visible || state.deep) && field.kind == *kind),
+		Expr::Filter(Filter::Tag(tag)) => {
+			let term = &state.terms[tag.as_str()];
+			entry.tags.contains(&term.lowercase)
+		},
+		Expr::Filter(Filter::Modified(date_filter)) => {
+			date_filter.matches(entry.last_modified, state.now)
+		},
+		Expr::Filter(Filter::Archived) => {
+			entry.fields.iter().any(|field| !field.visible)
+		},
+	}
+}
+
+impl SearchIndex {
+	// get the slot of a field title, adding it if it's new
+	pub fn field_title_slot(&mut self, title: &str) -> usize {
+		if let Some(slot) = self.field_title_slots.get(title) {
+			return *slot;
+		}
+
+		let slot = self.field_titles.len();
+		self.field_titles.push(Haystack::new(title));
+		self.field_title_slots.insert(String::from(title), slot);
+		slot
+	}
+
+	// append an entry while building the index in db order
+	pub fn push(&mut self, entry: IndexedEntry) {
+		self.entries.push(entry);
+	}
+
+	// replace the entry with the same id or append it if it's new
+	pub fn upsert(&mut self, entry: IndexedEntry) {
+		match self.entries.iter_mut().find(|item| item.id == entry.id) {
+			Some(item) => {
+				item.zeroize();
+				*item = entry;
+			},
+			None => self.entries.push(entry),
+		}
+	}
+
+	pub fn zeroize(&mut self) {
+		for entry in &mut self.entries {
+			entry.zeroize();
+		}
+		self.entries.clear();
+
+		for title in &mut self.field_titles {
+			title.zeroize();
+		}
+		self.field_titles.clear();
+
+		for (mut title, mut slot) in self.field_title_slots.drain() {
+			title.zeroize();
+			slot.zeroize();
+		}
+	}
+
+	// search through the index and return a list of hits ranked with title matches first,
+	// a deep search also looks at older values and hidden fields
+	pub fn search(&self, query: &Query, deep: bool, now: u64) -> Vec<SearchHit> {
+		if query.expr == Expr::All {
+			return self
+				.entries
+				.iter()
+				.enumerate()
+				.rev()
+				.map(|(idx, entry)| SearchHit {
+					id: entry.id,
+					title: entry.title.clone(),
+					idx,
+					score: 0,
+					title_positions: Vec::new(),
+					origin: None,
+				})
+				.collect();
+		}
+
+		let mut query_terms = Vec::new();
+		collect_terms(&query.expr, &mut query_terms);
+
+		let state = SearchState {
+			now,
+			deep,
+			terms: query_terms
+				.into_iter()
+				.map(|term| {
+					let needle = Needle::new(term);
+					let title_scores = self
+						.field_titles
+						.iter()
+						.map(|title| {
+							fuzzy_match_prepared(&needle, title).map(|found| found.score)
+						})
+						.collect();
+					let lowercase = term.to_lowercase();
+
+					(
+						term,
+						PreparedTerm {
+							needle,
+							contains_score: lowercase.chars().count() as i64,
+							lowercase,
+							title_scores,
+						},
+					)
+				})
+				.collect(),
+		};
+		let ranking_terms = query.positive_terms();
+
+		let mut ranked = self
+			.entries
+			.iter()
+			.enumerate()
+			.filter_map(|(idx, entry)| {
+				let term_matches = ranking_terms
+					.iter()
+					.map(|term| {
+						let found = score_term(entry, &state.terms[term.as_str()], deep);
+						(term.as_str(), found)
+					})
+					.collect::<Vec<(&str, Option<TermMatch>)>>();
+
+				if !matches_expr(entry, &query.expr, &state, &term_matches) {
+					return None;
+				}
+
+				let mut is_title_match = false;
+				let mut score = 0;
+				let mut title_positions = Vec::new();
+				let mut origin = None;
+
+				for (_, found) in term_matches.into_iter() {
+					if let Some(found) = found {
+						is_title_match |= found.is_title;
+						score += found.score;
+						title_positions.extend(found.positions);
+						origin = origin.or(found.origin);
+					}
+				}
+				title_positions.sort_unstable();
+				title_positions.dedup();
+
+				let age_days =
+					now.saturating_sub(entry.last_activity) as f64 / 86_400.0;
+				let recency =
+					RECENCY_WEIGHT * 0.5_f64.powf(age_days / RECENCY_HALF_LIFE_DAYS);
+
+				Some(RankedEntry {
+					entry,
+					idx,
+					is_title_match,
+					score: score + recency as i64,
+					title_positions,
+					origin,
+				})
+			})
+			.collect::<Vec<RankedEntry>>();
+
+		ranked.sort_unstable_by_key(|item| {
+			(Reverse(item.is_title_match), Reverse(item.score), Reverse(item.idx))
+		});
+
+		ranked
+			.into_iter()
+			.map(|item| SearchHit {
+				id: item.entry.id,
+				title: item.entry.title.clone(),
+				idx: item.idx,
+				score: item.score,
+				title_positions: item.title_positions,
+				origin: item.origin,
+			})
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::search::query::parse_query;
+
+	fn entry(
+		index: &mut SearchIndex,
+		id: usize,
+		title: &str,
+		fields: &[(&str, &[&str], bool)],
+	) -> IndexedEntry {
+		IndexedEntry {
+			id,
+			title: String::from(title),
+			title_haystack: Haystack::new(title),
+			tags: Vec::new(),
+			fields: fields
+				.iter()
+				.enumerate()
+				.map(|(field_id, (field_title, values, visible))| IndexedField {
+					id: field_id,
+					kind: DynFieldKind::TextLine,
+					visible: *visible,
+					title: String::from(*field_title),
+					title_slot: index.field_title_slot(field_title),
+					values: values
+						.iter()
+						.enumerate()
+						.map(|(date, value)| (date as u64, value.to_lowercase()))
+						.collect(),
+				})
+				.collect(),
+			last_modified: 0,
+			last_activity: 0,
+		}
+	}
+
+	fn search_ids(index: &SearchIndex, input: &str, deep: bool) -> Vec<usize> {
+		index
+			.search(&parse_query(input).unwrap(), deep, 0)
+			.into_iter()
+			.map(|hit| hit.id)
+			.collect()
+	}
+
+	#[test]
+	fn search_test() {
+		let mut index = SearchIndex::default();
+		let github =
+			entry(&mut index, 1, "GitHub", &[("Username", &["dom"], true)]);
+		let bank =
+			entry(&mut index, 2, "Bank", &[("Notes", &["Github 2FA"], true)]);
+		index.push(github);
+		index.push(bank);
+
+		assert_eq!(search_ids(&index, "github", false), vec![1, 2]);
+		assert_eq!(search_ids(&index, "field:username", false), vec![1]);
+		assert_eq!(search_ids(&index, "", false), vec![2, 1]);
+	}
+
+	#[test]
+	fn upsert_test() {
+		let mut index = SearchIndex::default();
+		let github = entry(&mut index, 1, "GitHub", &[]);
+		index.push(github);
+		let renamed = entry(&mut index, 1, "GitLab", &[]);
+		index.upsert(renamed);
+		let bank = entry(&mut index, 2, "Bank", &[]);
+		index.upsert(bank);
+
+		assert!(search_ids(&index, "title:github", false).is_empty());
+		assert_eq!(search_ids(&index, "title:gitlab", false), vec![1]);
+		assert_eq!(search_ids(&index, "", false), vec![2, 1]);
+	}
+
+	#[test]
+	fn deep_search_test() {
+		let mut index = SearchIndex::default();
+		let mail = entry(
+			&mut index,
+			1,
+			"Mail",
+			&[
+				("Password", &["zanzibar", "current"], true),
+				("Recovery", &["zanzibar2"], false),
+			],
+		);
+		index.push(mail);
+
+		assert!(search_ids(&index, "zanzibar", false).is_empty());
+
+		let hits = index.search(&parse_query("zanzibar").unwrap(), true, 0);
+		let origin = hits[0].origin.clone().unwrap();
+		assert_eq!(origin.field_title, "Password");
+		assert_eq!(origin.history_idx, 1);
+		assert!(!origin.is_archived);
+
+		let hits = index.search(&parse_query("recovery").unwrap(), true, 0);
+		assert!(hits[0].origin.clone().unwrap().is_archived);
+	}
+
+	#[test]
+	fn archived_test() {
+		let mut index = SearchIndex::default();
+		let mail = entry(
+			&mut index,
+			1,
+			"Mail",
+			&[("Password", &["a"], true), ("Recovery", &["b"], false)],
+		);
+		let bank = entry(&mut index, 2, "Bank", &[("Password", &["c"], true)]);
+		index.push(mail);
+		index.push(bank);
+
+		// archived fields are there whether or not the search is deep
+		assert_eq!(search_ids(&index, "is:archived", false), vec![1]);
+		assert_eq!(search_ids(&index, "archived", true), vec![1]);
+		assert_eq!(search_ids(&index, "-archived", false), vec![2]);
+		assert_eq!(search_ids(&index, "bank OR is:archived", false), vec![2, 1]);
+	}
+
+	#[test]
+	fn zeroize_test() {
+		let mut index = SearchIndex::default();
+		let github = entry(&mut index, 1, "GitHub", &[("Notes", &["a"], true)]);
+		index.push(github);
+		index.zeroize();
+
+		assert!(search_ids(&index, "", false).is_empty());
+		assert!(index.field_titles.is_empty());
+		assert!(index.field_title_slots.is_empty());
+	}
+}
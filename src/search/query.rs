@@ -0,0 +1,710 @@
+use chrono::NaiveDate;
+
+use crate::db::DynFieldKind;
+
+const SECONDS_PER_DAY: u64 = 60 * 60 * 24;
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum QueryError {
+	#[error(
+		"Unknown filter \"{0}:\", try title:, field:, kind:, tag:, is: or modified:"
+	)]
+	UnknownFilter(String),
+	#[error("Unknown state \"{0}\", try is:archived")]
+	UnknownState(String),
+	#[error("Unknown kind \"{0}\", try secret, text, multiline, multilinesecret, url, heading or reference")]
+	UnknownKind(String),
+	#[error("Invalid date \"{0}\", try modified:<90d or modified:>2024-03-01")]
+	InvalidDate(String),
+	#[error("Missing value after \"{0}:\"")]
+	EmptyValue(String),
+	#[error("Missing closing quote")]
+	UnclosedQuote,
+	#[error("Missing closing parenthesis")]
+	UnclosedParen,
+	#[error("Unexpected closing parenthesis")]
+	UnexpectedParen,
+	#[error("\"{0}\" needs a term after it")]
+	DanglingOperator(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+	Before,
+	After,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateValue {
+	// seconds before now
+	Age(u64),
+	// unix timestamp
+	Date(u64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateFilter {
+	pub comparison: Comparison,
+	pub value: DateValue,
+}
+
+impl DateFilter {
+	pub fn matches(&self, timestamp: u64, now: u64) -> bool {
+		match (self.value, self.comparison) {
+			// modified:<90d means less than 90 days old
+			(DateValue::Age(age), Comparison::Before) => {
+				timestamp > now.saturating_sub(age)
+			},
+			(DateValue::Age(age), Comparison::After) => {
+				timestamp <= now.saturating_sub(age)
+			},
+			(DateValue::Date(date), Comparison::Before) => timestamp < date,
+			(DateValue::Date(date), Comparison::After) => timestamp >= date,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+	Text(String),
+	Title(String),
+	Field(String),
+	Kind(DynFieldKind),
+	Tag(String),
+	Modified(DateFilter),
+	// entries with at least one archived field
+	Archived,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+	All,
+	Filter(Filter),
+	Not(Box<Expr>),
+	And(Box<Expr>, Box<Expr>),
+	Or(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+	pub expr: Expr,
+}
+
+impl Query {
+	// a query that searches for the input as is, used as fallback when the input doesn't parse
+	pub fn plain_text(input: &str) -> Self {
+		let input = input.trim();
+
+		Query {
+			expr: if input.is_empty() {
+				Expr::All
+			} else {
+				Expr::Filter(Filter::Text(String::from(input)))
+			},
+		}
+	}
+
+	// all text and title terms that are not negated, used to rank and highlight results
+	pub fn positive_terms(&self) -> Vec<String> {
+		fn collect(expr: &Expr, terms: &mut Vec<String>) {
+			match expr {
+				Expr::Filter(Filter::Text(term))
+				| Expr::Filter(Filter::Title(term)) => terms.push(term.clone()),
+				Expr::And(left, right) | Expr::Or(left, right) => {
+					collect(left, terms);
+					collect(right, terms);
+				},
+				Expr::All | Expr::Filter(_) | Expr::Not(_) => {},
+			}
+		}
+
+		let mut terms = Vec::new();
+		collect(&self.expr, &mut terms);
+		terms
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+	Word(String),
+	Quoted(String),
+	Not,
+	And,
+	Or,
+	OpenParen,
+	CloseParen,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, QueryError> {
+	let mut tokens = Vec::new();
+	let mut chars = input.chars().peekable();
+
+	while let Some(&c) = chars.peek() {
+		match c {
+			c if c.is_whitespace() => {
+				chars.next();
+			},
+			'(' => {
+				chars.next();
+				tokens.push(Token::OpenParen);
+			},
+			')' => {
+				chars.next();
+				tokens.push(Token::CloseParen);
+			},
+			'-' => {
+				chars.next();
+				tokens.push(Token::Not);
+			},
+			'"' => {
+				chars.next();
+				let mut text = String::new();
+				loop {
+					match chars.next() {
+						Some('"') => break,
+						Some(c) => text.push(c),
+						None => return Err(QueryError::UnclosedQuote),
+					}
+				}
+				tokens.push(Token::Quoted(text));
+			},
+			_ => {
+				let mut word = String::new();
+				while let Some(&c) = chars.peek() {
+					if c.is_whitespace() || c == '(' || c == ')' {
+						break;
+					}
+					if c == '"' {
+						// quoted values after a filter like title:"my bank"
+						chars.next();
+						loop {
+							match chars.next() {
+								Some('"') => break,
+								Some(c) => word.push(c),
+								None => return Err(QueryError::UnclosedQuote),
+							}
+						}
+						continue;
+					}
+					word.push(c);
+					chars.next();
+				}
+
+				tokens.push(match word.as_str() {
+					"AND" => Token::And,
+					"OR" => Token::Or,
+					"NOT" => Token::Not,
+					_ => Token::Word(word),
+				});
+			},
+		}
+	}
+
+	Ok(tokens)
+}
+
+fn parse_kind(value: &str) -> Result<DynFieldKind, QueryError> {
+	match value.to_lowercase().as_str() {
+		"secret" | "textlinesecret" => Ok(DynFieldKind::TextLineSecret),
+		"text" | "textline" => Ok(DynFieldKind::TextLine),
+		"multiline" => Ok(DynFieldKind::MultiLine),
+		"multilinesecret" => Ok(DynFieldKind::MultiLineSecret),
+		"url" => Ok(DynFieldKind::Url),
+		"heading" => Ok(DynFieldKind::Heading),
+		"reference" | "ref" => Ok(DynFieldKind::Reference),
+		_ => Err(QueryError::UnknownKind(String::from(value))),
+	}
+}
+
+fn parse_state(value: &str) -> Result<Filter, QueryError> {
+	match value.to_lowercase().as_str() {
+		"archived" => Ok(Filter::Archived),
+		_ => Err(QueryError::UnknownState(String::from(value))),
+	}
+}
+
+fn parse_date(value: &str) -> Result<DateFilter, QueryError> {
+	let invalid = || QueryError::InvalidDate(String::from(value));
+
+	let (comparison, rest) = if let Some(rest) = value.strip_prefix('<') {
+		(Comparison::Before, rest)
+	} else if let Some(rest) = value.strip_prefix('>') {
+		(Comparison::After, rest)
+	} else {
+		return Err(invalid());
+	};
+
+	if let Ok(date) = NaiveDate::parse_from_str(rest, "%Y-%m-%d") {
+		let timestamp = date.and_hms_opt(0, 0, 0).ok_or_else(invalid)?.and_utc();
+		return Ok(DateFilter {
+			comparison,
+			value: DateValue::Date(timestamp.timestamp().max(0) as u64),
+		});
+	}
+
+	let unit_at = rest.len().checked_sub(1).ok_or_else(invalid)?;
+	if !rest.is_char_boundary(unit_at) {
+		return Err(invalid());
+	}
+	let (amount, unit) = rest.split_at(unit_at);
+	let amount = amount.parse::<u64>().map_err(|_| invalid())?;
+	let days = match unit {
+		"d" => 1,
+		"w" => 7,
+		"m" => 30,
+		"y" => 365,
+		_ => return Err(invalid()),
+	};
+
+	Ok(DateFilter {
+		comparison,
+		value: DateValue::Age(
+			amount.checked_mul(days * SECONDS_PER_DAY).ok_or_else(invalid)?,
+		),
+	})
+}
+
+fn parse_term(word: &str) -> Result<Filter, QueryError> {
+	// a bare archived is short for is:archived, quote it to search the word
+	if word.to_lowercase() == "archived" {
+		return Ok(Filter::Archived);
+	}
+
+	let Some((key, value)) = word.split_once(':') else {
+		return Ok(Filter::Text(String::from(word)));
+	};
+
+	let key_lowercase = key.to_lowercase();
+	let filter_name = key_lowercase.as_str();
+	if !matches!(
+		filter_name,
+		"title" | "field" | "kind" | "tag" | "is" | "modified"
+	) {
+		// things like urls should still be searchable as plain text
+		return if !key.is_empty()
+			&& key.chars().all(char::is_alphabetic)
+			&& !value.starts_with("//")
+		{
+			Err(QueryError::UnknownFilter(String::from(key)))
+		} else {
+			Ok(Filter::Text(String::from(word)))
+		};
+	}
+
+	if value.is_empty() {
+		return Err(QueryError::EmptyValue(String::from(key)));
+	}
+
+	match filter_name {
+		"title" => Ok(Filter::Title(String::from(value))),
+		"field" => Ok(Filter::Field(String::from(value))),
+		"kind" => Ok(Filter::Kind(parse_kind(value)?)),
+		"tag" => Ok(Filter::Tag(String::from(value))),
+		"is" => parse_state(value),
+		_ => Ok(Filter::Modified(parse_date(value)?)),
+	}
+}
+
+struct Parser {
+	tokens: Vec<Token>,
+	position: usize,
+}
+
+impl Parser {
+	fn peek(&self) -> Option<&Token> {
+		self.tokens.get(self.position)
+	}
+
+	fn next(&mut self) -> Option<Token> {
+		let token = self.tokens.get(self.position).cloned();
+		self.position += 1;
+		token
+	}
+
+	// or := and ("OR" and)*
+	fn parse_or(&mut self) -> Result<Expr, QueryError> {
+		let mut expr = self.parse_and()?;
+
+		while let Some(Token::Or) = self.peek() {
+			self.next();
+			if self.peek().is_none() || self.peek() == Some(&Token::CloseParen) {
+				return Err(QueryError::DanglingOperator(String::from("OR")));
+			}
+			expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+		}
+
+		Ok(expr)
+	}
+
+	// and := unary ("AND"? unary)*
+	fn parse_and(&mut self) -> Result<Expr, QueryError> {
+		let mut expr = self.parse_unary()?;
+
+		loop {
+			match self.peek() {
+				None | Some(Token::Or) | Some(Token::CloseParen) => break,
+				Some(Token::And) => {
+					self.next();
+					if matches!(
+						self.peek(),
+						None | Some(Token::Or) | Some(Token::CloseParen)
+					) {
+						return Err(QueryError::DanglingOperator(String::from("AND")));
+					}
+				},
+				_ => {},
+			}
+			expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+		}
+
+		Ok(expr)
+	}
+
+	// unary := ("-" | "NOT") unary | "(" or ")" | term
+	fn parse_unary(&mut self) -> Result<Expr, QueryError> {
+		match self.next() {
+			Some(Token::Not) => match self.peek() {
+				None | Some(Token::Or) | Some(Token::And) | Some(Token::CloseParen) => {
+					Err(QueryError::DanglingOperator(String::from("-")))
+				},
+				_ => Ok(Expr::Not(Box::new(self.parse_unary()?))),
+			},
+			Some(Token::OpenParen) => {
+				if self.peek() == Some(&Token::CloseParen) {
+					self.next();
+					return Ok(Expr::All);
+				}
+				let expr = self.parse_or()?;
+				match self.next() {
+					Some(Token::CloseParen) => Ok(expr),
+					_ => Err(QueryError::UnclosedParen),
+				}
+			},
+			Some(Token::CloseParen) => Err(QueryError::UnexpectedParen),
+			Some(Token::Word(word)) => Ok(Expr::Filter(parse_term(&word)?)),
+			Some(Token::Quoted(text)) => Ok(Expr::Filter(Filter::Text(text))),
+			Some(Token::And) => {
+				Err(QueryError::DanglingOperator(String::from("AND")))
+			},
+			Some(Token::Or) => Err(QueryError::DanglingOperator(String::from("OR"))),
+			None => Ok(Expr::All),
+		}
+	}
+}
+
+// parse a search query like `title:github kind:url tag:ops -archived modified:<90d`
+pub fn parse_query(input: &str) -> Result<Query, QueryError> {
+	let mut parser = Parser {
+		tokens: tokenize(input)?,
+		position: 0,
+	};
+
+	if parser.tokens.is_empty() {
+		return Ok(Query { expr: Expr::All });
+	}
+
+	let expr = parser.parse_or()?;
+	match parser.peek() {
+		None => Ok(Query { expr }),
+		Some(_) => Err(QueryError::UnexpectedParen),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn text(value: &str) -> Expr {
+		Expr::Filter(Filter::Text(String::from(value)))
+	}
+
+	fn and(left: Expr, right: Expr) -> Expr {
+		Expr::And(Box::new(left), Box::new(right))
+	}
+
+	fn or(left: Expr, right: Expr) -> Expr {
+		Expr::Or(Box::new(left), Box::new(right))
+	}
+
+	fn not(expr: Expr) -> Expr {
+		Expr::Not(Box::new(expr))
+	}
+
+	fn parse(input: &str) -> Expr {
+		parse_query(input).unwrap().expr
+	}
+
+	#[test]
+	fn empty_query_test() {
+		assert_eq!(parse(""), Expr::All);
+		assert_eq!(parse("   "), Expr::All);
+		assert_eq!(parse("()"), Expr::All);
+	}
+
+	#[test]
+	fn plain_text_test() {
+		assert_eq!(parse("github"), text("github"));
+		assert_eq!(parse("my bank"), and(text("my"), text("bank")));
+		assert_eq!(parse("\"my bank\""), text("my bank"));
+	}
+
+	#[test]
+	fn filters_test() {
+		assert_eq!(
+			parse("title:github"),
+			Expr::Filter(Filter::Title(String::from("github")))
+		);
+		assert_eq!(
+			parse("field:password"),
+			Expr::Filter(Filter::Field(String::from("password")))
+		);
+		assert_eq!(
+			parse("tag:ops"),
+			Expr::Filter(Filter::Tag(String::from("ops")))
+		);
+		assert_eq!(
+			parse("title:\"my bank\""),
+			Expr::Filter(Filter::Title(String::from("my bank")))
+		);
+		assert_eq!(
+			parse("TITLE:GitHub"),
+			Expr::Filter(Filter::Title(String::from("GitHub")))
+		);
+	}
+
+	#[test]
+	fn kind_filter_test() {
+		assert_eq!(
+			parse("kind:url"),
+			Expr::Filter(Filter::Kind(DynFieldKind::Url))
+		);
+		assert_eq!(
+			parse("kind:Secret"),
+			Expr::Filter(Filter::Kind(DynFieldKind::TextLineSecret))
+		);
+		assert_eq!(
+			parse("kind:multilinesecret"),
+			Expr::Filter(Filter::Kind(DynFieldKind::MultiLineSecret))
+		);
+		assert_eq!(
+			parse("kind:ref"),
+			Expr::Filter(Filter::Kind(DynFieldKind::Reference))
+		);
+		assert_eq!(
+			parse_query("kind:folder"),
+			Err(QueryError::UnknownKind(String::from("folder")))
+		);
+	}
+
+	#[test]
+	fn modified_filter_test() {
+		assert_eq!(
+			parse("modified:<90d"),
+			Expr::Filter(Filter::Modified(DateFilter {
+				comparison: Comparison::Before,
+				value: DateValue::Age(90 * SECONDS_PER_DAY),
+			}))
+		);
+		assert_eq!(
+			parse("modified:>2w"),
+			Expr::Filter(Filter::Modified(DateFilter {
+				comparison: Comparison::After,
+				value: DateValue::Age(14 * SECONDS_PER_DAY),
+			}))
+		);
+		assert_eq!(
+			parse("modified:>2024-03-01"),
+			Expr::Filter(Filter::Modified(DateFilter {
+				comparison: Comparison::After,
+				value: DateValue::Date(1709251200),
+			}))
+		);
+		assert_eq!(
+			parse_query("modified:90d"),
+			Err(QueryError::InvalidDate(String::from("90d")))
+		);
+		assert_eq!(
+			parse_query("modified:<90x"),
+			Err(QueryError::InvalidDate(String::from("<90x")))
+		);
+		assert_eq!(
+			parse_query("modified:<"),
+			Err(QueryError::InvalidDate(String::from("<")))
+		);
+		assert_eq!(
+			parse_query("modified:<2024-13-01"),
+			Err(QueryError::InvalidDate(String::from("<2024-13-01")))
+		);
+		assert_eq!(
+			parse_query("modified:<99999999999999999999y"),
+			Err(QueryError::InvalidDate(String::from("<99999999999999999999y")))
+		);
+	}
+
+	#[test]
+	fn date_filter_matches_test() {
+		let now = 1_000 * SECONDS_PER_DAY;
+		let younger_than_90d = DateFilter {
+			comparison: Comparison::Before,
+			value: DateValue::Age(90 * SECONDS_PER_DAY),
+		};
+		let older_than_90d = DateFilter {
+			comparison: Comparison::After,
+			value: DateValue::Age(90 * SECONDS_PER_DAY),
+		};
+
+		assert!(younger_than_90d.matches(now - 10 * SECONDS_PER_DAY, now));
+		assert!(!younger_than_90d.matches(now - 100 * SECONDS_PER_DAY, now));
+		assert!(older_than_90d.matches(now - 100 * SECONDS_PER_DAY, now));
+		assert!(!older_than_90d.matches(now - 10 * SECONDS_PER_DAY, now));
+
+		let before_date = DateFilter {
+			comparison: Comparison::Before,
+			value: DateValue::Date(500),
+		};
+		let after_date = DateFilter {
+			comparison: Comparison::After,
+			value: DateValue::Date(500),
+		};
+		assert!(before_date.matches(499, now));
+		assert!(!before_date.matches(500, now));
+		assert!(after_date.matches(500, now));
+		assert!(!after_date.matches(499, now));
+	}
+
+	#[test]
+	fn negation_test() {
+		assert_eq!(parse("-old"), not(text("old")));
+		assert_eq!(parse("NOT old"), not(text("old")));
+		assert_eq!(parse("--old"), not(not(text("old"))));
+		assert_eq!(
+			parse("-tag:ops"),
+			not(Expr::Filter(Filter::Tag(String::from("ops"))))
+		);
+	}
+
+	#[test]
+	fn boolean_operators_test() {
+		assert_eq!(parse("a AND b"), and(text("a"), text("b")));
+		assert_eq!(parse("a OR b"), or(text("a"), text("b")));
+		assert_eq!(parse("a b OR c"), or(and(text("a"), text("b")), text("c")));
+		assert_eq!(parse("a OR b c"), or(text("a"), and(text("b"), text("c"))));
+		assert_eq!(parse("a (b OR c)"), and(text("a"), or(text("b"), text("c"))));
+		assert_eq!(parse("-(a OR b)"), not(or(text("a"), text("b"))));
+		assert_eq!(parse("a or b"), and(and(text("a"), text("or")), text("b")));
+	}
+
+	#[test]
+	fn full_query_test() {
+		assert_eq!(
+			parse("title:github kind:url tag:ops -archived modified:<90d"),
+			and(
+				and(
+					and(
+						and(
+							Expr::Filter(Filter::Title(String::from("github"))),
+							Expr::Filter(Filter::Kind(DynFieldKind::Url))
+						),
+						Expr::Filter(Filter::Tag(String::from("ops")))
+					),
+					not(Expr::Filter(Filter::Archived))
+				),
+				Expr::Filter(Filter::Modified(DateFilter {
+					comparison: Comparison::Before,
+					value: DateValue::Age(90 * SECONDS_PER_DAY),
+				}))
+			)
+		);
+	}
+
+	#[test]
+	fn archived_test() {
+		let archived = Expr::Filter(Filter::Archived);
+		assert_eq!(parse("is:archived"), archived);
+		assert_eq!(parse("IS:Archived"), archived);
+		assert_eq!(parse("archived"), archived);
+		assert_eq!(parse("-archived"), not(archived.clone()));
+		assert_eq!(parse("NOT is:archived"), not(archived));
+		assert_eq!(parse("\"archived\""), text("archived"));
+		assert_eq!(
+			parse_query("is:new"),
+			Err(QueryError::UnknownState(String::from("new")))
+		);
+		assert_eq!(
+			parse_query("is:"),
+			Err(QueryError::EmptyValue(String::from("is")))
+		);
+	}
+
+	#[test]
+	fn plain_text_with_colon_test() {
+		assert_eq!(parse("https://github.com"), text("https://github.com"));
+		assert_eq!(parse("10:30"), text("10:30"));
+	}
+
+	#[test]
+	fn errors_test() {
+		assert_eq!(
+			parse_query("owner:me"),
+			Err(QueryError::UnknownFilter(String::from("owner")))
+		);
+		assert_eq!(
+			parse_query("title:"),
+			Err(QueryError::EmptyValue(String::from("title")))
+		);
+		assert_eq!(parse_query("\"my bank"), Err(QueryError::UnclosedQuote));
+		assert_eq!(parse_query("title:\"my bank"), Err(QueryError::UnclosedQuote));
+		assert_eq!(parse_query("(a OR b"), Err(QueryError::UnclosedParen));
+		assert_eq!(parse_query("a)"), Err(QueryError::UnexpectedParen));
+		assert_eq!(parse_query(")"), Err(QueryError::UnexpectedParen));
+		assert_eq!(
+			parse_query("a OR"),
+			Err(QueryError::DanglingOperator(String::from("OR")))
+		);
+		assert_eq!(
+			parse_query("OR a"),
+			Err(QueryError::DanglingOperator(String::from("OR")))
+		);
+		assert_eq!(
+			parse_query("a AND"),
+			Err(QueryError::DanglingOperator(String::from("AND")))
+		);
+		assert_eq!(
+			parse_query("a -"),
+			Err(QueryError::DanglingOperator(String::from("-")))
+		);
+		assert_eq!(
+			parse_query("(a -)"),
+			Err(QueryError::DanglingOperator(String::from("-")))
+		);
+	}
+
+	#[test]
+	fn positive_terms_test() {
+		let query =
+			parse_query("github title:hub -archived (ops OR kind:url) -(x y)")
+				.unwrap();
+		assert_eq!(
+			query.positive_terms(),
+			vec![
+				String::from("github"),
+				String::from("hub"),
+				String::from("ops")
+			]
+		);
+	}
+
+	#[test]
+	fn plain_text_query_test() {
+		assert_eq!(Query::plain_text("  ").expr, Expr::All);
+		assert_eq!(Query::plain_text("owner:me (").expr, text("owner:me ("));
+	}
+
+	#[test]
+	fn error_message_test() {
+		assert_eq!(
+			QueryError::UnknownFilter(String::from("owner")).to_string(),
+			"Unknown filter \"owner:\", try title:, field:, kind:, tag:, is: or modified:"
+		);
+	}
+}
@@ -1,3 +1,6 @@
+use chrono::{DateTime, Local};
+use std::{collections::HashMap, sync::Arc};
+
 use floem::{
 	event::{Event, EventListener},
 	keyboard::{KeyCode, PhysicalKey},
@@ -9,19 +12,24 @@ use floem::{
 	},
 	style::{CursorStyle, Display, Position},
 	views::{
-		container, dyn_container, empty, label, scroll, virtual_stack, Decorators,
-		VirtualDirection, VirtualItemSize,
+		container, dyn_container, empty, h_stack_from_iter, label, scroll, svg,
+		virtual_stack, Decorators, VirtualDirection, VirtualItemSize,
 	},
 	IntoView,
 };
 
 use crate::{
 	config::{PresetFields, WindowSettings},
+	db::{Db, MatchOrigin},
 	env::Environment,
+	expiry::ExpiryStatus,
 	lock_app,
+	search::query::{parse_query, Query},
 	ui::{
+		audit_view::{audit_view, AuditView},
 		colors::*,
 		details::detail_view::{detail_view, DETAILS_MIN_WIDTH},
+		history_view::open_history_window,
 		keyboard::is_submit,
 		primitives::{
 			button::{icon_button, IconButton},
@@ -40,6 +48,109 @@ const SEARCHBAR_HEIGHT: f64 = 30.0;
 
 pub type SidebarList = RwSignal<im::Vector<(usize, String, usize)>>;
 pub type PresetFieldSignal = RwSignal<PresetFields>;
+pub type ActiveTab = RwSignal<usize>;
+pub type ExpiryList = RwSignal<HashMap<usize, ExpiryStatus>>;
+
+// split a title into runs of matched and unmatched chars for the sidebar
+fn highlighted_title(title: &str, positions: &[usize]) -> impl IntoView {
+	let mut segments: Vec<(String, bool)> = Vec::new();
+	for (idx, c) in title.chars().enumerate() {
+		let is_match = positions.contains(&idx);
+		match segments.last_mut() {
+			Some((text, last_match)) if *last_match == is_match => text.push(c),
+			_ => segments.push((c.to_string(), is_match)),
+		}
+	}
+
+	h_stack_from_iter(segments.into_iter().map(|(text, is_match)| {
+		label(move || text.clone()).style(move |s| {
+			s.font_size(12.0)
+				.color(C_SIDE_TEXT)
+				.apply_if(is_match, |s| s.color(C_FOCUS).font_bold())
+				.apply_if(!is_match, |s| s.text_ellipsis().min_width(0))
+		})
+	}))
+	.style(|s| s.max_width_full())
+}
+
+fn match_origin_text(origin: &MatchOrigin) -> String {
+	if origin.is_archived {
+		format!("matched in archived field {}", origin.field_title)
+	} else {
+		let date = DateTime::from_timestamp(origin.date as i64, 0)
+			.unwrap_or_default()
+			.with_timezone(&Local);
+		format!(
+			"matched in history of {} ({})",
+			origin.field_title,
+			date.format("%Y-%m-%d")
+		)
+	}
+}
+
+// PRIVATE: marks sidebar rows that only matched inside history or archived fields
+fn match_origin_badge(
+	id: usize,
+	origin: MatchOrigin,
+	active_tab: ActiveTab,
+	tooltip_signals: TooltipSignals,
+	db: Arc<Db>,
+) -> impl IntoView {
+	let history_icon = include_str!("./icons/history.svg");
+	let tooltip_text = match_origin_text(&origin);
+
+	svg(move || String::from(history_icon))
+		.on_event_cont(EventListener::PointerEnter, move |_| {
+			tooltip_signals.show(tooltip_text.clone());
+		})
+		.on_event_cont(EventListener::PointerLeave, move |_| {
+			tooltip_signals.hide();
+		})
+		.on_click_stop(move |_| {
+			tooltip_signals.hide();
+			db.touch(&id);
+			active_tab.set(id);
+			open_history_window(
+				id,
+				origin.field,
+				origin.field_title.clone(),
+				Some(origin.history_idx),
+				db.clone(),
+				|| {},
+			);
+		})
+		.style(|s| {
+			s.width(12)
+				.height(12)
+				.min_width(12)
+				.margin_left(3)
+				.cursor(CursorStyle::Pointer)
+		})
+}
+
+// PRIVATE: marks sidebar rows with secrets that are expired or due soon
+fn expiry_badge(
+	status: ExpiryStatus,
+	tooltip_signals: TooltipSignals,
+) -> impl IntoView {
+	let icon = match status {
+		ExpiryStatus::Expired => include_str!("./icons/expired.svg"),
+		ExpiryStatus::DueSoon => include_str!("./icons/due_soon.svg"),
+	};
+	let tooltip_text = match status {
+		ExpiryStatus::Expired => String::from("Needs to be rotated"),
+		ExpiryStatus::DueSoon => String::from("Needs to be rotated soon"),
+	};
+
+	svg(move || String::from(icon))
+		.on_event_cont(EventListener::PointerEnter, move |_| {
+			tooltip_signals.show(tooltip_text.clone());
+		})
+		.on_event_cont(EventListener::PointerLeave, move |_| {
+			tooltip_signals.hide();
+		})
+		.style(|s| s.width(12).height(12).min_width(12).margin_left(3))
+}
 
 #[derive(Debug, Copy, Clone)]
 pub struct QueSettings {
@@ -67,21 +178,31 @@ pub fn app_view(search_trigger: Trigger) -> impl IntoView {
 		create_rw_signal(env.db.get_sidebar_list());
 
 	provide_context(list_sidebar_signal);
+	let expiry_list: ExpiryList = create_rw_signal(env.db.get_expiry_list());
+	provide_context(expiry_list);
 	let field_presets: PresetFieldSignal =
 		create_rw_signal(env.config.get_field_presets());
 	provide_context(field_presets);
 
+	let env_search = env.clone();
 	let env_search_reset = env.clone();
+	let env_sidebar = env.clone();
+	let env_audit = env.clone();
 	let config_sidebar_drag = env.config.clone();
 	let config_sidebar_double_click = env.config.clone();
 
 	let sidebar_width =
 		create_rw_signal(env.config.general.read().window_settings.sidebar_width);
 	let is_sidebar_dragging = create_rw_signal(false);
-	let active_tab = create_rw_signal(
+	let active_tab: ActiveTab = create_rw_signal(
 		list_sidebar_signal.get().get(0).unwrap_or(&(0, String::from(""), 0)).0,
 	);
+	provide_context(active_tab);
 	let search_text = create_rw_signal(String::from(""));
+	let search_highlights = create_rw_signal(HashMap::<usize, Vec<usize>>::new());
+	let search_origins = create_rw_signal(HashMap::<usize, MatchOrigin>::new());
+	let deep_search = create_rw_signal(false);
+	let search_hint = create_rw_signal(String::from(""));
 	let sidebar_scrolled = create_rw_signal(false);
 	let main_scroll_to = create_rw_signal(0.0);
 
@@ -100,7 +221,42 @@ pub fn app_view(search_trigger: Trigger) -> impl IntoView {
 	let delete_icon = include_str!("./icons/delete.svg");
 	let icon = create_rw_signal(String::from(""));
 	let settings_icon = include_str!("./icons/settings.svg");
+	let audit_icon = include_str!("./icons/audit.svg");
 	let lock_icon = include_str!("./icons/lock.svg");
+	let deep_search_icon = include_str!("./icons/deep_search.svg");
+	let shallow_search_icon = include_str!("./icons/shallow_search.svg");
+
+	let run_search = move || {
+		// we still search for the raw text while showing why the query didn't parse
+		let query = match parse_query(&search_text.get()) {
+			Ok(query) => {
+				search_hint.set(String::from(""));
+				query
+			},
+			Err(error) => {
+				search_hint.set(error.to_string());
+				Query::plain_text(&search_text.get())
+			},
+		};
+		let hits = env_search.db.search(&query, deep_search.get());
+		list_sidebar_signal.set(
+			hits.iter().map(|hit| (hit.id, hit.title.clone(), hit.idx)).collect(),
+		);
+		search_highlights.set(
+			hits
+				.iter()
+				.filter(|hit| !hit.title_positions.is_empty())
+				.map(|hit| (hit.id, hit.title_positions.clone()))
+				.collect(),
+		);
+		search_origins.set(
+			hits
+				.into_iter()
+				.filter_map(|hit| hit.origin.map(|origin| (hit.id, origin)))
+				.collect(),
+		);
+	};
+	let run_search_deep = run_search.clone();
 
 	let search_text_input_view = input_button_field(
 		InputButtonField {
@@ -113,6 +269,9 @@ pub fn app_view(search_trigger: Trigger) -> impl IntoView {
 		move || {
 			icon.set(String::from(""));
 			search_text.set(String::from(""));
+			search_highlights.set(HashMap::new());
+			search_origins.set(HashMap::new());
+			search_hint.set(String::from(""));
 			list_sidebar_signal.update(
 				|list: &mut im::Vector<(usize, String, usize)>| {
 					*list = env_search_reset
@@ -167,14 +326,65 @@ pub fn app_view(search_trigger: Trigger) -> impl IntoView {
 					active_tab.set(search_list[0].0);
 					list_sidebar_signal.set(search_list);
 					search_text.set(String::from(""));
+					search_highlights.set(HashMap::new());
+					search_origins.set(HashMap::new());
+					search_hint.set(String::from(""));
 					icon.set(String::from(""));
 				} else {
-					list_sidebar_signal.update(|list| {
-						*list = env.db.search(&search_text.get());
-					});
+					run_search();
 				}
 			})
 			.style(|s| s.flex_grow(1.0)),
+		icon_button(
+			IconButton {
+				icon: String::from(deep_search_icon),
+				icon2: Some(String::from(shallow_search_icon)),
+				tooltip: String::from(
+					"Deep search: include history and archived fields",
+				),
+				tooltip2: Some(String::from("Only search current fields")),
+				switch: Some(deep_search),
+				tooltip_signals,
+				..IconButton::default()
+			},
+			move |_| {
+				if !search_text.get().is_empty() {
+					run_search_deep();
+				}
+			},
+		),
+		icon_button(
+			IconButton {
+				icon: String::from(audit_icon),
+				tooltip: String::from("Security audit"),
+				tooltip_signals,
+				..IconButton::default()
+			},
+			move |_| {
+				let env_audit = env_audit.clone();
+				let que_audit = Que::default();
+
+				tooltip_signals.hide();
+				opening_window(
+					move || {
+						audit_view(AuditView {
+							active_tab,
+							que: que_audit,
+							env: env_audit.clone(),
+						})
+					},
+					WindowSpec {
+						id: String::from("audit-window"),
+						title: String::from("Security Audit"),
+					},
+					Size::new(450.0, 350.0),
+					false,
+					move || {
+						que_audit.unque_all_tooltips();
+					},
+				);
+			},
+		),
 		icon_button(
 			IconButton {
 				icon: String::from(lock_icon),
@@ -227,19 +437,65 @@ pub fn app_view(search_trigger: Trigger) -> impl IntoView {
 			move |item| item.clone(),
 			move |item| {
 				let title = item.1.clone();
+				let title_highlight = item.1.clone();
+				let env_sidebar = env_sidebar.clone();
+				let db_badge = env_sidebar.db.clone();
 				container(
-					label(move || item.1.clone())
-						.style(|s| s.font_size(12.0).color(C_SIDE_TEXT))
+					(
+						dyn_container(
+							move || search_highlights.get().get(&item.0).cloned(),
+							move |positions| match positions {
+								Some(positions) => {
+									highlighted_title(&title_highlight, &positions).into_any()
+								},
+								None => {
+									let title_label = title_highlight.clone();
+									label(move || title_label.clone())
+										.style(|s| {
+											s.font_size(12.0)
+												.color(C_SIDE_TEXT)
+												.text_ellipsis()
+												.max_width_full()
+										})
+										.on_text_overflow(move |is_overflown| {
+											let mut labels = overflow_labels.get();
+											if is_overflown {
+												labels.push(item.0);
+											} else {
+												labels.retain(|i| *i != item.0);
+											}
+											overflow_labels.set(labels);
+										})
+										.into_any()
+								},
+							},
+						)
+						.style(|s| s.flex_grow(1.0).min_width(0)),
+						dyn_container(
+							move || search_origins.get().get(&item.0).cloned(),
+							move |origin| match origin {
+								Some(origin) => match_origin_badge(
+									item.0,
+									origin,
+									active_tab,
+									tooltip_signals,
+									db_badge.clone(),
+								)
+								.into_any(),
+								None => empty().into_any(),
+							},
+						),
+						dyn_container(
+							move || expiry_list.get().get(&item.0).copied(),
+							move |status| match status {
+								Some(status) => {
+									expiry_badge(status, tooltip_signals).into_any()
+								},
+								None => empty().into_any(),
+							},
+						),
+					)
 						.keyboard_navigatable()
-						.on_text_overflow(move |is_overflown| {
-							let mut labels = overflow_labels.get();
-							if is_overflown {
-								labels.push(item.0);
-							} else {
-								labels.retain(|i| *i != item.0);
-							}
-							overflow_labels.set(labels);
-						})
 						.on_event_cont(EventListener::PointerEnter, move |_| {
 							let labels = overflow_labels.get();
 							if labels.contains(&item.0) {
@@ -250,6 +506,7 @@ pub fn app_view(search_trigger: Trigger) -> impl IntoView {
 							tooltip_signals.hide();
 						})
 						.on_click_stop(move |_| {
+							env_sidebar.db.touch(&item.0);
 							active_tab.set(item.0);
 							main_scroll_to.set(0.0);
 						})
@@ -259,7 +516,7 @@ pub fn app_view(search_trigger: Trigger) -> impl IntoView {
 								.padding_top(3.0)
 								.padding_bottom(3.0)
 								.width(sidebar_width.get())
-								.items_start()
+								.items_center()
 								.border_bottom(1.0)
 								.border_color(C_SIDE_BG_BORDER)
 								.color(C_SIDE_TEXT)
@@ -390,6 +647,23 @@ pub fn app_view(search_trigger: Trigger) -> impl IntoView {
 			.z_index(3)
 	});
 
+	let search_hint_view = label(move || search_hint.get()).style(move |s| {
+		s.position(Position::Absolute)
+			.z_index(5)
+			.inset_top(SEARCHBAR_HEIGHT)
+			.inset_left(0)
+			.inset_right(0)
+			.padding(3.0)
+			.padding_left(10.0)
+			.font_size(11.0)
+			.color(C_TOOLTIP_TEXT)
+			.background(C_TOOLTIP_BG)
+			.border_bottom(1.0)
+			.border_color(C_TOOLTIP_BORDER)
+			.display(Display::None)
+			.apply_if(!search_hint.get().is_empty(), |s| s.display(Display::Flex))
+	});
+
 	let content =
 		(sidebar, shadow_box_top, shadow_box_right, dragger, main_window).style(
 			|s| {
@@ -405,6 +679,7 @@ pub fn app_view(search_trigger: Trigger) -> impl IntoView {
 		toast_view(toast_signals),
 		search_bar,
 		content,
+		search_hint_view,
 	)
 		.style(|s| s.flex_col().width_full().height_full())
 		.style(styles::default_window_styles)
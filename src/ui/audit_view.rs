@@ -0,0 +1,229 @@
+use chrono::Utc;
+
+use floem::{
+	event::{Event, EventListener},
+	reactive::{create_rw_signal, provide_context, SignalGet, SignalUpdate},
+	style::{CursorStyle, Display},
+	views::{
+		container, label, scroll, virtual_stack, Decorators, VirtualDirection,
+		VirtualItemSize,
+	},
+	IntoView, View,
+};
+
+use crate::{
+	audit::{
+		run_audit, run_breach_audit, sort_findings, AuditSummary, Finding,
+		FindingKind,
+	},
+	breach::BreachFile,
+	env::Environment,
+	ui::{
+		app_view::ActiveTab,
+		colors::*,
+		primitives::{
+			button::button,
+			input_field::input_field,
+			que::Que,
+			tooltip::{tooltip_view, TooltipSignals},
+		},
+	},
+};
+
+const FINDING_LINE_HEIGHT: f64 = 31.0;
+const PADDING: f64 = 10.0;
+
+pub struct AuditView {
+	pub active_tab: ActiveTab,
+	pub que: Que,
+	pub env: Environment,
+}
+
+// PRIVATE: the breach file is only read when asked for as it can be many GB
+fn scan(
+	env: &Environment,
+	with_breaches: bool,
+) -> (im::Vector<(usize, Finding)>, Option<String>) {
+	let mut findings = run_audit(
+		env.db.get_audit_fields(),
+		env.config.get_max_secret_age_days(),
+		Utc::now().timestamp() as u64,
+	);
+	let mut error = None;
+
+	if with_breaches {
+		let path = env.config.get_breach_file_path();
+		if path.is_empty() {
+			error = Some(String::from(
+				"Select a breach file in the database settings first",
+			));
+		} else {
+			match BreachFile::open(&path).and_then(|mut breach_file| {
+				run_breach_audit(env.db.get_audit_fields(), &mut breach_file)
+			}) {
+				Ok(breached) => findings.extend(breached),
+				Err(breach_error) => error = Some(breach_error.to_string()),
+			}
+		}
+		sort_findings(&mut findings);
+	}
+
+	(findings.into_iter().enumerate().collect(), error)
+}
+
+fn finding_line(
+	idx: usize,
+	finding: Finding,
+	active_tab: ActiveTab,
+	tooltip_signals: TooltipSignals,
+	env: Environment,
+) -> impl IntoView {
+	let color = match finding.kind {
+		FindingKind::Breached(_)
+		| FindingKind::Reused(_)
+		| FindingKind::Weak(_) => C_ERROR,
+		FindingKind::Old(_) | FindingKind::NotSecret => C_WARNING,
+	};
+	let kind_text = finding.kind.to_string();
+	let path = format!("{} › {}", finding.entry_title, finding.field_title);
+	let tooltip_text = format!("Show \"{}\"", finding.entry_title);
+	let id = finding.id;
+
+	(
+		label(move || kind_text.clone())
+			.style(move |s| s.color(color).font_size(10.0).min_width(110)),
+		label(move || path.clone())
+			.style(|s| s.flex_grow(1.0).width(80).text_ellipsis()),
+	)
+		.on_event_cont(EventListener::PointerEnter, move |_| {
+			tooltip_signals.show(tooltip_text.clone());
+		})
+		.on_event_cont(EventListener::PointerLeave, move |_| {
+			tooltip_signals.hide();
+		})
+		.on_click_stop(move |_| {
+			tooltip_signals.hide();
+			env.db.touch(&id);
+			active_tab.set(id);
+		})
+		.style(move |s| {
+			s.flex()
+				.flex_row()
+				.width_full()
+				.max_width_full()
+				.height(FINDING_LINE_HEIGHT)
+				.row_gap(4)
+				.padding_horiz(PADDING)
+				.items_center()
+				.cursor(CursorStyle::Pointer)
+				.background(if let 0 = idx % 2 {
+					C_SIDE_BG
+				} else {
+					C_SIDE_BG_SELECTED.with_alpha_factor(0.2)
+				})
+				.hover(|s| s.background(C_FOCUS.with_alpha_factor(0.05)))
+		})
+}
+
+pub fn audit_view(param: AuditView) -> impl IntoView {
+	let AuditView {
+		active_tab,
+		que,
+		env,
+	} = param;
+
+	let tooltip_signals = TooltipSignals::new(que);
+	provide_context(tooltip_signals);
+
+	let (initial_findings, _) = scan(&env, false);
+	let findings = create_rw_signal(initial_findings);
+	let error = create_rw_signal(None::<String>);
+	let max_age =
+		create_rw_signal(env.config.get_max_secret_age_days().to_string());
+
+	let env_scan = env.clone();
+	let env_breach = env.clone();
+
+	let audit_view = (
+		(
+			label(|| "Flag secrets older than"),
+			input_field(max_age).style(|s| s.width(40)),
+			label(|| "days (0 = never)"),
+			container(button("Scan").on_click_cont(move |_| {
+				if let Ok(days) = max_age.get().trim().parse::<u64>() {
+					if days != env_scan.config.get_max_secret_age_days() {
+						env_scan.config.set_max_secret_age_days(days);
+					}
+				}
+				max_age.set(env_scan.config.get_max_secret_age_days().to_string());
+				let (new_findings, new_error) = scan(&env_scan, false);
+				findings.set(new_findings);
+				error.set(new_error);
+			})),
+			container(button("Check breaches").on_click_cont(move |_| {
+				let (new_findings, new_error) = scan(&env_breach, true);
+				findings.set(new_findings);
+				error.set(new_error);
+			})),
+		)
+			.style(|s| s.items_center().row_gap(5).padding(PADDING)),
+		label(move || match error.get() {
+			Some(error) => error,
+			None => AuditSummary::new(
+				&findings.get().into_iter().map(|(_, item)| item).collect::<Vec<_>>(),
+			)
+			.to_string(),
+		})
+		.style(move |s| {
+			s.padding_horiz(PADDING)
+				.padding_bottom(PADDING)
+				.apply_if(error.get().is_some(), |s| s.color(C_ERROR))
+		}),
+		scroll(
+			virtual_stack(
+				VirtualDirection::Vertical,
+				VirtualItemSize::Fixed(Box::new(|| FINDING_LINE_HEIGHT)),
+				move || findings.get(),
+				move |(idx, item)| (*idx, item.id, item.field, item.kind.clone()),
+				move |(idx, item)| {
+					finding_line(idx, item, active_tab, tooltip_signals, env.clone())
+				},
+			)
+			.style(|s| s.flex_col().flex_grow(1.0)),
+		)
+		.style(move |s| {
+			s.width_full()
+				.flex_grow(1.0)
+				.apply_if(findings.get().is_empty(), |s| s.display(Display::None))
+		}),
+		tooltip_view(tooltip_signals),
+	)
+		.style(|s| s.flex_col().width_full().height_full().background(C_MAIN_BG))
+		.on_event_cont(EventListener::PointerMove, move |event| {
+			let pos = match event {
+				Event::PointerMove(p) => p.pos,
+				_ => (0.0, 0.0).into(),
+			};
+			tooltip_signals.mouse_pos.set((pos.x, pos.y));
+		})
+		.on_resize(move |event| {
+			tooltip_signals.window_size.set((event.x1, event.y1));
+		});
+
+	match std::env::var("DEBUG") {
+		Ok(_) => {
+			// for debugging the layout
+			let id = audit_view.id();
+			audit_view.on_event_stop(EventListener::KeyUp, move |e| {
+				if let floem::event::Event::KeyUp(e) = e {
+					if e.key.logical_key
+						== floem::keyboard::Key::Named(floem::keyboard::NamedKey::F11)
+					{
+						id.inspect();
+					}
+				}
+			})
+		},
+		Err(_) => audit_view,
+	}
+}
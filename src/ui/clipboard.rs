@@ -0,0 +1,215 @@
+use core::cell::RefCell;
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+use zeroize::Zeroize;
+
+use floem::{action::exec_after, Clipboard};
+
+use crate::ui::primitives::toast::ToastSignals;
+
+thread_local! {
+	// the hash of what we copied last and a counter that tells timers they've been replaced
+	static COPIED: RefCell<(Option<[u8; 32]>, u64)> = const { RefCell::new((None, 0)) };
+}
+
+// the parts of the system clipboard we use so tests can swap in their own
+trait SystemClipboard {
+	fn get(&self) -> Option<String>;
+	fn set(&self, value: String);
+}
+
+// PRIVATE: the clipboard of the os
+struct OsClipboard;
+
+impl SystemClipboard for OsClipboard {
+	fn get(&self) -> Option<String> {
+		Clipboard::get_contents().ok()
+	}
+
+	fn set(&self, value: String) {
+		let _ = Clipboard::set_contents(value);
+	}
+}
+
+// what a second of the countdown did
+#[derive(Debug, PartialEq)]
+enum Tick {
+	// something else was copied or the clipboard was cleared in the meantime
+	Cancelled,
+	// the time ran out and the clipboard was cleared if it still held our value
+	Finished,
+	Running,
+}
+
+// PRIVATE: we only keep a hash so the secret doesn't stay in memory
+fn hash(value: &str) -> [u8; 32] {
+	Sha256::digest(value.as_bytes()).into()
+}
+
+fn countdown_text(seconds: u64) -> String {
+	format!("Clipboard will be cleared in {}s", seconds)
+}
+
+// PRIVATE: copy a value and return the generation its timer has to match, the
+// hash is kept even without a timer so locking the app still clears it
+fn copy_with(clipboard: &impl SystemClipboard, value: String) -> u64 {
+	let generation = COPIED.with(|copied| {
+		let mut copied = copied.borrow_mut();
+		copied.0 = Some(hash(&value));
+		copied.1 += 1;
+		copied.1
+	});
+	clipboard.set(value);
+
+	generation
+}
+
+// PRIVATE: one second of the countdown of the given generation
+fn tick(
+	clipboard: &impl SystemClipboard,
+	generation: u64,
+	remaining: u64,
+) -> Tick {
+	if COPIED.with(|copied| copied.borrow().1 != generation) {
+		return Tick::Cancelled;
+	}
+
+	if remaining <= 1 {
+		clear_with(clipboard);
+		Tick::Finished
+	} else {
+		Tick::Running
+	}
+}
+
+// PRIVATE: clear the clipboard but only if it still holds what we copied
+fn clear_with(clipboard: &impl SystemClipboard) {
+	let copied = COPIED.with(|copied| {
+		let mut copied = copied.borrow_mut();
+		copied.1 += 1;
+		copied.0.take()
+	});
+
+	if let Some(copied) = copied {
+		if let Some(mut current) = clipboard.get() {
+			if hash(&current) == copied {
+				clipboard.set(String::new());
+			}
+			current.zeroize();
+		}
+	}
+}
+
+// copy a value and clear it again after the timeout (0 keeps it until the app
+// locks)
+pub fn copy_to_clipboard(
+	value: String,
+	timeout: u64,
+	toast_signals: Option<ToastSignals>,
+) {
+	let generation = copy_with(&OsClipboard, value);
+
+	if timeout > 0 {
+		let toast = toast_signals.map(|toast_signals| {
+			(toast_signals, toast_signals.add_for(countdown_text(timeout), timeout))
+		});
+		countdown(generation, timeout, toast);
+	}
+}
+
+// PRIVATE: tick every second until the clipboard is cleared or something new was copied
+fn countdown(
+	generation: u64,
+	remaining: u64,
+	toast: Option<(ToastSignals, u8)>,
+) {
+	exec_after(Duration::from_secs(1), move |_| {
+		match tick(&OsClipboard, generation, remaining) {
+			Tick::Cancelled => {
+				if let Some((toast_signals, id)) = toast {
+					toast_signals.remove(id);
+				}
+			},
+			Tick::Finished => {},
+			Tick::Running => {
+				if let Some((toast_signals, id)) = toast {
+					toast_signals.set_text(id, countdown_text(remaining - 1));
+				}
+				countdown(generation, remaining - 1, toast);
+			},
+		}
+	});
+}
+
+// clear the clipboard but only if it still holds what we copied
+pub fn clear_clipboard() {
+	clear_with(&OsClipboard);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(Default)]
+	struct FakeClipboard {
+		contents: RefCell<String>,
+	}
+
+	impl SystemClipboard for FakeClipboard {
+		fn get(&self) -> Option<String> {
+			Some(self.contents.borrow().clone())
+		}
+
+		fn set(&self, value: String) {
+			*self.contents.borrow_mut() = value;
+		}
+	}
+
+	#[test]
+	fn countdown_test() {
+		let clipboard = FakeClipboard::default();
+		let generation = copy_with(&clipboard, String::from("secret"));
+		assert_eq!(*clipboard.contents.borrow(), "secret");
+
+		assert_eq!(tick(&clipboard, generation, 2), Tick::Running);
+		assert_eq!(*clipboard.contents.borrow(), "secret");
+		assert_eq!(tick(&clipboard, generation, 1), Tick::Finished);
+		assert_eq!(*clipboard.contents.borrow(), "");
+	}
+
+	#[test]
+	fn later_copy_cancels_timer_test() {
+		let clipboard = FakeClipboard::default();
+		let first = copy_with(&clipboard, String::from("first"));
+		let second = copy_with(&clipboard, String::from("second"));
+
+		assert_eq!(tick(&clipboard, first, 1), Tick::Cancelled);
+		assert_eq!(*clipboard.contents.borrow(), "second");
+		assert_eq!(tick(&clipboard, second, 1), Tick::Finished);
+		assert_eq!(*clipboard.contents.borrow(), "");
+	}
+
+	#[test]
+	fn lock_without_timeout_test() {
+		let clipboard = FakeClipboard::default();
+		let timed = copy_with(&clipboard, String::from("timed"));
+		// a copy with the timeout set to never starts no timer of its own
+		copy_with(&clipboard, String::from("secret"));
+		assert_eq!(tick(&clipboard, timed, 1), Tick::Cancelled);
+		assert_eq!(*clipboard.contents.borrow(), "secret");
+
+		// locking the app clears it all the same
+		clear_with(&clipboard);
+		assert_eq!(*clipboard.contents.borrow(), "");
+	}
+
+	#[test]
+	fn changed_content_test() {
+		let clipboard = FakeClipboard::default();
+		let generation = copy_with(&clipboard, String::from("secret"));
+		clipboard.set(String::from("copied somewhere else"));
+
+		assert_eq!(tick(&clipboard, generation, 1), Tick::Finished);
+		assert_eq!(*clipboard.contents.borrow(), "copied somewhere else");
+	}
+}
@@ -32,3 +32,4 @@ pub const C_TOOLTIP_BORDER: Color = Color::rgb8(215, 216, 219);
 
 pub const C_ERROR: Color = Color::rgb8(255, 0, 0);
 pub const C_SUCCESS: Color = Color::rgb8(27, 136, 73);
+pub const C_WARNING: Color = Color::rgb8(219, 130, 0);
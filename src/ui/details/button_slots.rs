@@ -17,22 +17,27 @@ use floem::{
 		},
 		empty, svg, Decorators,
 	},
-	Clipboard, IntoView, ViewId,
+	IntoView, ViewId,
 };
 
 use crate::{
 	db::{Db, DbFields},
 	env::Environment,
+	qr::field_payload,
 	ui::{
+		clipboard::copy_to_clipboard,
 		details::detail_view::{
 			save_edit, SaveEdit, SECRET_MULTILINE_PLACEHOLDER, SECRET_PLACEHOLDER,
 		},
-		history_view::history_view,
+		history_view::open_history_window,
 		primitives::{
 			button::{icon_button, IconButton},
 			que::Que,
+			toast::ToastSignals,
 			tooltip::TooltipSignals,
 		},
+		qr_view::qr_view,
+		transfer_view::{transfer_view, TransferView},
 		window_management::{
 			closing_window, make_field_path, opening_window, WindowSpec,
 		},
@@ -196,10 +201,13 @@ pub fn view_button_slot(
 }
 
 pub fn clipboard_button_slot(
+	is_secret: bool,
 	getter: impl Fn() -> String + 'static,
 ) -> impl IntoView {
+	let env = use_context::<Environment>().expect("No env context provider");
 	let tooltip_signals = use_context::<TooltipSignals>()
 		.expect("No tooltip_signals context provider");
+	let toast_signals = use_context::<ToastSignals>();
 
 	let clipboard_icon = include_str!("../icons/clipboard.svg");
 
@@ -211,12 +219,71 @@ pub fn clipboard_button_slot(
 			..IconButton::default()
 		},
 		move |_| {
-			let data = getter();
-			let _ = Clipboard::set_contents(data);
+			let timeout = if is_secret {
+				env.config.get_clipboard_timeout()
+			} else {
+				0
+			};
+			copy_to_clipboard(getter(), timeout, toast_signals);
 		},
 	)
 }
 
+pub struct QrButtonSlot {
+	pub id: usize,
+	pub field: DbFields,
+	pub field_title: String,
+	pub is_shown: bool,
+}
+
+pub fn qr_button_slot(param: QrButtonSlot) -> impl IntoView {
+	let QrButtonSlot {
+		id,
+		field,
+		field_title,
+		is_shown,
+	} = param;
+
+	let env = use_context::<Environment>().expect("No env context provider");
+	let tooltip_signals = use_context::<TooltipSignals>()
+		.expect("No tooltip_signals context provider");
+
+	let qr_icon = include_str!("../icons/qr.svg");
+
+	if is_shown {
+		container(icon_button(
+			IconButton {
+				icon: String::from(qr_icon),
+				tooltip: String::from("Show as QR code"),
+				tooltip_signals,
+				..IconButton::default()
+			},
+			move |_| {
+				let env_qr = env.clone();
+				let field_title = field_title.clone();
+
+				tooltip_signals.hide();
+				// the popup is closed with all other windows when the app locks
+				opening_window(
+					move || {
+						qr_view(field_title.clone(), field_payload(&env_qr.db, &id, &field))
+					},
+					WindowSpec {
+						id: format!("qr-{}", make_field_path(id, &field)),
+						title: String::from("QR code"),
+					},
+					Size::new(290.0, 320.0),
+					false,
+					|| {},
+				);
+			},
+		))
+		.into_any()
+	} else {
+		empty_button_slot().into_any()
+	}
+}
+
 pub struct HistoryButtonSlot {
 	pub id: usize,
 	pub field: DbFields,
@@ -265,30 +332,13 @@ pub fn history_button_slot(param: HistoryButtonSlot) -> impl IntoView {
 			},
 			move |_| {
 				if hide_history_button_visible.get() {
-					let db_history_inner = db_history.clone();
-					let window_title = format!("{} Field History", field_title);
-					let dates_window = dates.get();
-					let que_history = Que::default();
-					let tooltip_signals_history = TooltipSignals::new(que_history);
-
-					opening_window(
-						move || {
-							history_view(
-								id,
-								field,
-								dates_window.clone(),
-								tooltip_signals_history,
-								db_history_inner.clone(),
-							)
-						},
-						WindowSpec {
-							id: make_field_path(id, &field),
-							title: window_title,
-						},
-						Size::new(350.0, 300.0),
-						true,
+					open_history_window(
+						id,
+						field,
+						field_title.clone(),
+						None,
+						db_history.clone(),
 						move || {
-							que_history.unque_all_tooltips();
 							hide_history_button_visible.set(false);
 						},
 					);
@@ -303,6 +353,90 @@ pub fn history_button_slot(param: HistoryButtonSlot) -> impl IntoView {
 	}
 }
 
+pub struct TransferButtonSlot {
+	pub id: usize,
+	pub field: DbFields,
+	pub field_title: String,
+	pub hidden_field_list: RwSignal<im::Vector<DbFields>>,
+	pub field_list: RwSignal<im::Vector<DbFields>>,
+	pub hidden_field_len: RwSignal<usize>,
+	pub is_shown: bool,
+}
+
+pub fn transfer_button_slot(param: TransferButtonSlot) -> impl IntoView {
+	let TransferButtonSlot {
+		id,
+		field,
+		field_title,
+		hidden_field_list,
+		field_list,
+		hidden_field_len,
+		is_shown,
+	} = param;
+
+	let env = use_context::<Environment>().expect("No env context provider");
+	let tooltip_signals = use_context::<TooltipSignals>()
+		.expect("No tooltip_signals context provider");
+
+	let transfer_icon = include_str!("../icons/transfer.svg");
+
+	if is_shown {
+		container(icon_button(
+			IconButton {
+				icon: String::from(transfer_icon),
+				tooltip: String::from("Copy or move to another entry"),
+				tooltip_signals,
+				..IconButton::default()
+			},
+			move |_| {
+				let env_transfer = env.clone();
+				let field_title = field_title.clone();
+				let window_id = format!("transfer-{}", make_field_path(id, &field));
+				let que_transfer = Que::default();
+
+				tooltip_signals.hide();
+				opening_window(
+					move || {
+						let env_move = env_transfer.clone();
+
+						transfer_view(
+							TransferView {
+								id,
+								field,
+								field_title: field_title.clone(),
+								window_id: window_id.clone(),
+								que: que_transfer,
+								env: env_transfer.clone(),
+							},
+							move || {
+								let hidden_field_list_db: im::Vector<DbFields> =
+									env_move.db.get_hidden_fields(&id).into();
+								hidden_field_len.set(hidden_field_list_db.len());
+								hidden_field_list.set(hidden_field_list_db);
+								let field_list_db: im::Vector<DbFields> =
+									env_move.db.get_visible_fields(&id).into();
+								field_list.set(field_list_db);
+							},
+						)
+					},
+					WindowSpec {
+						id: format!("transfer-{}", make_field_path(id, &field)),
+						title: String::from("Copy or move field"),
+					},
+					Size::new(300.0, 150.0),
+					false,
+					move || {
+						que_transfer.unque_all_tooltips();
+					},
+				);
+			},
+		))
+		.into_any()
+	} else {
+		empty_button_slot().into_any()
+	}
+}
+
 pub struct DeleteButtonSlot {
 	pub id: usize,
 	pub field: DbFields,
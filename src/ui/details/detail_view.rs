@@ -2,7 +2,9 @@ use std::rc::Rc;
 use zeroize::Zeroize;
 
 use floem::{
-	event::EventListener,
+	event::{Event, EventListener},
+	keyboard::{KeyCode, PhysicalKey},
+	kurbo::Size,
 	reactive::{
 		create_effect, create_rw_signal, use_context, RwSignal, SignalGet,
 		SignalUpdate,
@@ -23,13 +25,23 @@ use crate::{
 	db::DbFields,
 	env::Environment,
 	ui::{
-		app_view::{PresetFieldSignal, SidebarList},
+		app_view::{ActiveTab, ExpiryList, PresetFieldSignal, SidebarList},
 		details::{
 			hidden_fields::{hidden_fields, HiddeFields},
 			list_item::{list_item, ListItem},
 			new_field::new_field,
 		},
-		primitives::tooltip::TooltipSignals,
+		entry_history_view::entry_history_view,
+		expiry_view::{expiry_view, ExpiryView},
+		generator_rule_view::{generator_rule_view, GeneratorRuleView},
+		keyboard::is_submit,
+		primitives::{
+			button::{icon_button, IconButton},
+			input_field::input_field,
+			que::Que,
+			tooltip::TooltipSignals,
+		},
+		window_management::{opening_window, WindowSpec},
 	},
 };
 
@@ -38,9 +50,9 @@ pub const SECRET_MULTILINE_PLACEHOLDER: &str =
 	"•••••••••••\n•••••••••••••\n••••••\n•••••••••••••\n•••••••••\n•••••••••••\n••••••••••••••••\n••••••••••\n••••••\n••••••••••\n••••";
 pub const INPUT_LINE_WIDTH: f64 = 250.0;
 pub const LABEL_WIDTH: f64 = 142.0;
-pub const LINE_WIDTH: f64 = 590.0;
+pub const LINE_WIDTH: f64 = 654.0;
 pub const MULTILINE_HEIGHT: f64 = 165.0;
-pub const DETAILS_MIN_WIDTH: f64 = 600.0;
+pub const DETAILS_MIN_WIDTH: f64 = 664.0;
 
 pub struct SaveEdit {
 	pub id: usize,
@@ -68,6 +80,8 @@ pub fn save_edit(params: SaveEdit) {
 	let env = use_context::<Environment>().expect("No env context provider");
 	let list_sidebar_signal = use_context::<SidebarList>()
 		.expect("No list_sidebar_signal context provider");
+	let expiry_list =
+		use_context::<ExpiryList>().expect("No expiry_list context provider");
 
 	let field_value = if is_multiline {
 		String::from(doc.text())
@@ -88,6 +102,7 @@ pub fn save_edit(params: SaveEdit) {
 			);
 		}
 
+		expiry_list.set(env.db.get_expiry_list());
 		dates.set(env.db.get_history_dates(&id, &field));
 		input_id.request_focus();
 	}
@@ -112,6 +127,56 @@ pub fn save_edit(params: SaveEdit) {
 	}
 }
 
+fn duplicate_entry(id: usize, with_history: bool) {
+	let env = use_context::<Environment>().expect("No env context provider");
+	let list_sidebar_signal = use_context::<SidebarList>()
+		.expect("No list_sidebar_signal context provider");
+	let active_tab =
+		use_context::<ActiveTab>().expect("No active_tab context provider");
+
+	let new_id = env.db.duplicate_entry(&id, with_history);
+	let _ = env.db.save();
+	list_sidebar_signal.set(env.db.get_sidebar_list());
+	active_tab.set(new_id);
+}
+
+fn save_tags(id: usize, tags_value: RwSignal<String>) {
+	let env = use_context::<Environment>().expect("No env context provider");
+
+	let tags = tags_value.get().split(',').map(String::from).collect();
+	env.db.set_tags(&id, tags);
+	let _ = env.db.save();
+	tags_value.set(env.db.get_tags(&id).join(", "));
+}
+
+fn tags_line(id: usize) -> impl IntoView {
+	let env = use_context::<Environment>().expect("No env context provider");
+	let tags_value = create_rw_signal(env.db.get_tags(&id).join(", "));
+
+	(
+		label(|| "Tags").style(|s| {
+			s.width(LABEL_WIDTH).justify_content(AlignContent::End).flex()
+		}),
+		input_field(tags_value)
+			.placeholder("Comma separated tags")
+			.on_event_cont(EventListener::KeyDown, move |event| {
+				let key = match event {
+					Event::KeyDown(k) => k.key.physical_key,
+					_ => PhysicalKey::Code(KeyCode::F35),
+				};
+
+				if is_submit(key) {
+					save_tags(id, tags_value);
+				}
+			})
+			.on_event_cont(EventListener::FocusLost, move |_| {
+				save_tags(id, tags_value);
+			})
+			.style(|s| s.width(INPUT_LINE_WIDTH).margin_left(5)),
+	)
+		.style(|s| s.items_center().width(LINE_WIDTH).padding_bottom(5))
+}
+
 pub fn detail_view(id: usize, main_scroll_to: RwSignal<f32>) -> impl IntoView {
 	let env = use_context::<Environment>().expect("No env context provider");
 	let tooltip_signals = use_context::<TooltipSignals>()
@@ -124,6 +189,17 @@ pub fn detail_view(id: usize, main_scroll_to: RwSignal<f32>) -> impl IntoView {
 	let is_overflowing = create_rw_signal(false);
 
 	let password_icon = include_str!("../icons/password.svg");
+	let duplicate_icon = include_str!("../icons/duplicate.svg");
+	let duplicate_history_icon = include_str!("../icons/duplicate_history.svg");
+	let history_icon = include_str!("../icons/history.svg");
+	let rules_icon = include_str!("../icons/rules.svg");
+	let expiry_icon = include_str!("../icons/expiry.svg");
+
+	let env_history = env.clone();
+	let env_rules = env.clone();
+	let env_expiry = env.clone();
+	let expiry_list =
+		use_context::<ExpiryList>().expect("No expiry_list context provider");
 
 	let field_list: im::Vector<DbFields> = env.db.get_visible_fields(&id).into();
 	let field_list = create_rw_signal(field_list);
@@ -196,6 +272,135 @@ pub fn detail_view(id: usize, main_scroll_to: RwSignal<f32>) -> impl IntoView {
 				tooltip_signals.hide();
 			})
 			.style(|s| s.text_ellipsis().font_size(24.0).max_width_full()),
+			(
+				icon_button(
+					IconButton {
+						icon: String::from(duplicate_icon),
+						tooltip: String::from("Duplicate entry"),
+						tooltip_signals,
+						..IconButton::default()
+					},
+					move |_| {
+						tooltip_signals.hide();
+						duplicate_entry(id, false);
+					},
+				),
+				icon_button(
+					IconButton {
+						icon: String::from(duplicate_history_icon),
+						tooltip: String::from("Duplicate entry with history"),
+						tooltip_signals,
+						..IconButton::default()
+					},
+					move |_| {
+						tooltip_signals.hide();
+						duplicate_entry(id, true);
+					},
+				),
+				icon_button(
+					IconButton {
+						icon: String::from(history_icon),
+						tooltip: String::from("See history of entry"),
+						tooltip_signals,
+						..IconButton::default()
+					},
+					move |_| {
+						let db_history = env_history.db.clone();
+						let que_history = Que::default();
+						let tooltip_signals_history = TooltipSignals::new(que_history);
+
+						tooltip_signals.hide();
+						opening_window(
+							move || {
+								entry_history_view(
+									id,
+									tooltip_signals_history,
+									db_history.clone(),
+								)
+							},
+							WindowSpec {
+								id: format!("entry-history-{}", id),
+								title: String::from("Entry History"),
+							},
+							Size::new(350.0, 300.0),
+							true,
+							move || {
+								que_history.unque_all_tooltips();
+							},
+						);
+					},
+				),
+				icon_button(
+					IconButton {
+						icon: String::from(rules_icon),
+						tooltip: String::from("Password rules of entry"),
+						tooltip_signals,
+						..IconButton::default()
+					},
+					move |_| {
+						let env_rules = env_rules.clone();
+						let window_id = format!("generator-rules-{}", id);
+						let que_rules = Que::default();
+
+						tooltip_signals.hide();
+						opening_window(
+							move || {
+								generator_rule_view(GeneratorRuleView {
+									id,
+									window_id: window_id.clone(),
+									que: que_rules,
+									env: env_rules.clone(),
+								})
+							},
+							WindowSpec {
+								id: format!("generator-rules-{}", id),
+								title: String::from("Password Rules"),
+							},
+							Size::new(350.0, 430.0),
+							false,
+							move || {
+								que_rules.unque_all_tooltips();
+							},
+						);
+					},
+				),
+				icon_button(
+					IconButton {
+						icon: String::from(expiry_icon),
+						tooltip: String::from("Rotation reminders of entry"),
+						tooltip_signals,
+						..IconButton::default()
+					},
+					move |_| {
+						let env_expiry = env_expiry.clone();
+						let window_id = format!("expiry-{}", id);
+						let que_expiry = Que::default();
+
+						tooltip_signals.hide();
+						opening_window(
+							move || {
+								expiry_view(ExpiryView {
+									id,
+									window_id: window_id.clone(),
+									expiry_list,
+									que: que_expiry,
+									env: env_expiry.clone(),
+								})
+							},
+							WindowSpec {
+								id: format!("expiry-{}", id),
+								title: String::from("Rotation Reminders"),
+							},
+							Size::new(400.0, 300.0),
+							false,
+							move || {
+								que_expiry.unque_all_tooltips();
+							},
+						);
+					},
+				),
+			)
+				.style(|s| s.row_gap(3).margin_left(5)),
 		)
 			.style(|s| {
 				s.flex()
@@ -220,6 +425,7 @@ pub fn detail_view(id: usize, main_scroll_to: RwSignal<f32>) -> impl IntoView {
 				dragger_id: None,
 				field_id: None,
 			}),
+			tags_line(id),
 			dyn_stack(
 				move || sorted_field_list.get(),
 				move |field_id| *field_id,
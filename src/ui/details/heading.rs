@@ -137,6 +137,8 @@ pub fn heading_view(
 		empty_button_slot(),
 		empty_button_slot(),
 		empty_button_slot(),
+		empty_button_slot(),
+		empty_button_slot(),
 		delete_button_slot(DeleteButtonSlot {
 			id,
 			field,
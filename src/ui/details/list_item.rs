@@ -7,7 +7,8 @@ use floem::{
 	event::{Event, EventListener},
 	keyboard::{KeyCode, PhysicalKey},
 	reactive::{
-		create_rw_signal, use_context, RwSignal, SignalGet, SignalUpdate,
+		create_effect, create_rw_signal, use_context, RwSignal, SignalGet,
+		SignalUpdate,
 	},
 	style::{AlignItems, CursorStyle, Display, Position},
 	views::{
@@ -23,14 +24,18 @@ use floem::{
 use crate::{
 	db::{DbFields, DynFieldKind},
 	env::Environment,
-	password_gen::generate_password,
+	password_gen::{
+		generate_password, get_entry_rule, get_profile_entropy, get_rule_entropy,
+	},
 	ui::{
 		colors::*,
 		details::{
 			button_slots::{
 				clipboard_button_slot, delete_button_slot, drag_button_slot,
-				edit_button_slot, history_button_slot, view_button_slot,
-				DeleteButtonSlot, EditButtonSlot, HistoryButtonSlot, ViewButtonSlot,
+				edit_button_slot, history_button_slot, qr_button_slot,
+				transfer_button_slot, view_button_slot, DeleteButtonSlot,
+				EditButtonSlot, HistoryButtonSlot, QrButtonSlot, TransferButtonSlot,
+				ViewButtonSlot,
 			},
 			detail_view::{
 				save_edit, SaveEdit, INPUT_LINE_WIDTH, LINE_WIDTH, MULTILINE_HEIGHT,
@@ -45,7 +50,10 @@ use crate::{
 			input_button_field::{input_button_field, InputButtonField},
 			input_field::input_field,
 			multiline_input_field::multiline_input_field,
+			select::select,
+			strength_meter::strength_meter,
 			styles,
+			toast::ToastSignals,
 			tooltip::TooltipSignals,
 		},
 	},
@@ -91,6 +99,8 @@ pub fn list_item(param: ListItem) -> impl IntoView {
 	let env = use_context::<Environment>().expect("No env context provider");
 	let tooltip_signals = use_context::<TooltipSignals>()
 		.expect("No tooltip_signals context provider");
+	let toast_signals =
+		use_context::<ToastSignals>().expect("No toast_signals context provider");
 
 	let env_order = env.clone();
 
@@ -104,20 +114,42 @@ pub fn list_item(param: ListItem) -> impl IntoView {
 	let generator_entropy_value = create_rw_signal(String::from(""));
 	let generator_entropy_timing = create_rw_signal(Vec::new());
 	let generator_entropy_mouse = create_rw_signal(Vec::new());
+	let generator_profiles = env.config.get_generator_profiles();
+	let generator_profile = create_rw_signal(
+		env
+			.db
+			.get_generator_profile(&id, &field)
+			.filter(|profile| {
+				generator_profiles.iter().any(|item| item.id == *profile)
+			})
+			.unwrap_or(0),
+	);
+	let generated_entropy = create_rw_signal(None);
+
+	create_effect(move |_| {
+		// the entropy only describes the password while it's being edited
+		if !edit_button_switch.get() {
+			generated_entropy.set(None);
+		}
+	});
 
 	let field_title = match field {
 		DbFields::Fields(_) => env.db.get_name_of_field(&id, &field),
 		other => format!("{}", other),
 	};
 	let title_value = create_rw_signal(field_title.clone());
-	let dyn_field_kind = env.db.get_field_kind(&id, &field);
+	let dyn_field_kind = env.db.get_resolved_field_kind(&id, &field);
 	let is_secret = match dyn_field_kind {
 		DynFieldKind::TextLine
 		| DynFieldKind::MultiLine
 		| DynFieldKind::Url
 		| DynFieldKind::Heading => false,
-		DynFieldKind::TextLineSecret | DynFieldKind::MultiLineSecret => true,
+		DynFieldKind::TextLineSecret
+		| DynFieldKind::MultiLineSecret
+		| DynFieldKind::Reference => true,
 	};
+	let is_reference =
+		env.db.get_field_kind(&id, &field) == DynFieldKind::Reference;
 
 	let is_multiline = matches!(
 		dyn_field_kind,
@@ -157,6 +189,10 @@ pub fn list_item(param: ListItem) -> impl IntoView {
 		let env_title = env.clone();
 		let env_view_button = env.clone();
 		let env_history = env.clone();
+		let env_reference = env.clone();
+		let env_generator = env.clone();
+		let env_entropy = env.clone();
+		let env_rule = env.clone();
 
 		let multiline_input = multiline_input_field(field_value.get());
 		let field_doc = create_rw_signal(multiline_input.doc());
@@ -312,14 +348,30 @@ pub fn list_item(param: ListItem) -> impl IntoView {
 								generator_entropy_mouse.get().join(""),
 							);
 
-							let mut pass = generate_password(entropy);
-							field_value.set(pass.clone());
-							field_doc.get().edit_single(
-								Selection::region(0, field_doc.get().text().len()),
-								&format!("{}\n{}\n", field_doc.get().text(), pass),
-								EditType::DeleteSelection,
-							);
-							pass.zeroize();
+							let profile = env_entropy
+								.config
+								.get_generator_profile(generator_profile.get());
+							let rule = get_entry_rule(&env_entropy, &id);
+							match generate_password(entropy, profile.id, rule.as_ref()) {
+								Some(mut pass) => {
+									field_value.set(pass.clone());
+									generated_entropy.set(Some(match &rule {
+										Some(rule) => get_rule_entropy(&profile, rule),
+										None => get_profile_entropy(&profile),
+									}));
+									field_doc.get().edit_single(
+										Selection::region(0, field_doc.get().text().len()),
+										&format!("{}\n{}\n", field_doc.get().text(), pass),
+										EditType::DeleteSelection,
+									);
+									pass.zeroize();
+								},
+								None => {
+									toast_signals.add(String::from(
+										"The password rules of this entry can't be met",
+									));
+								},
+							}
 							generator_entropy_value.set(String::from(""));
 							secret_generator_progress.set(0.0);
 							show_generator_progress.set(false);
@@ -335,7 +387,38 @@ pub fn list_item(param: ListItem) -> impl IntoView {
 							.border(0)
 							.padding(0)
 					}),
-				container("Start typing to generate password")
+				(
+					container(select(
+						generator_profile,
+						generator_profiles
+							.iter()
+							.map(|profile| (profile.id, profile.name.clone()))
+							.collect(),
+						move |profile| {
+							// only write to the database when the choice changed
+							if env_generator
+								.db
+								.get_generator_profile(&id, &field)
+								.unwrap_or(0)
+								!= profile
+							{
+								env_generator.db.set_generator_profile(&id, &field, profile);
+								let _ = env_generator.db.save();
+							}
+						},
+					))
+					.style(|s| s.width(120)),
+					label(move || {
+						// rules can change while the field is open
+						if show_generator_progress.get()
+							&& get_entry_rule(&env_rule, &id).is_some()
+						{
+							"Type to generate, rules apply"
+						} else {
+							"Start typing to generate"
+						}
+					}),
+				)
 					.on_click_stop(move |_| {
 						generator_input_id.request_focus();
 					})
@@ -349,6 +432,7 @@ pub fn list_item(param: ListItem) -> impl IntoView {
 							.background(C_MAIN_BG_INACTIVE.with_alpha_factor(0.9))
 							.items_center()
 							.justify_center()
+							.row_gap(10)
 							.apply_if(is_multiline, |s| {
 								s.height(MULTILINE_HEIGHT).inset_top(
 									((MULTILINE_HEIGHT / 2.0) * -1.0) + BUTTON_WIDTH / 2.0,
@@ -393,6 +477,17 @@ pub fn list_item(param: ListItem) -> impl IntoView {
 			input.style(move |s| {
 				s.flex_grow(1.0).apply_if(is_multiline, |s| s.height(MULTILINE_HEIGHT))
 			}),
+			label(move || {
+				format!("{:.0} bits", generated_entropy.get().unwrap_or_default())
+			})
+			.style(move |s| {
+				s.font_size(10)
+					.color(C_TOP_TEXT)
+					.selectable(false)
+					.apply_if(generated_entropy.get().is_none(), |s| {
+						s.display(Display::None)
+					})
+			}),
 			generate_slot,
 		)
 			.style(move |s| {
@@ -405,6 +500,12 @@ pub fn list_item(param: ListItem) -> impl IntoView {
 					.apply_if(edit_button_switch.get(), |s| s.display(Display::Flex))
 			});
 
+		let strength_slot = if is_secret && !is_multiline && !is_reference {
+			strength_meter(field_value).into_any()
+		} else {
+			empty().into_any()
+		};
+
 		(
 			dyn_field_title_form(
 				DynFieldTitleForm {
@@ -435,7 +536,12 @@ pub fn list_item(param: ListItem) -> impl IntoView {
 				},
 			),
 			(
-				input_line,
+				(input_line, strength_slot).style(move |s| {
+					s.flex_col()
+						.column_gap(2)
+						.display(Display::None)
+						.apply_if(edit_button_switch.get(), |s| s.display(Display::Flex))
+				}),
 				scroll(
 					label(move || replace_consecutive_newlines(field_value.get())).style(
 						|s| s.padding_bottom(3).font_family(String::from("Monospace")),
@@ -464,6 +570,23 @@ pub fn list_item(param: ListItem) -> impl IntoView {
 							&field_value.get(),
 						));
 					}
+				})
+				.on_event_cont(EventListener::PointerEnter, move |_| {
+					if is_reference {
+						tooltip_signals.show(
+							match env_reference.db.get_reference_source(&id, &field) {
+								Some((entry_title, field_title)) => {
+									format!("Linked to \"{}\" › \"{}\"", entry_title, field_title)
+								},
+								None => String::from("Dangling reference: the source is gone"),
+							},
+						);
+					}
+				})
+				.on_event_cont(EventListener::PointerLeave, move |_| {
+					if is_reference {
+						tooltip_signals.hide();
+					}
 				}),
 			)
 				.style(|s| s.width(INPUT_LINE_WIDTH)),
@@ -481,7 +604,15 @@ pub fn list_item(param: ListItem) -> impl IntoView {
 				reset_text,
 				view_button_switch,
 			}),
-			clipboard_button_slot(move || env.db.get_last_by_field(&id, &field)),
+			clipboard_button_slot(is_secret, move || {
+				env.db.get_last_by_field(&id, &field)
+			}),
+			qr_button_slot(QrButtonSlot {
+				id,
+				field,
+				field_title: field_title.clone(),
+				is_shown: is_dyn_field && !is_hidden,
+			}),
 			view_button_slot(
 				ViewButtonSlot {
 					switch: view_button_switch,
@@ -500,9 +631,18 @@ pub fn list_item(param: ListItem) -> impl IntoView {
 				field,
 				dates,
 				is_shown: !matches!(field, DbFields::Title),
-				field_title,
+				field_title: field_title.clone(),
 				db: env_history.db,
 			}),
+			transfer_button_slot(TransferButtonSlot {
+				id,
+				field,
+				field_title,
+				hidden_field_list,
+				field_list,
+				hidden_field_len,
+				is_shown: is_dyn_field,
+			}),
 			delete_button_slot(DeleteButtonSlot {
 				id,
 				field,
@@ -8,14 +8,15 @@ use floem::{
 	},
 	style::{AlignItems, Display},
 	views::{
-		container, dyn_container, editor::text::Document, text_editor, Decorators,
+		container, dyn_container, editor::text::Document, empty, text_editor,
+		Decorators,
 	},
 	IntoView, View,
 };
 
 use crate::{
 	config::PresetFields,
-	db::{DbFields, DynFieldKind},
+	db::{parse_reference, DbFields, DynFieldKind},
 	env::Environment,
 	ui::{
 		keyboard::is_submit,
@@ -24,6 +25,7 @@ use crate::{
 			input_field::input_field,
 			multiline_input_field::multiline_input_field,
 			select::select,
+			strength_meter::strength_meter,
 			styles,
 			tooltip::TooltipSignals,
 		},
@@ -61,7 +63,8 @@ fn save_new_field(params: SaveNewField) {
 		DynFieldKind::Url
 		| DynFieldKind::Heading
 		| DynFieldKind::TextLine
-		| DynFieldKind::TextLineSecret => field_value.get(),
+		| DynFieldKind::TextLineSecret
+		| DynFieldKind::Reference => field_value.get(),
 		DynFieldKind::MultiLine | DynFieldKind::MultiLineSecret => {
 			String::from(multiline_field_value.get().text())
 		},
@@ -88,6 +91,7 @@ pub fn new_field(
 	field_list: RwSignal<im::Vector<DbFields>>,
 	main_scroll_to: RwSignal<f32>,
 ) -> impl IntoView {
+	let env = use_context::<Environment>().expect("No env context provider");
 	let tooltip_signals = use_context::<TooltipSignals>()
 		.expect("No tooltip_signals context provider");
 
@@ -97,6 +101,7 @@ pub fn new_field(
 	let field_value = create_rw_signal(String::from(""));
 	let kind = create_rw_signal(DynFieldKind::default());
 	let kind_signal = create_rw_signal(0);
+	let reference_value = create_rw_signal(0);
 	let multiline_doc = create_rw_signal(text_editor("").doc());
 
 	let add_icon = include_str!("../icons/add.svg");
@@ -208,37 +213,45 @@ pub fn new_field(
 						DynFieldKind::Url
 						| DynFieldKind::Heading
 						| DynFieldKind::TextLine
-						| DynFieldKind::TextLineSecret => input_field(field_value)
-							.placeholder("Value of field")
-							.style(move |s| s.width(177))
-							.on_event_cont(EventListener::KeyDown, move |event| {
-								let key = match event {
-									Event::KeyDown(k) => k.key.physical_key,
-									_ => PhysicalKey::Code(KeyCode::F35),
-								};
+						| DynFieldKind::TextLineSecret => (
+							input_field(field_value)
+								.placeholder("Value of field")
+								.style(move |s| s.width(177))
+								.on_event_cont(EventListener::KeyDown, move |event| {
+									let key = match event {
+										Event::KeyDown(k) => k.key.physical_key,
+										_ => PhysicalKey::Code(KeyCode::F35),
+									};
 
-								if key == PhysicalKey::Code(KeyCode::Escape) {
-									field_value.set(String::from(""));
-									show_minus_button.set(false);
-								}
+									if key == PhysicalKey::Code(KeyCode::Escape) {
+										field_value.set(String::from(""));
+										show_minus_button.set(false);
+									}
 
-								if is_submit(key) {
-									let selected_kind = DynFieldKind::all_values()
-										.into_iter()
-										.nth(kind_signal)
-										.unwrap_or_default();
-									save_new_field(SaveNewField {
-										id,
-										kind: create_rw_signal(selected_kind),
-										preset_value,
-										title_value,
-										field_value,
-										multiline_field_value: multiline_doc,
-										field_list,
-									});
-									title_input_id.request_focus();
-								}
-							})
+									if is_submit(key) {
+										let selected_kind = DynFieldKind::all_values()
+											.into_iter()
+											.nth(kind_signal)
+											.unwrap_or_default();
+										save_new_field(SaveNewField {
+											id,
+											kind: create_rw_signal(selected_kind),
+											preset_value,
+											title_value,
+											field_value,
+											multiline_field_value: multiline_doc,
+											field_list,
+										});
+										title_input_id.request_focus();
+									}
+								}),
+							if selected_kind == DynFieldKind::TextLineSecret {
+								strength_meter(field_value).into_any()
+							} else {
+								empty().into_any()
+							},
+						)
+							.style(|s| s.flex_col().column_gap(2))
 							.into_any(),
 						DynFieldKind::MultiLine | DynFieldKind::MultiLineSecret => {
 							let multiline_input = multiline_input_field(String::from(""))
@@ -249,6 +262,32 @@ pub fn new_field(
 								.style(|s| s.width(177).height(150))
 								.into_any()
 						},
+						DynFieldKind::Reference => {
+							let targets = env.db.get_reference_targets();
+							let options = targets
+								.iter()
+								.enumerate()
+								.map(|(idx, (label, _))| (idx, label.clone()))
+								.collect();
+							reference_value.set(0);
+							field_value.set(
+								targets
+									.first()
+									.map(|(_, reference)| reference.clone())
+									.unwrap_or_default(),
+							);
+
+							select(reference_value, options, move |idx| {
+								field_value.set(
+									targets
+										.get(idx)
+										.map(|(_, reference)| reference.clone())
+										.unwrap_or_default(),
+								);
+							})
+							.style(|s| s.width(177))
+							.into_any()
+						},
 					}
 				},
 			)
@@ -267,12 +306,19 @@ pub fn new_field(
 			select(
 				kind_signal,
 				DynFieldKind::all_values().into_iter().enumerate().collect(),
-				move |_| {
+				move |id| {
 					// println!(
 					// 	"changing kind: {:?} and {:?}",
 					// 	field_value.get(),
 					// 	multiline_doc.get().text()
 					// );
+					let selected_kind =
+						DynFieldKind::all_values().into_iter().nth(id).unwrap_or_default();
+					if selected_kind != DynFieldKind::Reference
+						&& parse_reference(&field_value.get()).is_some()
+					{
+						field_value.set(String::from(""));
+					}
 				},
 			),
 			icon_button(
@@ -0,0 +1,126 @@
+use chrono::{DateTime, Local, Utc};
+use std::sync::Arc;
+
+use floem::{
+	event::{Event, EventListener},
+	reactive::{create_rw_signal, provide_context, SignalGet, SignalUpdate},
+	style::Display,
+	views::{
+		label, scroll, virtual_stack, Decorators, VirtualDirection, VirtualItemSize,
+	},
+	IntoView, View,
+};
+
+use crate::{
+	db::Db,
+	ui::{
+		colors::*,
+		primitives::tooltip::{tooltip_view, TooltipSignals},
+	},
+};
+
+const HISTORY_LINE_HEIGHT: f64 = 31.0;
+const PADDING: f64 = 10.0;
+
+fn entry_history_line(
+	idx: usize,
+	date: u64,
+	text: String,
+	tooltip_signals: TooltipSignals,
+) -> impl IntoView {
+	let datetime_utc: DateTime<Utc> =
+		DateTime::from_timestamp(date as i64, 0).unwrap_or_default();
+	let datetime_local: DateTime<Local> = datetime_utc.with_timezone(&Local);
+
+	(
+		datetime_local
+			.format("%v")
+			.to_string()
+			.style(|s| s.color(C_SIDE_TEXT_INACTIVE).font_size(9.0).min_width(60))
+			.on_event_cont(EventListener::PointerEnter, move |_| {
+				tooltip_signals.show(datetime_local.to_rfc2822());
+			})
+			.on_event_cont(EventListener::PointerLeave, move |_| {
+				tooltip_signals.hide();
+			}),
+		label(move || text.clone())
+			.style(|s| s.flex_grow(1.0).width(80).text_ellipsis()),
+	)
+		.style(move |s| {
+			s.flex()
+				.flex_row()
+				.width_full()
+				.max_width_full()
+				.height(HISTORY_LINE_HEIGHT)
+				.row_gap(4)
+				.padding_horiz(PADDING)
+				.items_center()
+				.background(if let 0 = idx % 2 {
+					C_SIDE_BG
+				} else {
+					C_SIDE_BG_SELECTED.with_alpha_factor(0.2)
+				})
+		})
+}
+
+pub fn entry_history_view(
+	id: usize,
+	tooltip_signals: TooltipSignals,
+	db: Arc<Db>,
+) -> impl IntoView {
+	provide_context(tooltip_signals);
+
+	let history_list: im::Vector<(usize, (u64, String))> =
+		db.get_entry_history(&id).into_iter().enumerate().collect();
+	let history_list = create_rw_signal(history_list);
+
+	let entry_history_view = (
+		scroll(
+			virtual_stack(
+				VirtualDirection::Vertical,
+				VirtualItemSize::Fixed(Box::new(|| HISTORY_LINE_HEIGHT)),
+				move || history_list.get(),
+				move |(idx, _)| *idx,
+				move |(idx, (date, text))| {
+					entry_history_line(idx, date, text, tooltip_signals)
+				},
+			)
+			.style(|s| s.flex_col().flex_grow(1.0)),
+		)
+		.style(|s| s.width_full().height_full()),
+		label(|| "No history recorded for this entry yet").style(move |s| {
+			s.padding(PADDING)
+				.color(C_MAIN_TEXT_INACTIVE)
+				.apply_if(!history_list.get().is_empty(), |s| s.display(Display::None))
+		}),
+		tooltip_view(tooltip_signals),
+	)
+		.style(|s| s.flex_col().width_full().height_full())
+		.on_event_cont(EventListener::PointerMove, move |event| {
+			let pos = match event {
+				Event::PointerMove(p) => p.pos,
+				_ => (0.0, 0.0).into(),
+			};
+			tooltip_signals.mouse_pos.set((pos.x, pos.y));
+		})
+		.on_resize(move |event| {
+			tooltip_signals.window_size.set((event.x1, event.y1));
+		});
+
+	match std::env::var("DEBUG") {
+		Ok(_) => {
+			// for debugging the layout
+			let id = entry_history_view.id();
+			entry_history_view.on_event_stop(EventListener::KeyUp, move |e| {
+				if let floem::event::Event::KeyUp(e) = e {
+					if e.key.logical_key
+						== floem::keyboard::Key::Named(floem::keyboard::NamedKey::F11)
+					{
+						id.inspect();
+					}
+				}
+			})
+		},
+		Err(_) => entry_history_view,
+	}
+}
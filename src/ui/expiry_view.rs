@@ -0,0 +1,154 @@
+use floem::{
+	event::{Event, EventListener},
+	reactive::{
+		create_rw_signal, provide_context, RwSignal, SignalGet, SignalUpdate,
+	},
+	style::Display,
+	views::{container, label, v_stack_from_iter, Decorators},
+	IntoView, View,
+};
+
+use crate::{
+	db::DbFields,
+	env::Environment,
+	expiry::{format_date, parse_expiry, Expiry},
+	ui::{
+		app_view::ExpiryList,
+		colors::*,
+		primitives::{
+			button::button,
+			input_field::input_field,
+			que::Que,
+			tooltip::{tooltip_view, TooltipSignals},
+		},
+		window_management::closing_window,
+	},
+};
+
+pub struct ExpiryView {
+	pub id: usize,
+	pub window_id: String,
+	pub expiry_list: ExpiryList,
+	pub que: Que,
+	pub env: Environment,
+}
+
+pub fn expiry_view(param: ExpiryView) -> impl IntoView {
+	let ExpiryView {
+		id,
+		window_id,
+		expiry_list,
+		que,
+		env,
+	} = param;
+
+	let tooltip_signals = TooltipSignals::new(que);
+	provide_context(tooltip_signals);
+
+	let error = create_rw_signal(String::new());
+
+	let mut fields = vec![(DbFields::Title, String::from("Whole entry"))];
+	fields.extend(
+		env
+			.db
+			.get_visible_fields(&id)
+			.into_iter()
+			.map(|field| (field, env.db.get_name_of_field(&id, &field))),
+	);
+	let lines = fields
+		.into_iter()
+		.map(|(field, title)| {
+			let value = create_rw_signal(
+				env
+					.db
+					.get_expiry(&id, &field)
+					.map(|expiry| expiry.to_string())
+					.unwrap_or_default(),
+			);
+			let due = env
+				.db
+				.get_due_date(&id, &field)
+				.map(|date| format!("due {}", format_date(date)))
+				.unwrap_or_default();
+			(field, title, value, due)
+		})
+		.collect::<Vec<(DbFields, String, RwSignal<String>, String)>>();
+	let values = lines
+		.iter()
+		.map(|(field, _, value, _)| (*field, *value))
+		.collect::<Vec<(DbFields, RwSignal<String>)>>();
+
+	let expiry_view = (
+		label(|| "Rotate every number of days (90d) or by a date (2030-12-31)")
+			.style(|s| s.color(C_MAIN_TEXT_INACTIVE).font_size(10.0)),
+		v_stack_from_iter(lines.into_iter().map(|(_, title, value, due)| {
+			(
+				label(move || title.clone()).style(|s| s.width(110).text_ellipsis()),
+				input_field(value).placeholder("No expiry").style(|s| s.width(90)),
+				label(move || due.clone())
+					.style(|s| s.color(C_MAIN_TEXT_INACTIVE).font_size(10.0)),
+			)
+				.style(|s| s.items_center().row_gap(5))
+		}))
+		.style(|s| s.column_gap(5)),
+		label(move || error.get()).style(move |s| {
+			s.color(C_ERROR)
+				.apply_if(error.get().is_empty(), |s| s.display(Display::None))
+		}),
+		container(button("Save").on_click_cont(move |_| {
+			let mut parsed: Vec<(DbFields, Option<Expiry>)> = Vec::new();
+			for (field, value) in &values {
+				match parse_expiry(&value.get()) {
+					Ok(expiry) => parsed.push((*field, expiry)),
+					Err(parse_error) => {
+						error.set(parse_error.to_string());
+						return;
+					},
+				}
+			}
+
+			for (field, expiry) in parsed {
+				env.db.set_expiry(&id, &field, expiry);
+			}
+			let _ = env.db.save();
+			expiry_list.set(env.db.get_expiry_list());
+			closing_window(window_id.clone(), || ());
+		})),
+		tooltip_view(tooltip_signals),
+	)
+		.style(|s| {
+			s.flex_col()
+				.width_full()
+				.height_full()
+				.padding(10)
+				.column_gap(10)
+				.background(C_MAIN_BG)
+		})
+		.on_event_cont(EventListener::PointerMove, move |event| {
+			let pos = match event {
+				Event::PointerMove(p) => p.pos,
+				_ => (0.0, 0.0).into(),
+			};
+			tooltip_signals.mouse_pos.set((pos.x, pos.y));
+		})
+		.on_resize(move |event| {
+			tooltip_signals.window_size.set((event.x1, event.y1));
+		});
+
+	match std::env::var("DEBUG") {
+		Ok(_) => {
+			// for debugging the layout
+			let id = expiry_view.id();
+			expiry_view.on_event_stop(EventListener::KeyUp, move |e| {
+				if let floem::event::Event::KeyUp(e) = e {
+					if e.key.logical_key
+						== floem::keyboard::Key::Named(floem::keyboard::NamedKey::F11)
+					{
+						id.inspect();
+					}
+				}
+			})
+		},
+		Err(_) => expiry_view,
+	}
+}
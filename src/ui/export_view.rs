@@ -0,0 +1,440 @@
+use chrono::Utc;
+use std::fs;
+use zeroize::Zeroize;
+
+use floem::{
+	event::{Event, EventListener},
+	file::FileDialogOptions,
+	file_action::save_as,
+	reactive::{
+		create_rw_signal, provide_context, RwSignal, SignalGet, SignalUpdate,
+	},
+	style::{CursorStyle, Display, Position},
+	views::{
+		container, label, scroll, v_stack_from_iter, virtual_stack, Decorators,
+		VirtualDirection, VirtualItemSize,
+	},
+	IntoView, View,
+};
+
+use crate::{
+	env::Environment,
+	export::{emergency_kit, plaintext::write_private},
+	ui::{
+		colors::*,
+		keyboard::is_submit,
+		primitives::{
+			button::button,
+			checkbox::checkbox,
+			password_field::password_field,
+			que::Que,
+			styles,
+			tooltip::{tooltip_view, TooltipSignals},
+		},
+		window_management::closing_window,
+	},
+};
+
+const TOP_HEIGHT: f32 = 50.0;
+const BOTTOM_HEIGHT: f32 = 110.0;
+
+// what the selected entries are exported as
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExportKind {
+	// a vault file of their own with a separate password
+	Encrypted,
+	// a printable html page with the secrets in plain text
+	EmergencyKit,
+}
+
+impl ExportKind {
+	pub fn window_id(&self) -> String {
+		match self {
+			ExportKind::Encrypted => String::from("export-window"),
+			ExportKind::EmergencyKit => String::from("emergency-kit-window"),
+		}
+	}
+}
+
+// PRIVATE: selecting a tag selects all entries that carry it
+fn tag_line(
+	tag: String,
+	ids: Vec<usize>,
+	export_items: RwSignal<im::Vector<(usize, bool)>>,
+) -> impl IntoView {
+	let ids_checkbox = ids.clone();
+	let is_selected = move || {
+		!ids_checkbox.is_empty()
+			&& ids_checkbox.iter().all(|id| export_items.get().contains(&(*id, true)))
+	};
+	let is_selected_click = is_selected.clone();
+	let update_items = move |state: bool| {
+		export_items.update(|items| {
+			items
+				.iter_mut()
+				.filter(|item| ids.contains(&item.0))
+				.for_each(|item| item.1 = state);
+		});
+	};
+	let update_items_click = update_items.clone();
+
+	(
+		checkbox(is_selected).on_update(update_items),
+		label(move || format!("#{}", tag))
+			.style(|s| s.cursor(CursorStyle::Pointer).selectable(false))
+			.on_click_cont(move |_| {
+				update_items_click(!is_selected_click());
+			}),
+	)
+		.style(|s| s.height(30).padding_left(10).items_center().row_gap(10))
+}
+
+fn export_line(
+	item: (usize, bool),
+	title: String,
+	export_items: RwSignal<im::Vector<(usize, bool)>>,
+) -> impl IntoView {
+	let update_checkbox = move |id, state| {
+		export_items.update(|items| {
+			if let Some(index) = items.iter().position(|&x| x.0 == id) {
+				items[index].1 = state;
+			}
+		});
+	};
+
+	(
+		checkbox(move || item.1).on_update(move |state| {
+			update_checkbox(item.0, state);
+		}),
+		label(move || title.clone())
+			.style(|s| {
+				s.text_ellipsis()
+					.flex_grow(1.0)
+					.min_width(0)
+					.cursor(CursorStyle::Pointer)
+					.selectable(false)
+			})
+			.on_click_cont(move |_| {
+				update_checkbox(item.0, !item.1);
+			}),
+	)
+		.style(|s| {
+			s.height(30).padding_left(10).width_full().items_center().row_gap(10)
+		})
+}
+
+// PRIVATE: the kit is password-gated like the other plaintext exports
+fn export_emergency_kit(
+	ids: Vec<usize>,
+	password: RwSignal<String>,
+	error: RwSignal<String>,
+	env: Environment,
+) {
+	let is_correct = env.db.check_password(password.get());
+	password.update(|pass| pass.zeroize());
+	if !is_correct {
+		error.set(String::from("Wrong password"));
+		return;
+	}
+	error.set(String::new());
+
+	save_as(
+		FileDialogOptions::new()
+			.default_name("vault-emergency-kit.html")
+			.title("Save emergency kit"),
+		move |file_info| {
+			if let Some(file) = file_info {
+				let mut html = emergency_kit::to_html(
+					&env.db,
+					&ids,
+					&env.config.general.read().db_path,
+					Utc::now().timestamp() as u64,
+				);
+				let result = write_private(&file.path[0], &html);
+				html.zeroize();
+				match result {
+					Ok(()) => {
+						closing_window(ExportKind::EmergencyKit.window_id(), || ());
+					},
+					Err(err) => {
+						error.set(err.to_string());
+					},
+				}
+			}
+		},
+	);
+}
+
+// PRIVATE: check the selection and the password before asking where to save
+fn export(
+	kind: ExportKind,
+	export_items: RwSignal<im::Vector<(usize, bool)>>,
+	password: RwSignal<String>,
+	password_repeat: RwSignal<String>,
+	error: RwSignal<String>,
+	env: Environment,
+) {
+	let ids = export_items
+		.get()
+		.iter()
+		.filter(|(_, is_selected)| *is_selected)
+		.map(|(id, _)| *id)
+		.collect::<Vec<usize>>();
+
+	if ids.is_empty() {
+		error.set(String::from("Select at least one entry to export"));
+	} else if kind == ExportKind::EmergencyKit {
+		export_emergency_kit(ids, password, error, env);
+	} else if password.get().is_empty() {
+		error.set(String::from("Enter a password for the export"));
+	} else if password.get() != password_repeat.get() {
+		error.set(String::from("The passwords don't match"));
+	} else {
+		error.set(String::new());
+		save_as(
+			FileDialogOptions::new()
+				.default_name("vault-export.backup")
+				.title("Save export file"),
+			move |file_info| {
+				if let Some(file) = file_info {
+					let result = env
+						.db
+						.export_entries(&ids, password.get())
+						.and_then(|content| Ok(fs::write(&file.path[0], content)?));
+					match result {
+						Ok(()) => {
+							password.set(String::new());
+							password_repeat.set(String::new());
+							closing_window(ExportKind::Encrypted.window_id(), || ());
+						},
+						Err(err) => {
+							error.set(err.to_string());
+						},
+					}
+				}
+			},
+		);
+	}
+}
+
+pub fn export_view(
+	que: Que,
+	kind: ExportKind,
+	env: Environment,
+) -> impl IntoView {
+	let tooltip_signals = TooltipSignals::new(que);
+	provide_context(tooltip_signals);
+
+	let select_all = create_rw_signal(false);
+	let password = create_rw_signal(String::new());
+	let password_repeat = create_rw_signal(String::new());
+	let error = create_rw_signal(String::new());
+
+	let entries = env.db.get_sidebar_list();
+	let export_items = create_rw_signal(
+		entries
+			.iter()
+			.map(|(id, _, _)| (*id, false))
+			.collect::<im::Vector<(usize, bool)>>(),
+	);
+	let tags = env
+		.db
+		.get_all_tags()
+		.into_iter()
+		.map(|tag| {
+			let ids = entries
+				.iter()
+				.filter(|(id, _, _)| {
+					env
+						.db
+						.get_tags(id)
+						.iter()
+						.any(|item| item.to_lowercase() == tag.to_lowercase())
+				})
+				.map(|(id, _, _)| *id)
+				.collect::<Vec<usize>>();
+			(tag, ids)
+		})
+		.collect::<Vec<(String, Vec<usize>)>>();
+	let has_tags = !tags.is_empty();
+	let is_kit = kind == ExportKind::EmergencyKit;
+
+	let env_enter = env.clone();
+	let env_click = env.clone();
+
+	let export_view = (
+		(
+			label(move || if is_kit { "Emergency kit" } else { "Exporting" })
+				.style(|s| s.font_size(21.0).margin_bottom(3)),
+			container(
+				label(move || {
+					format!(
+						"{} selected",
+						export_items.get().iter().filter(|&(_, b)| *b).count()
+					)
+				})
+				.style(|s| s.color(C_MAIN_TEXT_INACTIVE)),
+			)
+			.style(|s| s.width_full().justify_end()),
+		)
+			.style(|s| {
+				s.height(TOP_HEIGHT)
+					.row_gap(5)
+					.padding(5)
+					.items_center()
+					.justify_center()
+					.border_color(C_TOP_BG_BORDER)
+					.border_bottom(1)
+			}),
+		scroll(
+			(
+				container(
+					label(move || {
+						if select_all.get() {
+							String::from("Deselect all")
+						} else {
+							String::from("Select all")
+						}
+					})
+					.on_click_cont(move |_| {
+						export_items.update(|items| {
+							items.iter_mut().for_each(|item| item.1 = !select_all.get());
+						});
+						select_all.set(!select_all.get());
+					})
+					.style(styles::button)
+					.style(|s| s.selectable(false)),
+				)
+				.style(|s| s.margin_left(10).margin_top(10)),
+				"Tags".style(move |s| {
+					s.margin_left(10)
+						.margin_top(5)
+						.color(C_MAIN_TEXT_INACTIVE)
+						.apply_if(!has_tags, |s| s.display(Display::None))
+				}),
+				v_stack_from_iter(
+					tags.into_iter().map(|(tag, ids)| tag_line(tag, ids, export_items)),
+				),
+				"Entries".style(|s| {
+					s.margin_left(10).margin_top(5).color(C_MAIN_TEXT_INACTIVE)
+				}),
+				virtual_stack(
+					VirtualDirection::Vertical,
+					VirtualItemSize::Fixed(Box::new(|| 30.0)),
+					move || export_items.get(),
+					move |item| *item,
+					move |item| {
+						let title = entries
+							.iter()
+							.find(|(id, _, _)| *id == item.0)
+							.map(|(_, title, _)| title.clone())
+							.unwrap_or_default();
+						export_line(item, title, export_items)
+					},
+				)
+				.style(|s| s.width_full().margin_bottom(10)),
+			)
+				.style(|s| s.flex_col().width_full().column_gap(5)),
+		)
+		.style(|s| {
+			s.width_full()
+				.position(Position::Absolute)
+				.inset_top(TOP_HEIGHT)
+				.inset_bottom(BOTTOM_HEIGHT)
+				.min_width(0)
+		}),
+		// the export gets its own password so the vault password is never shared,
+		// the kit asks for the vault password instead as it isn't encrypted
+		(
+			label(|| "The kit holds the selected secrets unencrypted").style(
+				move |s| {
+					s.color(C_ERROR).apply_if(!is_kit, |s| s.display(Display::None))
+				},
+			),
+			password_field(password, "Enter a password for the export").style(
+				move |s| s.width_full().apply_if(is_kit, |s| s.display(Display::None)),
+			),
+			password_field(
+				if is_kit { password } else { password_repeat },
+				if is_kit {
+					"Enter your master password"
+				} else {
+					"Repeat the password"
+				},
+			)
+			.on_event_cont(EventListener::KeyDown, move |event| {
+				if let Event::KeyDown(key) = event {
+					if is_submit(key.key.physical_key) {
+						export(
+							kind,
+							export_items,
+							password,
+							password_repeat,
+							error,
+							env_enter.clone(),
+						);
+					}
+				}
+			})
+			.style(|s| s.width_full()),
+			(
+				label(move || error.get()).style(|s| {
+					s.color(C_ERROR).flex_grow(1.0).min_width(0).text_ellipsis()
+				}),
+				button(if is_kit { "Create kit" } else { "Export" }).on_click_cont(
+					move |_| {
+						export(
+							kind,
+							export_items,
+							password,
+							password_repeat,
+							error,
+							env_click.clone(),
+						);
+					},
+				),
+			)
+				.style(|s| s.items_center().row_gap(5)),
+		)
+			.style(|s| {
+				s.flex_col()
+					.column_gap(5)
+					.padding(10)
+					.width_full()
+					.height(BOTTOM_HEIGHT)
+					.position(Position::Absolute)
+					.inset_bottom(0)
+					.border_color(C_TOP_BG_BORDER)
+					.border_top(1)
+			}),
+		tooltip_view(tooltip_signals),
+	)
+		.style(|s| s.flex_col().flex().width_full().height_full())
+		.on_event_cont(EventListener::PointerMove, move |event| {
+			let pos = match event {
+				Event::PointerMove(p) => p.pos,
+				_ => (0.0, 0.0).into(),
+			};
+			tooltip_signals.mouse_pos.set((pos.x, pos.y));
+		})
+		.on_resize(move |event| {
+			tooltip_signals.window_size.set((event.x1, event.y1));
+		});
+
+	match std::env::var("DEBUG") {
+		Ok(_) => {
+			// for debugging the layout
+			let id = export_view.id();
+			export_view.on_event_stop(EventListener::KeyUp, move |e| {
+				if let floem::event::Event::KeyUp(e) = e {
+					if e.key.logical_key
+						== floem::keyboard::Key::Named(floem::keyboard::NamedKey::F11)
+					{
+						id.inspect();
+					}
+				}
+			})
+		},
+		Err(_) => export_view,
+	}
+}
@@ -0,0 +1,305 @@
+use floem::{
+	event::{Event, EventListener},
+	reactive::{
+		create_rw_signal, provide_context, RwSignal, SignalGet, SignalUpdate,
+	},
+	style::Display,
+	views::{container, dyn_container, label, Decorators},
+	IntoView, View,
+};
+
+use crate::{
+	config::{GeneratorRule, GeneratorRulePreset},
+	env::Environment,
+	password_gen::{get_entry_rule, get_host},
+	ui::{
+		colors::*,
+		primitives::{
+			button::button,
+			input_field::input_field,
+			que::Que,
+			select::select,
+			tooltip::{tooltip_view, TooltipSignals},
+		},
+		settings::generator::option_line,
+		window_management::closing_window,
+	},
+};
+
+pub struct GeneratorRuleView {
+	pub id: usize,
+	pub window_id: String,
+	pub que: Que,
+	pub env: Environment,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct RuleDraft {
+	min_length: RwSignal<String>,
+	max_length: RwSignal<String>,
+	require_upper: RwSignal<bool>,
+	require_lower: RwSignal<bool>,
+	require_digits: RwSignal<bool>,
+	require_symbols: RwSignal<bool>,
+	forbidden: RwSignal<String>,
+	no_repeats: RwSignal<bool>,
+}
+
+impl RuleDraft {
+	fn new(rule: GeneratorRule) -> Self {
+		RuleDraft {
+			min_length: create_rw_signal(rule.min_length.to_string()),
+			max_length: create_rw_signal(rule.max_length.to_string()),
+			require_upper: create_rw_signal(rule.require_upper),
+			require_lower: create_rw_signal(rule.require_lower),
+			require_digits: create_rw_signal(rule.require_digits),
+			require_symbols: create_rw_signal(rule.require_symbols),
+			forbidden: create_rw_signal(rule.forbidden),
+			no_repeats: create_rw_signal(rule.no_repeats),
+		}
+	}
+
+	fn load(&self, rule: GeneratorRule) {
+		self.min_length.set(rule.min_length.to_string());
+		self.max_length.set(rule.max_length.to_string());
+		self.require_upper.set(rule.require_upper);
+		self.require_lower.set(rule.require_lower);
+		self.require_digits.set(rule.require_digits);
+		self.require_symbols.set(rule.require_symbols);
+		self.forbidden.set(rule.forbidden);
+		self.no_repeats.set(rule.no_repeats);
+	}
+
+	fn get(&self) -> GeneratorRule {
+		GeneratorRule {
+			min_length: self.min_length.get().trim().parse().unwrap_or(0),
+			max_length: self.max_length.get().trim().parse().unwrap_or(0),
+			require_upper: self.require_upper.get(),
+			require_lower: self.require_lower.get(),
+			require_digits: self.require_digits.get(),
+			require_symbols: self.require_symbols.get(),
+			forbidden: self.forbidden.get(),
+			no_repeats: self.no_repeats.get(),
+		}
+	}
+}
+
+// PRIVATE: the hosts of all url fields of an entry as a comma separated list
+fn get_domains(env: &Environment, id: &usize) -> String {
+	let mut domains = env
+		.db
+		.get_urls(id)
+		.iter()
+		.map(|url| get_host(url))
+		.filter(|host| !host.is_empty())
+		.collect::<Vec<String>>();
+	domains.sort();
+	domains.dedup();
+	domains.join(", ")
+}
+
+fn rule_form(draft: RuleDraft) -> impl IntoView {
+	(
+		(
+			label(|| "Length"),
+			input_field(draft.min_length).style(|s| s.width(40)),
+			label(|| "to"),
+			input_field(draft.max_length).style(|s| s.width(40)),
+			label(|| "(0 = no limit)")
+				.style(|s| s.color(C_MAIN_TEXT_INACTIVE).font_size(10.0)),
+		)
+			.style(|s| s.items_center().row_gap(5)),
+		label(|| "Must contain"),
+		(
+			option_line("Uppercase", draft.require_upper),
+			option_line("Lowercase", draft.require_lower),
+		)
+			.style(|s| s.row_gap(10)),
+		(
+			option_line("Digits", draft.require_digits),
+			option_line("Symbols", draft.require_symbols),
+		)
+			.style(|s| s.row_gap(10)),
+		(
+			label(|| "Forbidden characters"),
+			input_field(draft.forbidden).style(|s| s.flex_grow(1.0)),
+		)
+			.style(|s| s.items_center().row_gap(5)),
+		option_line("No repeated characters", draft.no_repeats),
+	)
+		.style(|s| s.flex_col().column_gap(5))
+}
+
+pub fn generator_rule_view(param: GeneratorRuleView) -> impl IntoView {
+	let GeneratorRuleView {
+		id,
+		window_id,
+		que,
+		env,
+	} = param;
+
+	let tooltip_signals = TooltipSignals::new(que);
+	provide_context(tooltip_signals);
+
+	let own_rule = env.db.get_generator_rule(&id);
+	let has_own_rule = own_rule.is_some();
+	let draft = RuleDraft::new(get_entry_rule(&env, &id).unwrap_or_default());
+	let status = if has_own_rule {
+		String::from("This entry has its own password rules")
+	} else if get_entry_rule(&env, &id).is_some() {
+		String::from("A preset matches the url of this entry")
+	} else {
+		String::from("No password rules apply to this entry")
+	};
+
+	let presets = create_rw_signal(env.config.get_generator_rule_presets());
+	let preset = create_rw_signal(0);
+	let preset_name = create_rw_signal(String::new());
+	let preset_domains = create_rw_signal(get_domains(&env, &id));
+
+	let env_save = env.clone();
+	let env_remove = env.clone();
+	let env_preset = env.clone();
+	let env_delete = env.clone();
+	let window_id_save = window_id.clone();
+
+	let generator_rule_view = (
+		label(move || status.clone())
+			.style(|s| s.color(C_MAIN_TEXT_INACTIVE).font_size(10.0)),
+		dyn_container(
+			move || presets.get(),
+			move |presets_list| {
+				let has_presets = !presets_list.is_empty();
+				let options = presets_list
+					.iter()
+					.map(|item| (item.id, item.name.clone()))
+					.collect::<Vec<(usize, String)>>();
+				preset.set(options.first().map(|(id, _)| *id).unwrap_or(0));
+				let env_update = env_delete.clone();
+				let env_delete = env_delete.clone();
+				let presets_update = presets_list.clone();
+
+				(
+					select(preset, options, move |_| {}),
+					container(button("Apply preset").on_click_cont(move |_| {
+						if let Some(item) =
+							presets_list.iter().find(|item| item.id == preset.get())
+						{
+							draft.load(item.rule.clone());
+						}
+					})),
+					// the preset keeps its name and domains and takes the new rules
+					container(button("Update preset").on_click_cont(move |_| {
+						if let Some(item) =
+							presets_update.iter().find(|item| item.id == preset.get())
+						{
+							presets.set(env_update.config.edit_generator_rule_preset(
+								GeneratorRulePreset {
+									rule: draft.get(),
+									..item.clone()
+								},
+							));
+							let _ = env_update.config.save();
+						}
+					})),
+					container(button("Delete preset").on_click_cont(move |_| {
+						presets.set(
+							env_delete.config.delete_generator_rule_preset(preset.get()),
+						);
+						let _ = env_delete.config.save();
+					})),
+				)
+					.style(move |s| {
+						s.items_center()
+							.row_gap(5)
+							.apply_if(!has_presets, |s| s.display(Display::None))
+					})
+					.into_any()
+			},
+		),
+		rule_form(draft),
+		(
+			container(button("Save").on_click_cont(move |_| {
+				env_save.db.set_generator_rule(&id, Some(draft.get()));
+				let _ = env_save.db.save();
+				closing_window(window_id_save.clone(), || ());
+			})),
+			container(button("Remove rules").on_click_cont(move |_| {
+				env_remove.db.set_generator_rule(&id, None);
+				let _ = env_remove.db.save();
+				closing_window(window_id.clone(), || ());
+			}))
+			.style(move |s| s.apply_if(!has_own_rule, |s| s.display(Display::None))),
+		)
+			.style(|s| s.row_gap(5)),
+		label(|| "Save as preset for domains")
+			.style(|s| s.margin_top(10).color(C_MAIN_TEXT_INACTIVE)),
+		(
+			label(|| "Name").style(|s| s.width(60)),
+			input_field(preset_name).style(|s| s.flex_grow(1.0)),
+		)
+			.style(|s| s.items_center().row_gap(5)),
+		(
+			label(|| "Domains").style(|s| s.width(60)),
+			input_field(preset_domains).style(|s| s.flex_grow(1.0)),
+		)
+			.style(|s| s.items_center().row_gap(5)),
+		container(button("Save as preset").on_click_cont(move |_| {
+			let name = String::from(preset_name.get().trim());
+			if !name.is_empty() {
+				let domains = preset_domains
+					.get()
+					.split(',')
+					.map(|domain| String::from(domain.trim()))
+					.filter(|domain| !domain.is_empty())
+					.collect::<Vec<String>>();
+				presets.set(env_preset.config.add_generator_rule_preset(
+					GeneratorRulePreset {
+						id: 0,
+						name,
+						domains,
+						rule: draft.get(),
+					},
+				));
+				let _ = env_preset.config.save();
+				preset_name.set(String::new());
+			}
+		})),
+		tooltip_view(tooltip_signals),
+	)
+		.style(|s| {
+			s.flex_col()
+				.width_full()
+				.height_full()
+				.padding(10)
+				.column_gap(10)
+				.background(C_MAIN_BG)
+		})
+		.on_event_cont(EventListener::PointerMove, move |event| {
+			let pos = match event {
+				Event::PointerMove(p) => p.pos,
+				_ => (0.0, 0.0).into(),
+			};
+			tooltip_signals.mouse_pos.set((pos.x, pos.y));
+		})
+		.on_resize(move |event| {
+			tooltip_signals.window_size.set((event.x1, event.y1));
+		});
+
+	match std::env::var("DEBUG") {
+		Ok(_) => {
+			// for debugging the layout
+			let id = generator_rule_view.id();
+			generator_rule_view.on_event_stop(EventListener::KeyUp, move |e| {
+				if let floem::event::Event::KeyUp(e) = e {
+					if e.key.logical_key
+						== floem::keyboard::Key::Named(floem::keyboard::NamedKey::F11)
+					{
+						id.inspect();
+					}
+				}
+			})
+		},
+		Err(_) => generator_rule_view,
+	}
+}
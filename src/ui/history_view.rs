@@ -3,6 +3,7 @@ use std::sync::Arc;
 
 use floem::{
 	event::{Event, EventListener},
+	kurbo::Size,
 	reactive::{create_rw_signal, provide_context, SignalGet, SignalUpdate},
 	views::{
 		container, label, scroll, virtual_stack, Decorators, VirtualDirection,
@@ -22,7 +23,11 @@ use crate::{
 			},
 			list_item::replace_consecutive_newlines,
 		},
-		primitives::tooltip::{tooltip_view, TooltipSignals},
+		primitives::{
+			que::Que,
+			tooltip::{tooltip_view, TooltipSignals},
+		},
+		window_management::{make_field_path, opening_window, WindowSpec},
 	},
 };
 
@@ -34,6 +39,7 @@ fn history_line(
 	id: usize,
 	field: DbFields,
 	date: u64,
+	is_highlighted: bool,
 	tooltip_signals: TooltipSignals,
 	db: Arc<Db>,
 ) -> impl IntoView {
@@ -45,7 +51,9 @@ fn history_line(
 		| DynFieldKind::MultiLine
 		| DynFieldKind::Url
 		| DynFieldKind::Heading => false,
-		DynFieldKind::TextLineSecret | DynFieldKind::MultiLineSecret => true,
+		DynFieldKind::TextLineSecret
+		| DynFieldKind::MultiLineSecret
+		| DynFieldKind::Reference => true,
 	};
 
 	let is_multiline = matches!(
@@ -107,7 +115,9 @@ fn history_line(
 			},
 			move || db_view_button.get_n_by_field(&id, &field, idx),
 		),
-		clipboard_button_slot(move || db.get_n_by_field(&id, &field, idx)),
+		clipboard_button_slot(is_secret, move || {
+			db.get_n_by_field(&id, &field, idx)
+		}),
 	)
 		.style(move |s| {
 			s.flex()
@@ -124,6 +134,9 @@ fn history_line(
 				} else {
 					C_SIDE_BG_SELECTED.with_alpha_factor(0.2)
 				})
+				.apply_if(is_highlighted, |s| {
+					s.background(C_FOCUS.with_alpha_factor(0.15))
+				})
 		})
 }
 
@@ -131,6 +144,7 @@ pub fn history_view(
 	id: usize,
 	field: DbFields,
 	dates: Vec<(usize, u64)>,
+	highlight: Option<usize>,
 	tooltip_signals: TooltipSignals,
 	db: Arc<Db>,
 ) -> impl IntoView {
@@ -158,7 +172,15 @@ pub fn history_view(
 				move || dates_list.get(),
 				move |item| *item,
 				move |(idx, date)| {
-					history_line(idx, id, field, date, tooltip_signals, db.clone())
+					history_line(
+						idx,
+						id,
+						field,
+						date,
+						highlight == Some(idx),
+						tooltip_signals,
+						db.clone(),
+					)
 				},
 			)
 			.style(|s| s.flex_col().flex_grow(1.0)),
@@ -195,3 +217,41 @@ pub fn history_view(
 		Err(_) => history_view,
 	}
 }
+
+// open the history of a field in its own window, optionally highlighting one value
+pub fn open_history_window(
+	id: usize,
+	field: DbFields,
+	field_title: String,
+	highlight: Option<usize>,
+	db: Arc<Db>,
+	on_close: impl Fn() + 'static,
+) {
+	let window_title = format!("{} Field History", field_title);
+	let dates = db.get_history_dates(&id, &field);
+	let que_history = Que::default();
+	let tooltip_signals_history = TooltipSignals::new(que_history);
+
+	opening_window(
+		move || {
+			history_view(
+				id,
+				field,
+				dates.clone(),
+				highlight,
+				tooltip_signals_history,
+				db.clone(),
+			)
+		},
+		WindowSpec {
+			id: make_field_path(id, &field),
+			title: window_title,
+		},
+		Size::new(350.0, 300.0),
+		true,
+		move || {
+			que_history.unque_all_tooltips();
+			on_close();
+		},
+	);
+}
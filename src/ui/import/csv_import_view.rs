@@ -0,0 +1,263 @@
+use chrono::Utc;
+
+use floem::{
+	event::{Event, EventListener},
+	reactive::{
+		create_rw_signal, provide_context, RwSignal, SignalGet, SignalUpdate,
+	},
+	style::Display,
+	views::{
+		container, dyn_container, h_stack_from_iter, label, scroll,
+		v_stack_from_iter, Decorators,
+	},
+	IntoView, View,
+};
+
+use crate::{
+	db::{Db, DynFieldKind},
+	env::Environment,
+	import::csv::{
+		detect_delimiter, guess_mapping, parse, to_entries, ColumnMapping,
+		DELIMITERS,
+	},
+	ui::{
+		colors::*,
+		primitives::{
+			button::button,
+			checkbox::checkbox,
+			que::Que,
+			select::select,
+			tooltip::{tooltip_view, TooltipSignals},
+		},
+		settings::database::open_import_window,
+		window_management::closing_window,
+	},
+};
+
+const PREVIEW_ROWS: usize = 5;
+const COLUMN_WIDTH: f64 = 130.0;
+
+pub struct CsvImportView {
+	pub content: String,
+	pub que: Que,
+	pub env: Environment,
+}
+
+// PRIVATE: parse the file again and start over with guessed mappings
+fn load(
+	content: &str,
+	delimiter: char,
+	has_header: bool,
+	rows: RwSignal<Vec<Vec<String>>>,
+	mappings: RwSignal<Vec<ColumnMapping>>,
+	error: RwSignal<String>,
+) {
+	match parse(content, delimiter) {
+		Ok(parsed) => {
+			let columns = parsed.iter().map(|row| row.len()).max().unwrap_or(0);
+			mappings.set(
+				(0..columns)
+					.map(|column| match parsed.first() {
+						Some(header) if has_header => guess_mapping(
+							header.get(column).map(|s| s.as_str()).unwrap_or(""),
+						),
+						_ => ColumnMapping::Field(DynFieldKind::TextLine),
+					})
+					.collect(),
+			);
+			rows.set(parsed);
+			error.set(String::new());
+		},
+		Err(parse_error) => {
+			mappings.set(Vec::new());
+			rows.set(Vec::new());
+			error.set(parse_error.to_string());
+		},
+	}
+}
+
+// PRIVATE: one column of the preview with the select that maps it
+fn preview_column(
+	column: usize,
+	cells: Vec<String>,
+	mappings: RwSignal<Vec<ColumnMapping>>,
+) -> impl IntoView {
+	let all_mappings = ColumnMapping::all_values();
+	let current =
+		mappings.get_untracked().get(column).cloned().unwrap_or_default();
+	let selected = create_rw_signal(
+		all_mappings.iter().position(|mapping| *mapping == current).unwrap_or(0),
+	);
+	let options = all_mappings.clone().into_iter().enumerate().collect();
+
+	(
+		select(selected, options, move |idx| {
+			mappings.update(|list| {
+				if let Some(mapping) = list.get_mut(column) {
+					*mapping = all_mappings[idx].clone();
+				}
+			});
+		}),
+		v_stack_from_iter(cells.into_iter().map(|cell| {
+			let text = cell.lines().next().unwrap_or("").to_string();
+			label(move || text.clone()).style(|s| {
+				s.width(COLUMN_WIDTH)
+					.text_ellipsis()
+					.padding(3)
+					.border_bottom(1)
+					.border_color(C_MAIN_BG_BORDER)
+			})
+		})),
+	)
+		.style(|s| s.flex_col().width(COLUMN_WIDTH).column_gap(5))
+}
+
+pub fn csv_import_view(param: CsvImportView) -> impl IntoView {
+	let CsvImportView { content, que, env } = param;
+
+	let tooltip_signals = TooltipSignals::new(que);
+	provide_context(tooltip_signals);
+
+	let delimiter = create_rw_signal(
+		DELIMITERS
+			.iter()
+			.position(|item| *item == detect_delimiter(&content))
+			.unwrap_or(0),
+	);
+	let has_header = create_rw_signal(true);
+	let rows = create_rw_signal(Vec::new());
+	let mappings = create_rw_signal(Vec::new());
+	let error = create_rw_signal(String::new());
+
+	load(
+		&content,
+		DELIMITERS[delimiter.get_untracked()],
+		true,
+		rows,
+		mappings,
+		error,
+	);
+
+	let content_header = content.clone();
+	let expected_delimiter = create_rw_signal(delimiter.get_untracked());
+
+	let csv_import_view = (
+		(
+			label(|| "Delimiter"),
+			select(
+				delimiter,
+				vec![(0, "Comma"), (1, "Semicolon"), (2, "Tab"), (3, "Pipe")],
+				move |idx| {
+					if idx != expected_delimiter.get() {
+						expected_delimiter.set(idx);
+						load(
+							&content,
+							DELIMITERS[idx],
+							has_header.get(),
+							rows,
+							mappings,
+							error,
+						);
+					}
+				},
+			),
+			checkbox(move || has_header.get()).on_update(move |state| {
+				has_header.set(state);
+				load(
+					&content_header,
+					DELIMITERS[delimiter.get()],
+					state,
+					rows,
+					mappings,
+					error,
+				);
+			}),
+			label(|| "First row is a header"),
+		)
+			.style(|s| s.items_center().row_gap(5)),
+		label(move || error.get()).style(move |s| {
+			s.color(C_ERROR)
+				.apply_if(error.get().is_empty(), |s| s.display(Display::None))
+		}),
+		scroll(
+			dyn_container(
+				move || rows.get(),
+				move |rows| {
+					let columns = mappings.get_untracked().len();
+					h_stack_from_iter((0..columns).map(|column| {
+						let cells = rows
+							.iter()
+							.take(PREVIEW_ROWS + 1)
+							.map(|row| row.get(column).cloned().unwrap_or_default())
+							.collect::<Vec<String>>();
+						preview_column(column, cells, mappings)
+					}))
+					.style(|s| s.row_gap(10))
+					.into_any()
+				},
+			)
+			.style(|s| s.padding_bottom(10)),
+		)
+		.style(|s| s.flex_grow(1.0).width_full().min_height(0)),
+		(
+			label(move || {
+				let count = rows.get().len();
+				let count = if has_header.get() {
+					count.saturating_sub(1)
+				} else {
+					count
+				};
+				format!("{} rows", count)
+			}),
+			container(button("Continue").on_click_cont(move |_| {
+				let entries = to_entries(
+					&rows.get(),
+					&mappings.get(),
+					has_header.get(),
+					Utc::now().timestamp() as u64,
+				);
+				if !entries.is_empty() {
+					open_import_window(Db::from_new_entries(entries), env.clone());
+					closing_window(String::from("csv-import-window"), || ());
+				}
+			})),
+		)
+			.style(|s| s.items_center().row_gap(10)),
+		tooltip_view(tooltip_signals),
+	)
+		.style(|s| {
+			s.flex_col()
+				.width_full()
+				.height_full()
+				.padding(10)
+				.column_gap(10)
+				.background(C_MAIN_BG)
+		})
+		.on_event_cont(EventListener::PointerMove, move |event| {
+			let pos = match event {
+				Event::PointerMove(p) => p.pos,
+				_ => (0.0, 0.0).into(),
+			};
+			tooltip_signals.mouse_pos.set((pos.x, pos.y));
+		})
+		.on_resize(move |event| {
+			tooltip_signals.window_size.set((event.x1, event.y1));
+		});
+
+	match std::env::var("DEBUG") {
+		Ok(_) => {
+			// for debugging the layout
+			let id = csv_import_view.id();
+			csv_import_view.on_event_stop(EventListener::KeyUp, move |e| {
+				if let floem::event::Event::KeyUp(e) = e {
+					if e.key.logical_key
+						== floem::keyboard::Key::Named(floem::keyboard::NamedKey::F11)
+					{
+						id.inspect();
+					}
+				}
+			})
+		},
+		Err(_) => csv_import_view,
+	}
+}
@@ -87,7 +87,9 @@ pub fn import_detail_view(id: usize, db: Db, que: Que) -> impl IntoView {
 					| DynFieldKind::MultiLine
 					| DynFieldKind::Url
 					| DynFieldKind::Heading => false,
-					DynFieldKind::TextLineSecret | DynFieldKind::MultiLineSecret => true,
+					DynFieldKind::TextLineSecret
+					| DynFieldKind::MultiLineSecret
+					| DynFieldKind::Reference => true,
 				};
 				let is_url_field = matches!(dyn_field_kind, DynFieldKind::Url);
 
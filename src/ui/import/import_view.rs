@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use floem::{
 	event::{Event, EventListener},
 	kurbo::Size,
@@ -5,7 +7,7 @@ use floem::{
 		create_rw_signal, provide_context, use_context, RwSignal, SignalGet,
 		SignalUpdate,
 	},
-	style::{CursorStyle, Position},
+	style::{CursorStyle, Display, Position},
 	views::virtual_stack,
 	views::{
 		container, label, scroll, Decorators, VirtualDirection, VirtualItemSize,
@@ -16,13 +18,15 @@ use floem::{
 use crate::{
 	db::Db,
 	env::Environment,
+	import::merge::{find_matches, summarize, MergeAction, MergeSummary},
 	ui::{
 		colors::*,
 		import::import_detail_view::import_detail_view,
 		primitives::{
-			button::{icon_button, IconButton},
+			button::{button, icon_button, IconButton},
 			checkbox::checkbox,
 			que::Que,
+			select::select,
 			styles,
 			tooltip::{tooltip_view, TooltipSignals},
 		},
@@ -32,10 +36,32 @@ use crate::{
 };
 
 const TOP_HEIGHT: f32 = 50.0;
+const SELECT_WIDTH: f64 = 115.0;
+
+// PRIVATE: entries that match one in the vault get a select for what to do with them
+fn action_select(
+	id: usize,
+	actions: RwSignal<HashMap<usize, MergeAction>>,
+) -> impl IntoView {
+	let all_actions = MergeAction::all_values();
+	let current = actions.get_untracked().get(&id).copied().unwrap_or_default();
+	let selected = create_rw_signal(
+		all_actions.iter().position(|action| *action == current).unwrap_or(0),
+	);
+	let options = all_actions.clone().into_iter().enumerate().collect();
+
+	select(selected, options, move |idx| {
+		actions.update(|list| {
+			list.insert(id, all_actions[idx]);
+		});
+	})
+}
 
 fn import_line(
 	item: (usize, bool),
 	import_items: RwSignal<im::Vector<(usize, bool)>>,
+	is_match: bool,
+	actions: RwSignal<HashMap<usize, MergeAction>>,
 	db: Db,
 ) -> impl IntoView {
 	let tooltip_signals = use_context::<TooltipSignals>()
@@ -72,6 +98,9 @@ fn import_line(
 					.inset_left(16 + 5 + 10)
 					.cursor(CursorStyle::Pointer)
 					.inset_right(29.5 + 5.0 + 10.0)
+					.apply_if(is_match, |s| {
+						s.inset_right(29.5 + 5.0 + 10.0 + SELECT_WIDTH + 5.0)
+					})
 			})
 			.on_text_overflow(move |is_overflown| {
 				does_overflow.set(is_overflown);
@@ -87,6 +116,12 @@ fn import_line(
 			.on_click_cont(move |_| {
 				update_checkbox(item.0, !item.1);
 			}),
+		container(action_select(item.0, actions)).style(move |s| {
+			s.position(Position::Absolute)
+				.width(SELECT_WIDTH)
+				.inset_right(29.5 + 5.0 + 10.0)
+				.apply_if(!is_match, |s| s.display(Display::None))
+		}),
 		container(icon_button(
 			IconButton {
 				icon: String::from(detail_icon),
@@ -142,7 +177,16 @@ pub fn import_view(db: Db, que: Que, env: Environment) -> impl IntoView {
 		.collect::<im::Vector<(usize, bool)>>();
 	let import_items = create_rw_signal(import_items.clone());
 
+	// the actions only matter for entries that are already in the vault
+	let matches = find_matches(&env.db, &db);
+	let actions = create_rw_signal(HashMap::new());
+	let summary = create_rw_signal(None::<MergeSummary>);
+
 	let db_import = db.clone();
+	let db_summary = db.clone();
+	let matches_line = matches.clone();
+	let matches_summary = matches.clone();
+	let env_summary = env.clone();
 
 	let import_view = (
 		(
@@ -158,7 +202,13 @@ pub fn import_view(db: Db, que: Que, env: Environment) -> impl IntoView {
 				.style(styles::button)
 				.style(|s| s.selectable(false))
 				.on_click_cont(move |_| {
-					import(import_items.get(), db_import.clone(), env.clone());
+					summary.set(Some(summarize(
+						&env_summary.db,
+						&db_summary,
+						&import_items.get(),
+						&matches_summary,
+						&actions.get(),
+					)));
 				}),
 			)
 			.style(|s| s.width_full().justify_end()),
@@ -197,7 +247,15 @@ pub fn import_view(db: Db, que: Que, env: Environment) -> impl IntoView {
 					VirtualItemSize::Fixed(Box::new(|| 30.0)),
 					move || import_items.get(),
 					move |item| *item,
-					move |item| import_line(item, import_items, db.clone()),
+					move |item| {
+						import_line(
+							item,
+							import_items,
+							matches_line.contains_key(&item.0),
+							actions,
+							db.clone(),
+						)
+					},
 				)
 				.style(|s| s.width_full().margin_bottom(10)),
 			)
@@ -211,6 +269,37 @@ pub fn import_view(db: Db, que: Que, env: Environment) -> impl IntoView {
 				.min_width(0)
 				.flex_grow(1.0)
 		}),
+		// nothing is written before the summary was confirmed
+		(
+			"Summary".style(|s| s.font_size(21.0)),
+			label(move || {
+				summary.get().map(|summary| summary.to_string()).unwrap_or_default()
+			}),
+			(
+				button("Back").on_click_cont(move |_| {
+					summary.set(None);
+				}),
+				button("Confirm import").on_click_cont(move |_| {
+					import(
+						import_items.get(),
+						matches.clone(),
+						actions.get(),
+						db_import.clone(),
+						env.clone(),
+					);
+				}),
+			)
+				.style(|s| s.row_gap(5)),
+		)
+			.style(move |s| {
+				s.flex_col()
+					.column_gap(10)
+					.padding(10)
+					.position(Position::Absolute)
+					.inset(0)
+					.background(C_MAIN_BG)
+					.apply_if(summary.get().is_none(), |s| s.display(Display::None))
+			}),
 		tooltip_view(tooltip_signals),
 	)
 		.style(|s| s.flex_col().flex().width_full().height_full())
@@ -7,18 +7,25 @@ use floem::{
 		create_rw_signal, use_context, RwSignal, SignalGet, SignalRead,
 		SignalUpdate,
 	},
-	style::Position,
-	views::Decorators,
+	style::{Display, Position},
+	views::{container, label, Decorators},
 	IntoView,
 };
 
-use crate::ui::{
-	colors::*,
-	keyboard::is_submit,
-	primitives::{
-		logo::logo,
-		password_field::password_field,
-		toast::{toast_view, ToastSignals},
+use crate::{
+	env::Environment,
+	password_gen::{get_passphrase_entropy, get_random_passphrase},
+	password_strength::{estimate, get_score_name},
+	ui::{
+		colors::*,
+		keyboard::is_submit,
+		primitives::{
+			button::button,
+			logo::logo,
+			password_field::password_field,
+			strength_meter::strength_meter,
+			toast::{toast_view, ToastSignals},
+		},
 	},
 };
 
@@ -26,24 +33,35 @@ fn save(
 	password: RwSignal<String>,
 	new_password_value: RwSignal<String>,
 	repeat_password_value: RwSignal<String>,
+	suggestion: RwSignal<String>,
+	min_strength: u8,
 	toast_signals: ToastSignals,
 ) {
-	if new_password_value.get() == repeat_password_value.get() {
+	if new_password_value.get() != repeat_password_value.get() {
+		toast_signals.add(String::from("The passwords are not the same"));
+	} else if estimate(&new_password_value.get()).score < min_strength {
+		toast_signals.add(format!(
+			"This password is too weak, it needs to be at least \"{}\"",
+			get_score_name(min_strength)
+		));
+	} else {
 		toast_signals.kill_all_toasts();
+		suggestion.update(|text| text.zeroize());
 		password.set(new_password_value.get());
 		new_password_value.update(|pass| pass.zeroize());
 		repeat_password_value.update(|pass| pass.zeroize());
-	} else {
-		toast_signals.add(String::from("The passwords are not the same"));
 	}
 }
 
 pub fn onboard_view(password: RwSignal<String>) -> impl IntoView {
 	let toast_signals =
 		use_context::<ToastSignals>().expect("No toast_signals context provider");
+	let env = use_context::<Environment>().expect("No env context provider");
 
 	let new_password_value = create_rw_signal(String::from(""));
 	let repeat_password_value = create_rw_signal(String::from(""));
+	let suggestion = create_rw_signal(String::from(""));
+	let min_strength = env.config.get_min_master_strength();
 
 	let password_input = password_field(new_password_value, "Create a password");
 	let input_id = password_input.input_id;
@@ -65,12 +83,15 @@ pub fn onboard_view(password: RwSignal<String>) -> impl IntoView {
 						password,
 						new_password_value,
 						repeat_password_value,
+						suggestion,
+						min_strength,
 						toast_signals,
 					);
 					input_id.request_focus();
 				}
 			})
 			.style(|s| s.width(250)),
+		container(strength_meter(new_password_value)).style(|s| s.width(250)),
 		password_field(repeat_password_value, "Repeat password")
 			.on_event_cont(EventListener::KeyDown, move |event| {
 				let key = match event {
@@ -83,12 +104,32 @@ pub fn onboard_view(password: RwSignal<String>) -> impl IntoView {
 						password,
 						new_password_value,
 						repeat_password_value,
+						suggestion,
+						min_strength,
 						toast_signals,
 					);
 					input_id.request_focus();
 				}
 			})
 			.style(|s| s.width(250)),
+		container(button("Suggest a passphrase").on_click_cont(move |_| {
+			let options = env.config.get_passphrase_options();
+			let mut passphrase = get_random_passphrase(&options);
+			new_password_value.set(passphrase.clone());
+			repeat_password_value.update(|pass| pass.zeroize());
+			suggestion.set(format!(
+				"{}\n{:.0} bits of entropy, repeat it to continue",
+				passphrase,
+				get_passphrase_entropy(&options)
+			));
+			passphrase.zeroize();
+		}))
+		.style(|s| s.margin_top(4)),
+		label(move || suggestion.get()).style(move |s| {
+			s.width(250)
+				.color(C_TOP_TEXT)
+				.apply_if(suggestion.get().is_empty(), |s| s.display(Display::None))
+		}),
 	)
 		.style(|s| {
 			s.flex_col()
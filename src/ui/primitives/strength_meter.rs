@@ -0,0 +1,52 @@
+use floem::{
+	peniko::Color,
+	reactive::{create_memo, RwSignal, SignalGet},
+	style::Display,
+	views::{empty, h_stack_from_iter, label, Decorators},
+	IntoView,
+};
+
+use crate::{password_strength::estimate, ui::colors::*};
+
+const SEGMENTS: u8 = 4;
+
+fn get_color(score: u8) -> Color {
+	match score {
+		0 | 1 => C_ERROR,
+		2 => C_WARNING,
+		_ => C_SUCCESS,
+	}
+}
+
+pub fn strength_meter(value: RwSignal<String>) -> impl IntoView {
+	let strength = create_memo(move |_| estimate(&value.get()));
+
+	(
+		h_stack_from_iter((0..SEGMENTS).map(move |idx| {
+			empty().style(move |s| {
+				let score = strength.get().score;
+				s.flex_grow(1.0)
+					.height(3)
+					.border_radius(2)
+					.background(C_MAIN_BG_BORDER.with_alpha_factor(0.5))
+					.apply_if(score > idx, |s| s.background(get_color(score)))
+			})
+		}))
+		.style(|s| s.width_full().row_gap(3)),
+		label(move || {
+			let strength = strength.get();
+			format!(
+				"{} · {} to crack",
+				strength.get_label(),
+				strength.get_crack_time()
+			)
+		})
+		.style(|s| s.font_size(10).color(C_TOP_TEXT).selectable(false)),
+	)
+		.style(move |s| {
+			s.flex_col()
+				.width_full()
+				.column_gap(2)
+				.apply_if(value.get().is_empty(), |s| s.display(Display::None))
+		})
+}
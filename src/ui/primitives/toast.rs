@@ -5,7 +5,7 @@ use floem::{
 	animate::animation,
 	reactive::{create_rw_signal, RwSignal, SignalGet, SignalUpdate},
 	style::{FlexDirection, Position},
-	views::{container, dyn_stack, empty, scroll, svg, Decorators},
+	views::{container, dyn_stack, empty, label, scroll, svg, Decorators},
 	IntoView,
 };
 
@@ -15,7 +15,8 @@ const DISMISS_TIMEOUT: u64 = 10;
 
 #[derive(Debug, Copy, Clone)]
 pub struct ToastSignals {
-	pub toasts: RwSignal<Vec<(u8, String)>>,
+	// id, text and the seconds until the toast is dismissed
+	pub toasts: RwSignal<Vec<(u8, String, u64)>>,
 	pub que: Que,
 }
 
@@ -37,22 +38,40 @@ impl ToastSignals {
 	}
 
 	pub fn add(self, text: String) -> u8 {
+		self.add_for(text, DISMISS_TIMEOUT)
+	}
+
+	// add a toast that stays for the given number of seconds
+	pub fn add_for(self, text: String, seconds: u64) -> u8 {
 		let id = self.que.toast.get().last().unwrap_or(&0) + 1;
-		self.toasts.update(|item| item.push((id, text.clone())));
+		self.toasts.update(|item| item.push((id, text, seconds)));
 		self.que.toast.update(|item| item.push(id));
 
-		exec_after(Duration::from_secs(DISMISS_TIMEOUT), move |_| {
+		exec_after(Duration::from_secs(seconds), move |_| {
 			if self.que.toast.get().contains(&id) {
-				self.unque_toast(id);
-				// make sure we don't execute toasts after a view has been destroyed (window closed)
-				let _ = catch_unwind(|| {
-					self.toasts.update(|item| item.retain(|(ids, _)| *ids != id));
-				});
+				self.remove(id);
 			}
 		});
 
 		id
 	}
+
+	// change the text of a toast while it's still showing
+	pub fn set_text(self, id: u8, text: String) {
+		self.toasts.update(|item| {
+			if let Some(toast) = item.iter_mut().find(|(ids, _, _)| *ids == id) {
+				toast.1 = text;
+			}
+		});
+	}
+
+	pub fn remove(self, id: u8) {
+		self.unque_toast(id);
+		// make sure we don't execute toasts after a view has been destroyed (window closed)
+		let _ = catch_unwind(|| {
+			self.toasts.update(|item| item.retain(|(ids, _, _)| *ids != id));
+		});
+	}
 }
 
 pub fn toast_view(toast_signals: ToastSignals) -> impl IntoView {
@@ -61,8 +80,8 @@ pub fn toast_view(toast_signals: ToastSignals) -> impl IntoView {
 	scroll(
 		dyn_stack(
 			move || toast_signals.toasts.get(),
-			move |toasts| toasts.clone(),
-			move |toast| {
+			move |toast| toast.0,
+			move |(id, _, seconds)| {
 				(
 					container(
 						svg(move || String::from(alert_icon))
@@ -77,7 +96,16 @@ pub fn toast_view(toast_signals: ToastSignals) -> impl IntoView {
 							.justify_center()
 					}),
 					(
-						toast.1.clone().style(|s| s.width_full().height_full().padding(5)),
+						label(move || {
+							toast_signals
+								.toasts
+								.get()
+								.iter()
+								.find(|(ids, _, _)| *ids == id)
+								.map(|(_, text, _)| text.clone())
+								.unwrap_or_default()
+						})
+						.style(|s| s.width_full().height_full().padding(5)),
 						empty()
 							.style(|s| {
 								s.width(0).height(2).background(C_ERROR.with_alpha_factor(0.7))
@@ -86,7 +114,7 @@ pub fn toast_view(toast_signals: ToastSignals) -> impl IntoView {
 								animation()
 									.width(|| 200.0 - 35.0 + 3.0)
 									.ease_in_out()
-									.duration(Duration::from_secs(DISMISS_TIMEOUT)),
+									.duration(Duration::from_secs(seconds)),
 							),
 					)
 						.style(|s| {
@@ -0,0 +1,66 @@
+use zeroize::Zeroize;
+
+use floem::{
+	event::EventListener,
+	style::Display,
+	views::{container, label, svg, Decorators},
+	IntoView, View,
+};
+
+use crate::{qr::to_svg, ui::colors::*};
+
+const QR_SIZE: f64 = 250.0;
+
+pub fn qr_view(field_title: String, mut payload: String) -> impl IntoView {
+	let code = to_svg(&payload);
+	payload.zeroize();
+
+	let is_ok = code.is_ok();
+	let (qr_code, error) = match code {
+		Ok(qr_code) => (qr_code, String::new()),
+		Err(err) => (String::new(), err.to_string()),
+	};
+
+	let qr_view = (
+		label(move || field_title.clone())
+			.style(|s| s.text_ellipsis().max_width_full()),
+		// the code needs a light border around it to be scanned
+		container(
+			svg(move || qr_code.clone()).style(|s| s.width(QR_SIZE).height(QR_SIZE)),
+		)
+		.style(move |s| {
+			s.padding(10)
+				.background(C_MAIN_BG)
+				.apply_if(!is_ok, |s| s.display(Display::None))
+		}),
+		label(move || error.clone()).style(move |s| {
+			s.color(C_ERROR).apply_if(is_ok, |s| s.display(Display::None))
+		}),
+	)
+		.style(|s| {
+			s.flex_col()
+				.width_full()
+				.height_full()
+				.padding(10)
+				.column_gap(10)
+				.items_center()
+				.background(C_MAIN_BG)
+		});
+
+	match std::env::var("DEBUG") {
+		Ok(_) => {
+			// for debugging the layout
+			let id = qr_view.id();
+			qr_view.on_event_stop(EventListener::KeyUp, move |e| {
+				if let floem::event::Event::KeyUp(e) = e {
+					if e.key.logical_key
+						== floem::keyboard::Key::Named(floem::keyboard::NamedKey::F11)
+					{
+						id.inspect();
+					}
+				}
+			})
+		},
+		Err(_) => qr_view,
+	}
+}
@@ -1,4 +1,6 @@
-use std::fs;
+use chrono::Utc;
+use std::{collections::HashMap, fs, path::Path};
+use zeroize::Zeroize;
 
 use floem::{
 	event::{Event, EventListener},
@@ -8,7 +10,8 @@ use floem::{
 	kurbo::Size,
 	peniko::Brush,
 	reactive::{
-		create_rw_signal, use_context, RwSignal, SignalGet, SignalUpdate,
+		create_effect, create_rw_signal, use_context, RwSignal, SignalGet,
+		SignalUpdate,
 	},
 	style::{CursorStyle, Display},
 	views::{container, label, slider::slider, svg, Decorators},
@@ -18,14 +21,27 @@ use floem::{
 use crate::{
 	config::DB_FILE_NAME,
 	create_lock_timeout,
-	db::Db,
+	db::{Db, NewDbEntry},
 	env::Environment,
+	export::plaintext::{self, PlaintextFormat},
+	import::{
+		bitwarden, csv, csv_formats,
+		detect::{detect_format, ImportFormat},
+		json, kdbx,
+		merge::{self, MergeAction},
+		onepux,
+		pass::{self, Gpg},
+	},
 	ui::{
 		app_view::{
 			QueSettings, SidebarList, ToastSignalsSettings, TooltipSignalsSettings,
 		},
 		colors::*,
-		import::import_view::import_view,
+		export_view::{export_view, ExportKind},
+		import::{
+			csv_import_view::{csv_import_view, CsvImportView},
+			import_view::import_view,
+		},
 		keyboard::is_submit,
 		primitives::{
 			button::{button, icon_button, IconButton},
@@ -42,6 +58,15 @@ use crate::{
 
 const MIN: f32 = 60.0; // 1min
 const MAX: f32 = (60.0 * 60.0 * 10.0) - 60.0; // 60s -> 60min -> 10h minus MIN
+const CLIPBOARD_TIMEOUTS: [(u64, &str); 7] = [
+	(0, "Never"),
+	(10, "10 seconds"),
+	(20, "20 seconds"),
+	(30, "30 seconds"),
+	(60, "1 minute"),
+	(120, "2 minutes"),
+	(300, "5 minutes"),
+];
 
 fn convert_pct_2_timeout(pct: f32) -> f32 {
 	((MAX / 100.0) * pct) + MIN
@@ -80,85 +105,264 @@ fn export(file: FileInfo, env: Environment) {
 	};
 }
 
+// PRIVATE: a plaintext export holds every secret so the master password has
+// to be entered again before we ask where to save it
+fn export_plaintext(
+	format: PlaintextFormat,
+	master_password: RwSignal<String>,
+	toast_signals: ToastSignals,
+	env: Environment,
+) {
+	// the copy handed to check_password is zeroized by the hash function
+	let is_correct = env.db.check_password(master_password.get());
+	master_password.update(|pass| pass.zeroize());
+	if !is_correct {
+		toast_signals.add(String::from("Wrong password"));
+		return;
+	}
+
+	save_as(
+		FileDialogOptions::new()
+			.default_name(format!("vault-export.{}", format.extension()))
+			.title("Save unencrypted export file"),
+		move |file_info| {
+			if let Some(file) = file_info {
+				let result =
+					plaintext::export(&env.db, format).and_then(|mut content| {
+						let result = plaintext::write_private(&file.path[0], &content);
+						content.zeroize();
+						result
+					});
+				if let Err(err) = result {
+					toast_signals.add(err.to_string());
+				}
+			}
+		},
+	);
+}
+
+// import the selected entries, the ones matching an existing entry are handled
+// by the action picked for them
 pub fn import(
 	import_list: im::Vector<(usize, bool)>,
+	matches: HashMap<usize, usize>,
+	actions: HashMap<usize, MergeAction>,
 	import_db: Db,
 	env: Environment,
 ) {
 	let list_sidebar_signal = use_context::<SidebarList>()
 		.expect("No list_sidebar_signal context provider");
 
-	for &(import_id, is_selected) in &import_list {
-		if is_selected {
-			let import_entry = import_db.get_by_id(&import_id);
-			let new_id = env.db.add(import_entry.title);
+	merge::apply(&env.db, &import_db, &import_list, &matches, &actions);
 
-			import_db.get_fields(&import_id).iter().for_each(
-				|(import_field, is_visible)| {
-					let name = import_db.get_name_of_field(&import_id, import_field);
-					let kind = import_db.get_field_kind(&import_id, import_field);
+	let _ = env.save();
+	list_sidebar_signal.set(env.db.get_sidebar_list());
+	closing_window(String::from("import-window"), || ());
+}
 
-					let mut history: Vec<(u64, String)> = import_db
-						.get_history(&import_id, import_field)
-						.unwrap_or_default()
-						.into_iter()
-						.collect();
+// open the selection list of entries to import from a db that only lives in memory
+pub fn open_import_window(imported_db: Db, env: Environment) {
+	let que_import = Que::default();
 
-					let last = history.len() - 1;
-					let value = import_db.get_n_by_field(&import_id, import_field, last);
+	opening_window(
+		move || import_view(imported_db.clone(), que_import, env.clone()),
+		WindowSpec {
+			id: String::from("import-window"),
+			title: String::from("Import into Vault"),
+		},
+		Size::new(420.0, 350.0),
+		true,
+		move || {
+			que_import.unque_all_tooltips();
+		},
+	);
+}
 
-					let field = env.db.add_field(&new_id, kind, name, value);
-					if !is_visible {
-						env.db.edit_field_visbility(&new_id, &field, false);
-					}
+// PRIVATE: pick the entries to hand out and the password they're encrypted
+// with or the entries to print in an emergency kit
+fn open_export_window(kind: ExportKind, env: Environment) {
+	let que_export = Que::default();
+
+	opening_window(
+		move || export_view(que_export, kind, env.clone()),
+		WindowSpec {
+			id: kind.window_id(),
+			title: match kind {
+				ExportKind::Encrypted => String::from("Export from Vault"),
+				ExportKind::EmergencyKit => String::from("Vault emergency kit"),
+			},
+		},
+		Size::new(350.0, 450.0),
+		true,
+		move || {
+			que_export.unque_all_tooltips();
+		},
+	);
+}
 
-					history.reverse();
-					history.iter().skip(1).for_each(|(_, value)| {
-						env.db.edit_field(new_id, &field, value.clone());
-					})
+// PRIVATE: csv files don't have a password but need their columns mapped first
+fn open_csv_import_window(content: String, env: Environment) {
+	let que_csv = Que::default();
+
+	opening_window(
+		move || {
+			csv_import_view(CsvImportView {
+				content: content.clone(),
+				que: que_csv,
+				env: env.clone(),
+			})
+		},
+		WindowSpec {
+			id: String::from("csv-import-window"),
+			title: String::from("Import CSV"),
+		},
+		Size::new(600.0, 350.0),
+		false,
+		move || {
+			que_csv.unque_all_tooltips();
+		},
+	);
+}
+
+// PRIVATE: read the entries of all formats that don't need any more input
+fn import_entries(
+	format: ImportFormat,
+	data: &[u8],
+	password: &str,
+	key_path: Option<String>,
+) -> Result<Vec<NewDbEntry>, String> {
+	let timestamp = Utc::now().timestamp() as u64;
+
+	match format {
+		ImportFormat::KeePass => {
+			let key_file = match key_path {
+				Some(key_path) => {
+					Some(fs::read(key_path).map_err(|err| err.to_string())?)
 				},
-			);
-		}
+				None => None,
+			};
+			kdbx::to_entries(data, password, key_file.as_deref())
+				.map_err(|err| err.to_string())
+		},
+		ImportFormat::KeePassXml => {
+			kdbx::xml_to_entries(&String::from_utf8_lossy(data))
+				.map_err(|err| err.to_string())
+		},
+		ImportFormat::Json => json::to_entries(&String::from_utf8_lossy(data))
+			.map_err(|err| err.to_string()),
+		ImportFormat::Bitwarden => {
+			bitwarden::to_entries(&String::from_utf8_lossy(data), password, timestamp)
+				.map_err(|err| err.to_string())
+		},
+		ImportFormat::OnePassword => {
+			onepux::to_entries(data, timestamp).map_err(|err| err.to_string())
+		},
+		ImportFormat::KnownCsv(csv_format) => {
+			let content = String::from_utf8_lossy(data);
+			csv::parse(&content, csv::detect_delimiter(&content))
+				.map(|rows| csv_formats::to_entries(csv_format, &rows, timestamp))
+				.map_err(|err| err.to_string())
+		},
+		ImportFormat::Vault | ImportFormat::Csv => Ok(Vec::new()),
 	}
+}
 
-	let _ = env.save();
-	list_sidebar_signal.set(env.db.get_sidebar_list());
-	closing_window(String::from("import-window"), || ());
+fn is_kdbx(path: &str) -> bool {
+	path.to_lowercase().ends_with(".kdbx")
 }
 
 fn import_window(
 	import_path: RwSignal<Vec<String>>,
 	import_password: RwSignal<String>,
+	import_key_path: RwSignal<Vec<String>>,
 	toast_signals: ToastSignals,
 	env: Environment,
 ) {
-	if !import_path.get().is_empty() {
-		let imported_db = Db::load(import_path.get()[0].clone());
-		let decrypted = imported_db.decrypt_database(import_password.get());
-		match decrypted {
-			Ok(()) => {
-				import_path.set(Vec::new());
-				import_password.set(String::from(""));
-
-				let que_import = Que::default();
+	if let Some(path) = import_path.get().first().cloned() {
+		let data = match fs::read(&path) {
+			Ok(data) => data,
+			Err(err) => {
+				toast_signals.add(err.to_string());
+				return;
+			},
+		};
 
-				opening_window(
-					move || import_view(imported_db.clone(), que_import, env.clone()),
-					WindowSpec {
-						id: String::from("import-window"),
-						title: String::from("Import into Vault"),
+		match detect_format(&data) {
+			ImportFormat::Csv => {
+				import_path.set(Vec::new());
+				open_csv_import_window(String::from_utf8_lossy(&data).to_string(), env);
+			},
+			ImportFormat::Vault => {
+				let imported_db = Db::load(path);
+				let decrypted = imported_db.decrypt_database(import_password.get());
+				match decrypted {
+					Ok(()) => {
+						import_path.set(Vec::new());
+						import_password.set(String::from(""));
+						open_import_window(imported_db, env);
 					},
-					Size::new(300.0, 350.0),
-					true,
-					move || {
-						que_import.unque_all_tooltips();
+					Err(err) => {
+						toast_signals.add(err.to_string());
 					},
-				);
+				};
+			},
+			format => match import_entries(
+				format,
+				&data,
+				&import_password.get(),
+				import_key_path.get().first().cloned(),
+			) {
+				Ok(entries) if entries.is_empty() => {
+					toast_signals.add(String::from("No entries found to import"));
+				},
+				Ok(entries) => {
+					import_path.set(Vec::new());
+					import_password.set(String::from(""));
+					import_key_path.set(Vec::new());
+					open_import_window(Db::from_new_entries(entries), env);
+				},
+				Err(err) => {
+					toast_signals.add(err);
+				},
+			},
+		}
+	}
+}
+
+// PRIVATE: a password store is a folder of gpg files so it gets its own import
+fn import_pass_window(
+	pass_path: RwSignal<Vec<String>>,
+	pass_key_path: RwSignal<Vec<String>>,
+	pass_passphrase: RwSignal<String>,
+	toast_signals: ToastSignals,
+	env: Environment,
+) {
+	if let Some(path) = pass_path.get().first().cloned() {
+		let key_path = pass_key_path.get().first().cloned();
+		let entries =
+			Gpg::new(key_path.as_deref().map(Path::new), &pass_passphrase.get())
+				.and_then(|gpg| {
+					pass::to_entries(
+						Path::new(&path),
+						|file| gpg.decrypt(file),
+						Utc::now().timestamp() as u64,
+					)
+				});
+
+		match entries {
+			Ok(entries) if entries.is_empty() => {
+				toast_signals.add(String::from("No entries found to import"));
+			},
+			Ok(entries) => {
+				pass_path.set(Vec::new());
+				pass_key_path.set(Vec::new());
+				pass_passphrase.set(String::from(""));
+				open_import_window(Db::from_new_entries(entries), env);
 			},
 			Err(err) => {
 				toast_signals.add(err.to_string());
 			},
-		};
+		}
 	}
 }
 
@@ -191,12 +395,67 @@ pub fn database_view() -> impl IntoView {
 		create_rw_signal(env.config.general.read().db_path.clone());
 	let import_path = create_rw_signal(Vec::new());
 	let import_password = create_rw_signal(String::from(""));
+	let import_key_path = create_rw_signal(Vec::new());
+	let pass_path = create_rw_signal(Vec::new());
+	let pass_key_path = create_rw_signal(Vec::new());
+	let pass_passphrase = create_rw_signal(String::from(""));
+	let plaintext_format = create_rw_signal(0);
+	let plaintext_password = create_rw_signal(String::from(""));
+	let plaintext_formats = PlaintextFormat::all_values();
+	let plaintext_formats_enter = plaintext_formats.clone();
+	let plaintext_formats_click = plaintext_formats.clone();
+	let plaintext_formats_warning = plaintext_formats.clone();
+	let integrity_report = create_rw_signal(Vec::new());
+	let breach_path = {
+		let path = env.config.get_breach_file_path();
+		create_rw_signal(if path.is_empty() {
+			Vec::new()
+		} else {
+			vec![path]
+		})
+	};
 
 	let env_dbpath_reset = env.clone();
 	let env_dbpath_save = env.clone();
 	let env_export = env.clone();
+	let env_export_selection = env.clone();
+	let env_plaintext_enter = env.clone();
+	let env_plaintext_click = env.clone();
+	let env_emergency_kit = env.clone();
 	let env_import_enter = env.clone();
 	let env_import_click = env.clone();
+	let env_pass_enter = env.clone();
+	let env_pass_click = env.clone();
+	let env_integrity = env.clone();
+	let env_breach = env.clone();
+	let env_clipboard = env.clone();
+
+	let clipboard_timeout = env.config.get_clipboard_timeout();
+	let mut clipboard_timeouts = CLIPBOARD_TIMEOUTS
+		.iter()
+		.map(|(seconds, name)| (*seconds, String::from(*name)))
+		.collect::<Vec<(u64, String)>>();
+	if !clipboard_timeouts
+		.iter()
+		.any(|(seconds, _)| *seconds == clipboard_timeout)
+	{
+		clipboard_timeouts
+			.push((clipboard_timeout, human_readable(clipboard_timeout as f32)));
+	}
+	let clipboard_timeout_idx = create_rw_signal(
+		clipboard_timeouts
+			.iter()
+			.position(|(seconds, _)| *seconds == clipboard_timeout)
+			.unwrap_or(0),
+	);
+
+	create_effect(move |_| {
+		if let Some(path) = breach_path.get().first() {
+			if *path != env_breach.config.get_breach_file_path() {
+				env_breach.config.set_breach_file_path(path.clone());
+			}
+		}
+	});
 
 	let all_snaps = [
 		Snap::NoSnaping,
@@ -320,6 +579,22 @@ pub fn database_view() -> impl IntoView {
 					.style(|s| s.row_gap(5).items_center()),
 			)
 				.style(|s| s.flex_col()),
+			"Clear copied secrets".style(|s| s.margin_top(20)),
+			select(
+				clipboard_timeout_idx,
+				clipboard_timeouts
+					.iter()
+					.enumerate()
+					.map(|(idx, (_, name))| (idx, name.clone()))
+					.collect(),
+				move |idx| {
+					let seconds = clipboard_timeouts[idx].0;
+					if seconds != env_clipboard.config.get_clipboard_timeout() {
+						env_clipboard.config.set_clipboard_timeout(seconds);
+					}
+				},
+			)
+			.style(|s| s.margin_top(20)),
 			"Database location".style(|s| s.margin_top(20)),
 			(
 				label(move || db_path.get())
@@ -400,29 +675,84 @@ pub fn database_view() -> impl IntoView {
 			)
 				.style(|s| s.flex_col().margin_top(20).column_gap(5)),
 			"Backup data".style(|s| s.margin_top(20)),
-			container(
-				(
-					"Export".style(|s| s.margin_left(5).selectable(false)),
-					svg(move || String::from(download_icon))
-						.style(|s| s.width(16).height(16).margin_left(5)),
-				)
-					.style(styles::button)
-					.style(|s| s.items_center())
-					.on_click_cont(move |_| {
-						let env_export = env_export.clone();
-						save_as(
-							FileDialogOptions::new()
-								.default_name("vault.backup")
-								.title("Save backup file"),
-							move |file_info| {
-								if let Some(file) = file_info {
-									export(file, env_export.clone());
-								}
-							},
-						);
-					}),
+			(
+				container(
+					(
+						"Export".style(|s| s.margin_left(5).selectable(false)),
+						svg(move || String::from(download_icon))
+							.style(|s| s.width(16).height(16).margin_left(5)),
+					)
+						.style(styles::button)
+						.style(|s| s.items_center())
+						.on_click_cont(move |_| {
+							let env_export = env_export.clone();
+							save_as(
+								FileDialogOptions::new()
+									.default_name("vault.backup")
+									.title("Save backup file"),
+								move |file_info| {
+									if let Some(file) = file_info {
+										export(file, env_export.clone());
+									}
+								},
+							);
+						}),
+				),
+				container(button("Export selection").on_click_cont(move |_| {
+					open_export_window(
+						ExportKind::Encrypted,
+						env_export_selection.clone(),
+					);
+				})),
 			)
-			.style(|s| s.margin_top(20)),
+				.style(|s| s.flex_col().margin_top(20).column_gap(5)),
+			"Plaintext export".style(|s| s.margin_top(20)),
+			(
+				select(
+					plaintext_format,
+					plaintext_formats.into_iter().enumerate().collect(),
+					move |_| {},
+				),
+				label(move || {
+					format!(
+						"Warning: this file holds {} unencrypted, anyone who can read it can read your secrets",
+						plaintext_formats_warning[plaintext_format.get()].description()
+					)
+				})
+				.style(|s| s.width(200).color(C_ERROR)),
+				password_field(plaintext_password, "Enter your master password")
+					.on_event_cont(EventListener::KeyDown, move |event| {
+						let key = match event {
+							Event::KeyDown(k) => k.key.physical_key,
+							_ => PhysicalKey::Code(KeyCode::F35),
+						};
+
+						if is_submit(key) {
+							export_plaintext(
+								plaintext_formats_enter[plaintext_format.get()],
+								plaintext_password,
+								toast_signals,
+								env_plaintext_enter.clone(),
+							);
+						}
+					})
+					.style(|s| s.width(200)),
+				container(button("Export unencrypted").on_click_cont(move |_| {
+					export_plaintext(
+						plaintext_formats_click[plaintext_format.get()],
+						plaintext_password,
+						toast_signals,
+						env_plaintext_click.clone(),
+					);
+				})),
+				container(button("Emergency kit").on_click_cont(move |_| {
+					open_export_window(
+						ExportKind::EmergencyKit,
+						env_emergency_kit.clone(),
+					);
+				})),
+			)
+				.style(|s| s.flex_col().margin_top(20).column_gap(5)),
 			"Importing data".style(|s| s.margin_top(20)),
 			(
 				file_input(
@@ -431,7 +761,9 @@ pub fn database_view() -> impl IntoView {
 					FileDialogOptions::new()
 						.allowed_types(vec![FileSpec {
 							name: "backup",
-							extensions: &["backup", "vault", "toml"],
+							extensions: &[
+								"backup", "vault", "toml", "csv", "kdbx", "json", "1pux", "xml",
+							],
 						}])
 						.title("Select import file"),
 					move |_| {},
@@ -448,22 +780,130 @@ pub fn database_view() -> impl IntoView {
 							import_window(
 								import_path,
 								import_password,
+								import_key_path,
 								toast_signals,
 								env_import_enter.clone(),
 							);
 						}
 					})
 					.style(|s| s.width(200)),
+				file_input(
+					import_key_path,
+					String::from("Select key file (optional)..."),
+					FileDialogOptions::new().title("Select key file of the KeePass database"),
+					move |_| {},
+				)
+				.style(move |s| {
+					s.width(200).apply_if(
+						!import_path.get().first().is_some_and(|path| is_kdbx(path)),
+						|s| s.display(Display::None),
+					)
+				}),
 				container(button("Import").on_click_cont(move |_| {
 					import_window(
 						import_path,
 						import_password,
+						import_key_path,
 						toast_signals,
 						env_import_click.clone(),
 					);
 				})),
 			)
 				.style(|s| s.flex_col().margin_top(20).column_gap(5)),
+			(
+				file_input(
+					pass_path,
+					String::from("Select password store..."),
+					FileDialogOptions::new()
+						.select_directories()
+						.title("Select the folder of the password store"),
+					move |_| {},
+				)
+				.style(|s| s.width(200)),
+				file_input(
+					pass_key_path,
+					String::from("Select private key (optional)..."),
+					FileDialogOptions::new()
+						.title("Select the exported gpg key of the password store"),
+					move |_| {},
+				)
+				.style(|s| s.width(200)),
+				password_field(pass_passphrase, "Enter passphrase of the gpg key")
+					.on_event_cont(EventListener::KeyDown, move |event| {
+						let key = match event {
+							Event::KeyDown(k) => k.key.physical_key,
+							_ => PhysicalKey::Code(KeyCode::F35),
+						};
+
+						if is_submit(key) {
+							import_pass_window(
+								pass_path,
+								pass_key_path,
+								pass_passphrase,
+								toast_signals,
+								env_pass_enter.clone(),
+							);
+						}
+					})
+					.style(|s| s.width(200)),
+				container(button("Import password store").on_click_cont(move |_| {
+					import_pass_window(
+						pass_path,
+						pass_key_path,
+						pass_passphrase,
+						toast_signals,
+						env_pass_click.clone(),
+					);
+				})),
+			)
+				.style(|s| s.flex_col().margin_top(20).column_gap(5)),
+			"Integrity".style(|s| s.margin_top(20)),
+			(
+				container(button("Check integrity").on_click_cont(move |_| {
+					let dangling = env_integrity.db.get_dangling_references();
+					integrity_report.set(if dangling.is_empty() {
+						vec![String::from("No problems found")]
+					} else {
+						dangling
+							.into_iter()
+							.map(|(_, entry_title, field_title)| {
+								format!(
+									"\"{}\" › \"{}\" references a deleted field",
+									entry_title, field_title
+								)
+							})
+							.collect()
+					});
+				})),
+				label(move || integrity_report.get().join("\n")).style(move |s| {
+					s.width(200)
+						.color(C_MAIN_TEXT_INACTIVE)
+						.apply_if(integrity_report.get().is_empty(), |s| {
+							s.display(Display::None)
+						})
+				}),
+			)
+				.style(|s| s.flex_col().margin_top(20).column_gap(5)),
+			"Breach file".style(|s| s.margin_top(20)),
+			(
+				file_input(
+					breach_path,
+					String::from("Select breach file..."),
+					FileDialogOptions::new()
+						.allowed_types(vec![FileSpec {
+							name: "hashes",
+							extensions: &["txt"],
+						}])
+						.title("Select the SHA-1 file from Have I Been Pwned"),
+					move |_| {},
+				)
+				.style(|s| s.width(200)),
+				label(|| {
+					"The SHA-1 hashes from haveibeenpwned.com ordered by hash, used by the security audit"
+				})
+				.style(|s| s.width(200).color(C_MAIN_TEXT_INACTIVE)),
+			)
+				.style(|s| s.flex_col().margin_top(20).column_gap(5)),
 		)
 			.style(styles::settings_line)
 			.style(|s| s.flex_col()),
@@ -14,6 +14,7 @@ use floem::{
 
 use crate::{
 	env::Environment,
+	password_strength::{estimate, get_score_name, SCORE_NAMES},
 	ui::{
 		app_view::{ToastSignalsSettings, TooltipSignalsSettings},
 		colors::*,
@@ -21,6 +22,8 @@ use crate::{
 		primitives::{
 			button::{button, icon_button, IconButton},
 			password_field::password_field,
+			select::select,
+			strength_meter::strength_meter,
 			styles,
 		},
 	},
@@ -46,6 +49,13 @@ fn change_password(
 		toast_signals.add(String::from("New passwords do not match"));
 	} else if new_password.get().is_empty() {
 		toast_signals.add(String::from("Empty passwords are not allowed"));
+	} else if estimate(&new_password.get()).score
+		< env.config.get_min_master_strength()
+	{
+		toast_signals.add(format!(
+			"The new password is too weak, it needs to be at least \"{}\"",
+			get_score_name(env.config.get_min_master_strength())
+		));
 	} else {
 		let result = env.db.change_password(old_password.get(), new_password.get());
 		match result {
@@ -93,11 +103,15 @@ pub fn general_view() -> impl IntoView {
 	let revert_icon = include_str!("../icons/revert.svg");
 
 	let env_salt = env.clone();
+	let env_strength = env.clone();
 
 	let old_password = create_rw_signal(String::from(""));
 	let new_password = create_rw_signal(String::from(""));
 	let new_password_check = create_rw_signal(String::from(""));
 	let success = create_rw_signal(false);
+	let min_master_strength = create_rw_signal(
+		(env.config.get_min_master_strength() as usize).min(SCORE_NAMES.len() - 1),
+	);
 
 	let db_salt_letter_count_pct = convert_letter_count_2_pct(
 		env.db.config_db.read().salt_letter_count as f32,
@@ -197,6 +211,7 @@ pub fn general_view() -> impl IntoView {
 					}
 				})
 				.style(|s| s.width(250)),
+			container(strength_meter(new_password)).style(|s| s.width(250)),
 			password_field(new_password_check, "New Password Again")
 				.on_event_cont(EventListener::KeyDown, move |event| {
 					let key = match event {
@@ -228,6 +243,17 @@ pub fn general_view() -> impl IntoView {
 			})),
 		)
 			.style(|s| s.flex_col().margin_bottom(20)),
+		"Minimum strength",
+		select(
+			min_master_strength,
+			SCORE_NAMES.into_iter().enumerate().collect(),
+			move |score| {
+				if score as u8 != env_strength.config.get_min_master_strength() {
+					env_strength.config.set_min_master_strength(score as u8);
+				}
+			},
+		)
+		.style(|s| s.margin_bottom(20)),
 		"Password salt",
 		(
 			label(move || {
@@ -0,0 +1,453 @@
+use floem::{
+	event::{Event, EventListener},
+	keyboard::{KeyCode, PhysicalKey},
+	peniko::Brush,
+	reactive::{
+		create_rw_signal, use_context, RwSignal, SignalGet, SignalUpdate,
+	},
+	style::{CursorStyle, Display},
+	views::{container, dyn_container, label, slider::slider, Decorators},
+	IntoView,
+};
+
+use crate::{
+	config::{
+		Capitalization, GeneratorMode, GeneratorProfile, GeneratorProfiles,
+		PassphraseOptions,
+	},
+	env::Environment,
+	password_gen::get_profile_entropy,
+	ui::{
+		app_view::TooltipSignalsSettings,
+		colors::*,
+		keyboard::is_submit,
+		primitives::{
+			button::{icon_button, IconButton},
+			checkbox::checkbox,
+			input_field::input_field,
+			select::select,
+		},
+	},
+};
+
+const MIN: f32 = 4.0;
+const MAX: f32 = 60.0;
+const WORD_MIN: f32 = 3.0;
+const WORD_MAX: f32 = 9.0;
+
+#[derive(Debug, Copy, Clone)]
+struct ProfileDraft {
+	id: RwSignal<usize>,
+	name: RwSignal<String>,
+	mode: RwSignal<GeneratorMode>,
+	mode_idx: RwSignal<usize>,
+	length_pct: RwSignal<f32>,
+	upper: RwSignal<bool>,
+	lower: RwSignal<bool>,
+	digits: RwSignal<bool>,
+	symbols: RwSignal<bool>,
+	custom_symbols: RwSignal<String>,
+	exclude_look_alikes: RwSignal<bool>,
+	at_least_one_of_each: RwSignal<bool>,
+	word_count_pct: RwSignal<f32>,
+	separator: RwSignal<String>,
+	capitalization: RwSignal<Capitalization>,
+	capitalization_idx: RwSignal<usize>,
+	add_digit: RwSignal<bool>,
+	add_symbol: RwSignal<bool>,
+}
+
+impl ProfileDraft {
+	fn new(profile: GeneratorProfile) -> Self {
+		ProfileDraft {
+			id: create_rw_signal(profile.id),
+			name: create_rw_signal(profile.name),
+			mode_idx: create_rw_signal(get_mode_idx(&profile.mode)),
+			mode: create_rw_signal(profile.mode),
+			length_pct: create_rw_signal(convert_letter_count_2_pct(
+				profile.length as f32,
+			)),
+			upper: create_rw_signal(profile.upper),
+			lower: create_rw_signal(profile.lower),
+			digits: create_rw_signal(profile.digits),
+			symbols: create_rw_signal(profile.symbols),
+			custom_symbols: create_rw_signal(profile.custom_symbols),
+			exclude_look_alikes: create_rw_signal(profile.exclude_look_alikes),
+			at_least_one_of_each: create_rw_signal(profile.at_least_one_of_each),
+			word_count_pct: create_rw_signal(convert_word_count_2_pct(
+				profile.passphrase.word_count as f32,
+			)),
+			separator: create_rw_signal(profile.passphrase.separator),
+			capitalization_idx: create_rw_signal(get_capitalization_idx(
+				&profile.passphrase.capitalization,
+			)),
+			capitalization: create_rw_signal(profile.passphrase.capitalization),
+			add_digit: create_rw_signal(profile.passphrase.add_digit),
+			add_symbol: create_rw_signal(profile.passphrase.add_symbol),
+		}
+	}
+
+	fn load(&self, profile: GeneratorProfile) {
+		self.id.set(profile.id);
+		self.name.set(profile.name);
+		self.mode_idx.set(get_mode_idx(&profile.mode));
+		self.mode.set(profile.mode);
+		self.length_pct.set(convert_letter_count_2_pct(profile.length as f32));
+		self.upper.set(profile.upper);
+		self.lower.set(profile.lower);
+		self.digits.set(profile.digits);
+		self.symbols.set(profile.symbols);
+		self.custom_symbols.set(profile.custom_symbols);
+		self.exclude_look_alikes.set(profile.exclude_look_alikes);
+		self.at_least_one_of_each.set(profile.at_least_one_of_each);
+		self
+			.word_count_pct
+			.set(convert_word_count_2_pct(profile.passphrase.word_count as f32));
+		self.separator.set(profile.passphrase.separator);
+		self
+			.capitalization_idx
+			.set(get_capitalization_idx(&profile.passphrase.capitalization));
+		self.capitalization.set(profile.passphrase.capitalization);
+		self.add_digit.set(profile.passphrase.add_digit);
+		self.add_symbol.set(profile.passphrase.add_symbol);
+	}
+
+	fn get(&self) -> GeneratorProfile {
+		GeneratorProfile {
+			id: self.id.get(),
+			name: String::from(self.name.get().trim()),
+			mode: self.mode.get(),
+			length: convert_pct_2_letter_count(self.length_pct.get()),
+			upper: self.upper.get(),
+			lower: self.lower.get(),
+			digits: self.digits.get(),
+			symbols: self.symbols.get(),
+			custom_symbols: self.custom_symbols.get(),
+			exclude_look_alikes: self.exclude_look_alikes.get(),
+			at_least_one_of_each: self.at_least_one_of_each.get(),
+			passphrase: PassphraseOptions {
+				word_count: convert_pct_2_word_count(self.word_count_pct.get()),
+				separator: self.separator.get(),
+				capitalization: self.capitalization.get(),
+				add_digit: self.add_digit.get(),
+				add_symbol: self.add_symbol.get(),
+			},
+		}
+	}
+
+	fn is_valid(&self) -> bool {
+		!self.name.get().trim().is_empty()
+			&& (self.mode.get() == GeneratorMode::Passphrase
+				|| self.upper.get()
+				|| self.lower.get()
+				|| self.digits.get()
+				|| self.symbols.get())
+	}
+}
+
+fn convert_pct_2_letter_count(pct: f32) -> usize {
+	(((MAX / 100.0) * pct) + MIN).round() as usize
+}
+
+fn convert_letter_count_2_pct(letter_count: f32) -> f32 {
+	((letter_count - MIN) / MAX) * 100.0
+}
+
+fn convert_pct_2_word_count(pct: f32) -> usize {
+	(((WORD_MAX / 100.0) * pct) + WORD_MIN).round() as usize
+}
+
+fn convert_word_count_2_pct(word_count: f32) -> f32 {
+	((word_count - WORD_MIN) / WORD_MAX) * 100.0
+}
+
+fn get_mode_idx(mode: &GeneratorMode) -> usize {
+	GeneratorMode::all_values().iter().position(|item| item == mode).unwrap_or(0)
+}
+
+fn get_capitalization_idx(capitalization: &Capitalization) -> usize {
+	Capitalization::all_values()
+		.iter()
+		.position(|item| item == capitalization)
+		.unwrap_or(0)
+}
+
+fn length_slider(value: RwSignal<f32>) -> impl IntoView {
+	slider(move || value.get())
+		.slider_style(|s| {
+			s.handle_color(Brush::Solid(C_FOCUS))
+				.accent_bar_color(C_FOCUS.with_alpha_factor(0.5))
+				.bar_height(5)
+				.bar_color(C_FOCUS.with_alpha_factor(0.2))
+				.handle_radius(6)
+		})
+		.style(|s| s.width(241).cursor(CursorStyle::Pointer))
+		.on_change_pct(move |pct| {
+			value.set(pct);
+		})
+}
+
+fn save_profile(
+	draft: ProfileDraft,
+	profiles: RwSignal<GeneratorProfiles>,
+	env: Environment,
+) {
+	if draft.is_valid() {
+		let new_profiles = env.config.edit_generator_profile(draft.get());
+		let _ = env.config.save();
+		profiles.set(new_profiles);
+	}
+}
+
+pub fn option_line(
+	title: &'static str,
+	value: RwSignal<bool>,
+) -> impl IntoView {
+	(
+		checkbox(move || value.get()).on_update(move |state| {
+			value.set(state);
+		}),
+		label(move || title)
+			.on_click_stop(move |_| {
+				value.set(!value.get());
+			})
+			.style(|s| s.cursor(CursorStyle::Pointer).selectable(false)),
+	)
+		.style(|s| s.items_center().row_gap(5))
+}
+
+pub fn generator_view() -> impl IntoView {
+	let tooltip_signals = use_context::<TooltipSignalsSettings>()
+		.expect("No tooltip_signals context provider")
+		.inner;
+	let env = use_context::<Environment>().expect("No env context provider");
+
+	let profiles = create_rw_signal(env.config.get_generator_profiles());
+	let selected = create_rw_signal(0);
+	let draft = ProfileDraft::new(env.config.get_generator_profile(0));
+
+	let add_icon = include_str!("../icons/add.svg");
+	let delete_icon = include_str!("../icons/delete.svg");
+	let save_icon = include_str!("../icons/save.svg");
+	let revert_icon = include_str!("../icons/revert.svg");
+
+	let env_select = env.clone();
+	let env_add = env.clone();
+	let env_delete = env.clone();
+	let env_enter_save = env.clone();
+	let env_button_save = env.clone();
+
+	let is_changed = move || {
+		profiles
+			.get()
+			.iter()
+			.find(|profile| profile.id == draft.id.get())
+			.map(|profile| *profile != draft.get())
+			.unwrap_or(false)
+	};
+
+	(
+		(
+			dyn_container(
+				move || profiles.get(),
+				move |profiles| {
+					let env_select = env_select.clone();
+					select(
+						selected,
+						profiles
+							.iter()
+							.map(|profile| (profile.id, profile.name.clone()))
+							.collect(),
+						move |id| {
+							draft.load(env_select.config.get_generator_profile(id));
+						},
+					)
+					.into_any()
+				},
+			)
+			.style(|s| s.width(140)),
+			icon_button(
+				IconButton {
+					icon: String::from(add_icon),
+					tooltip: String::from("Add a new profile"),
+					tooltip_signals,
+					..IconButton::default()
+				},
+				move |_| {
+					let new_profiles =
+						env_add.config.add_generator_profile(GeneratorProfile {
+							name: String::from("New profile"),
+							..draft.get()
+						});
+					let _ = env_add.config.save();
+					let new_id =
+						new_profiles.last().map(|profile| profile.id).unwrap_or(0);
+					profiles.set(new_profiles);
+					selected.set(new_id);
+					tooltip_signals.hide();
+				},
+			),
+			container(icon_button(
+				IconButton {
+					icon: String::from(delete_icon),
+					tooltip: String::from("Delete profile"),
+					tooltip_signals,
+					..IconButton::default()
+				},
+				move |_| {
+					let new_profiles =
+						env_delete.config.delete_generator_profile(selected.get());
+					let _ = env_delete.config.save();
+					profiles.set(new_profiles);
+					selected.set(0);
+					tooltip_signals.hide();
+				},
+			))
+			.style(move |s| {
+				s.apply_if(selected.get() == 0, |s| s.display(Display::None))
+			}),
+		)
+			.style(|s| s.items_center().row_gap(5)),
+		input_field(draft.name)
+			.on_event_cont(EventListener::KeyDown, move |event| {
+				let key = match event {
+					Event::KeyDown(k) => k.key.physical_key,
+					_ => PhysicalKey::Code(KeyCode::F35),
+				};
+
+				if is_submit(key) {
+					save_profile(draft, profiles, env_enter_save.clone());
+				}
+			})
+			.style(|s| s.width(241)),
+		select(
+			draft.mode_idx,
+			GeneratorMode::all_values().into_iter().enumerate().collect(),
+			move |idx| {
+				draft.mode.set(
+					GeneratorMode::all_values().into_iter().nth(idx).unwrap_or_default(),
+				);
+			},
+		),
+		label(move || {
+			let profile = draft.get();
+			match profile.mode {
+				GeneratorMode::Characters => format!(
+					"{} characters, {:.0} bits of entropy",
+					profile.length,
+					get_profile_entropy(&profile)
+				),
+				GeneratorMode::Passphrase => format!(
+					"{} words, {:.0} bits of entropy",
+					profile.passphrase.word_count,
+					get_profile_entropy(&profile)
+				),
+			}
+		}),
+		(
+			length_slider(draft.length_pct),
+			(
+				option_line("Uppercase", draft.upper),
+				option_line("Lowercase", draft.lower),
+				option_line("Digits", draft.digits),
+				option_line("Symbols", draft.symbols),
+			)
+				.style(|s| s.row_gap(10)),
+			(
+				label(|| "Custom symbols"),
+				input_field(draft.custom_symbols).style(|s| s.width(130)),
+			)
+				.style(move |s| {
+					s.items_center()
+						.row_gap(5)
+						.apply_if(!draft.symbols.get(), |s| s.display(Display::None))
+				}),
+			option_line("Exclude look-alikes (0/O, l/1)", draft.exclude_look_alikes),
+			option_line("At least one of each class", draft.at_least_one_of_each),
+		)
+			.style(move |s| {
+				s.flex_col()
+					.column_gap(5)
+					.apply_if(draft.mode.get() != GeneratorMode::Characters, |s| {
+						s.display(Display::None)
+					})
+			}),
+		(
+			length_slider(draft.word_count_pct),
+			(
+				label(|| "Separator"),
+				input_field(draft.separator).style(|s| s.width(50)),
+			)
+				.style(|s| s.items_center().row_gap(5)),
+			(
+				label(|| "Capitalization"),
+				select(
+					draft.capitalization_idx,
+					Capitalization::all_values().into_iter().enumerate().collect(),
+					move |idx| {
+						draft.capitalization.set(
+							Capitalization::all_values()
+								.into_iter()
+								.nth(idx)
+								.unwrap_or_default(),
+						);
+					},
+				),
+			)
+				.style(|s| s.items_center().row_gap(5)),
+			option_line("Add a digit", draft.add_digit),
+			option_line("Add a symbol", draft.add_symbol),
+		)
+			.style(move |s| {
+				s.flex_col()
+					.column_gap(5)
+					.apply_if(draft.mode.get() != GeneratorMode::Passphrase, |s| {
+						s.display(Display::None)
+					})
+			}),
+		container(
+			(
+				icon_button(
+					IconButton {
+						icon: String::from(revert_icon),
+						tooltip: String::from("Reset"),
+						tooltip_signals,
+						..IconButton::default()
+					},
+					move |_| {
+						if let Some(profile) = profiles
+							.get()
+							.into_iter()
+							.find(|profile| profile.id == draft.id.get())
+						{
+							draft.load(profile);
+						}
+						tooltip_signals.hide();
+					},
+				),
+				icon_button(
+					IconButton {
+						icon: String::from(save_icon),
+						tooltip: String::from("Save profile"),
+						tooltip_signals,
+						..IconButton::default()
+					},
+					move |_| {
+						save_profile(draft, profiles, env_button_save.clone());
+						tooltip_signals.hide();
+					},
+				)
+				.style(move |s| {
+					s.apply_if(!draft.is_valid(), |s| s.display(Display::None))
+				}),
+			)
+				.style(move |s| {
+					s.row_gap(5)
+						.display(Display::Flex)
+						.apply_if(!is_changed(), |s| s.display(Display::None))
+				}),
+		)
+		.style(|s| s.height(25)),
+	)
+		.style(|s| s.flex_col().column_gap(5))
+}
@@ -0,0 +1,131 @@
+use floem::{
+	event::{Event, EventListener},
+	reactive::{create_rw_signal, provide_context, SignalGet, SignalUpdate},
+	style::Display,
+	views::{container, label, Decorators},
+	IntoView, View,
+};
+
+use crate::{
+	db::DbFields,
+	env::Environment,
+	ui::{
+		colors::*,
+		primitives::{
+			button::button,
+			que::Que,
+			select::select,
+			tooltip::{tooltip_view, TooltipSignals},
+		},
+		window_management::closing_window,
+	},
+};
+
+pub struct TransferView {
+	pub id: usize,
+	pub field: DbFields,
+	pub field_title: String,
+	pub window_id: String,
+	pub que: Que,
+	pub env: Environment,
+}
+
+pub fn transfer_view(
+	param: TransferView,
+	on_move: impl Fn() + 'static,
+) -> impl IntoView {
+	let TransferView {
+		id,
+		field,
+		field_title,
+		window_id,
+		que,
+		env,
+	} = param;
+
+	let tooltip_signals = TooltipSignals::new(que);
+	provide_context(tooltip_signals);
+
+	let targets = env
+		.db
+		.get_sidebar_list()
+		.into_iter()
+		.filter(|(entry_id, _, _)| *entry_id != id)
+		.map(|(entry_id, title, _)| (entry_id, title))
+		.collect::<Vec<(usize, String)>>();
+	let has_targets = !targets.is_empty();
+	let target =
+		create_rw_signal(targets.first().map(|(id, _)| *id).unwrap_or(0));
+
+	let env_copy = env.clone();
+	let window_id_copy = window_id.clone();
+
+	let transfer_view = (
+		label(move || format!("Copy or move \"{}\" to:", field_title))
+			.style(|s| s.text_ellipsis().max_width_full()),
+		select(target, targets, move |_| {})
+			.style(move |s| s.apply_if(!has_targets, |s| s.display(Display::None))),
+		"There are no other entries yet".style(move |s| {
+			s.color(C_MAIN_TEXT_INACTIVE)
+				.apply_if(has_targets, |s| s.display(Display::None))
+		}),
+		(
+			container(button("Copy").on_click_cont(move |_| {
+				if has_targets
+					&& env_copy.db.copy_field(&id, &field, &target.get()).is_some()
+				{
+					let _ = env_copy.db.save();
+					closing_window(window_id_copy.clone(), || ());
+				}
+			})),
+			container(button("Move").on_click_cont(move |_| {
+				if has_targets
+					&& env.db.move_field(&id, &field, &target.get()).is_some()
+				{
+					let _ = env.db.save();
+					on_move();
+					closing_window(window_id.clone(), || ());
+				}
+			})),
+		)
+			.style(move |s| {
+				s.row_gap(5).apply_if(!has_targets, |s| s.display(Display::None))
+			}),
+		tooltip_view(tooltip_signals),
+	)
+		.style(|s| {
+			s.flex_col()
+				.width_full()
+				.height_full()
+				.padding(10)
+				.column_gap(10)
+				.background(C_MAIN_BG)
+		})
+		.on_event_cont(EventListener::PointerMove, move |event| {
+			let pos = match event {
+				Event::PointerMove(p) => p.pos,
+				_ => (0.0, 0.0).into(),
+			};
+			tooltip_signals.mouse_pos.set((pos.x, pos.y));
+		})
+		.on_resize(move |event| {
+			tooltip_signals.window_size.set((event.x1, event.y1));
+		});
+
+	match std::env::var("DEBUG") {
+		Ok(_) => {
+			// for debugging the layout
+			let id = transfer_view.id();
+			transfer_view.on_event_stop(EventListener::KeyUp, move |e| {
+				if let floem::event::Event::KeyUp(e) = e {
+					if e.key.logical_key
+						== floem::keyboard::Key::Named(floem::keyboard::NamedKey::F11)
+					{
+						id.inspect();
+					}
+				}
+			})
+		},
+		Err(_) => transfer_view,
+	}
+}